@@ -34,6 +34,12 @@ pub struct Cli {
     #[clap(short = 'a', long = "auth", env = "GH_PILOT_AUTH_TOKEN")]
     #[arg(hide_env_values = true)]
     pub auth_token: Option<String>,
+    /// The Github REST API URL, e.g. `https://github.example.com/api/v3` for a Github Enterprise Server.
+    #[clap(long = "api-url", env = "GH_PILOT_API_URL")]
+    pub api_url: Option<String>,
+    /// The Github GraphQL API URL. Defaults to one derived from the REST API URL.
+    #[clap(long = "graphql-url", env = "GH_PILOT_GRAPHQL_URL")]
+    pub graphql_url: Option<String>,
     /// If set, you will not be prompted for any missing info. Useful in scripts
     #[clap(short = 'x', long = "non-interactive", env = "GH_PILOT_NON_INTERACTIVE")]
     pub non_interactive: bool,
@@ -77,6 +83,58 @@ pub enum Commands {
         #[clap(subcommand)]
         sub_command: OrganizationCommand,
     },
+    /// Replay journaled webhook deliveries through the rule set of a running Github Pilot server
+    Replay {
+        /// The base URL of the Github Pilot server
+        #[clap(short, long, env = "GH_PILOT_SERVER_URL", default_value = "http://127.0.0.1:8330")]
+        server: String,
+        /// The admin token configured on the server
+        #[clap(short, long, env = "GH_PILOT_ADMIN_TOKEN")]
+        #[arg(hide_env_values = true)]
+        token: Option<String>,
+        /// Replay a single delivery, identified by its X-GitHub-Delivery id
+        #[clap(short, long, conflicts_with_all = ["since", "until"])]
+        delivery: Option<String>,
+        /// Replay deliveries received at or after this time, e.g. 2022-09-14T00:00:00Z
+        #[clap(long)]
+        since: Option<String>,
+        /// Replay deliveries received at or before this time, e.g. 2022-09-15T00:00:00Z
+        #[clap(long)]
+        until: Option<String>,
+    },
+    /// Work with Github Pilot rule files
+    Rules {
+        #[clap(subcommand)]
+        sub_command: RulesCommand,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum RulesCommand {
+    /// Evaluate a rule set against a saved webhook payload. Shows which rules match, and what they would do, without
+    /// executing anything
+    Test(RulesTestArgs),
+    /// Check a rule set for errors and likely mistakes. If the owner and repo are given, the labels used in the rules
+    /// are checked against the repo too
+    Lint {
+        /// The rule set file, in YAML
+        #[clap(long, env = "GH_PILOT_RULESET_PATH")]
+        rules: String,
+    },
+}
+
+#[derive(Debug, Clone, Args)]
+pub struct RulesTestArgs {
+    /// The rule set file, or a directory of YAML rule files. Rules are read as YAML, unless the file has a .json
+    /// extension
+    #[clap(long, env = "GH_PILOT_RULESET_PATH")]
+    pub rules: String,
+    /// The webhook event name, i.e. the value of the X-GitHub-Event header, e.g. pull_request
+    #[clap(short, long)]
+    pub event: String,
+    /// A file containing the JSON webhook payload
+    #[clap(short, long)]
+    pub payload: String,
 }
 
 #[derive(Debug, Clone, Subcommand)]
@@ -1,4 +1,7 @@
+use chrono::{DateTime, Utc};
+use ghp_server::journal::ReplayQuery;
 use github_pilot_api::{
+    models,
     models::Label,
     provider_traits::{IssueProvider, RepoProvider},
     wrappers::{IssueId, NewLabel, RepoId},
@@ -7,7 +10,16 @@ use github_pilot_api::{
 use prompts::{autocomplete::AutocompletePrompt, text::TextPrompt, Prompt};
 
 use crate::{
-    cli_def::{Cli, Commands, IssueCommand, LabelArg, LabelCommand, OrganizationCommand, PullRequestCommand},
+    cli_def::{
+        Cli,
+        Commands,
+        IssueCommand,
+        LabelArg,
+        LabelCommand,
+        OrganizationCommand,
+        PullRequestCommand,
+        RulesCommand,
+    },
     cli_prompts::user_command::extract_github_handle,
     pilot_command::{
         assign_labels,
@@ -18,6 +30,8 @@ use crate::{
         PilotCommand,
         PilotCommand::{NoOp, Organization},
         PrCmd,
+        ReplayCmd,
+        RulesCmd,
     },
 };
 
@@ -40,6 +54,29 @@ impl Cli {
                 let owner = self.owner.clone();
                 self.to_org_cmd(owner, &sub_command).await?
             },
+            Commands::Replay {
+                server,
+                token,
+                delivery,
+                since,
+                until,
+            } => to_replay_cmd(&server, token, delivery, since, until)?,
+            Commands::Rules {
+                sub_command: RulesCommand::Test(args),
+            } => PilotCommand::Rules(RulesCmd::Test {
+                rules: args.rules,
+                event_name: args.event,
+                payload: args.payload,
+            }),
+            Commands::Rules {
+                sub_command: RulesCommand::Lint { rules },
+            } => {
+                let repo = match (&self.owner, &self.repo) {
+                    (Some(owner), Some(repo)) => Some(RepoId::new(owner, repo)),
+                    _ => None,
+                };
+                PilotCommand::Rules(RulesCmd::Lint { rules, repo })
+            },
         };
         Ok(command)
     }
@@ -340,3 +377,28 @@ async fn fetch_issue_labels(provider: &GithubProvider, id: &IssueId) -> Result<V
         .map_err(|e| format!("Failed to fetch labels for {id}: {e}"))?;
     Ok(labels)
 }
+
+fn parse_timestamp(ts: Option<String>) -> Result<Option<models::DateTime>, String> {
+    ts.map(|ts| {
+        DateTime::parse_from_rfc3339(&ts)
+            .map(|t| models::DateTime::new(t.with_timezone(&Utc)))
+            .map_err(|e| format!("😥 '{ts}' is not a valid RFC 3339 timestamp. {e}"))
+    })
+    .transpose()
+}
+
+fn to_replay_cmd(
+    server: &str,
+    token: Option<String>,
+    delivery: Option<String>,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<PilotCommand, String> {
+    let token =
+        token.ok_or_else(|| "😥 An admin token is required. Set GH_PILOT_ADMIN_TOKEN or use --token".to_string())?;
+    let query = match delivery {
+        Some(id) => ReplayQuery::delivery(id),
+        None => ReplayQuery::between(parse_timestamp(since)?, parse_timestamp(until)?),
+    };
+    ReplayCmd::new(server, &token, query).map(PilotCommand::Replay)
+}
@@ -22,6 +22,18 @@ async fn main() -> Result<(), String> {
 }
 
 fn setup_github_api(cli: &Cli) -> GithubProvider {
+    let mut provider = github_credentials(cli);
+    if let Some(url) = &cli.api_url {
+        info!("🚀 Using the Github API at {url}");
+        provider = provider.with_base_url(url);
+    }
+    if let Some(url) = &cli.graphql_url {
+        provider = provider.with_graphql_url(url);
+    }
+    provider
+}
+
+fn github_credentials(cli: &Cli) -> GithubProvider {
     match (cli.user_name.as_ref(), cli.auth_token.as_ref()) {
         (Some(u), Some(a)) => {
             info!("🚀 Ignition! Launching Github Pilot in Authenticated Mode.");
@@ -4,6 +4,8 @@ mod labels;
 mod organization;
 mod pilot_command_def;
 mod pull_request;
+mod replay;
+mod rules;
 mod user;
 
 pub use issue::IssueCmd;
@@ -11,3 +13,5 @@ pub use labels::{assign_labels, LabelCmd};
 pub use organization::{DateRange, OrganizationCmd};
 pub use pilot_command_def::PilotCommand;
 pub use pull_request::PrCmd;
+pub use replay::ReplayCmd;
+pub use rules::RulesCmd;
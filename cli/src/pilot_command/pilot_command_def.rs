@@ -7,6 +7,8 @@ use crate::pilot_command::{
     issue::IssueCmd,
     labels::LabelCmd,
     pull_request::PrCmd,
+    replay::ReplayCmd,
+    rules::RulesCmd,
     user::{run_contributor_cmd, run_user_cmd},
     OrganizationCmd,
 };
@@ -29,6 +31,10 @@ pub enum PilotCommand {
     Contributors(RepoId),
     /// Query organisations
     Organization(OrganizationCmd),
+    /// Replay journaled webhook deliveries on a Github Pilot server
+    Replay(ReplayCmd),
+    /// Work with rule files
+    Rules(RulesCmd),
     /// Do Nothing
     NoOp,
 }
@@ -43,6 +49,8 @@ impl PilotCommand {
             PilotCommand::Contributors(ref id) => run_contributor_cmd(provider, id).await,
             PilotCommand::NoOp => Ok(()),
             PilotCommand::Organization(cmd) => cmd.execute(provider).await,
+            PilotCommand::Replay(cmd) => cmd.execute().await,
+            PilotCommand::Rules(cmd) => cmd.execute(provider).await,
         }
     }
 }
@@ -0,0 +1,66 @@
+use ghp_server::journal::{ReplayQuery, ReplaySummary};
+use log::*;
+
+/// Asks a Github Pilot server to replay journaled webhook deliveries through its current rule set.
+#[derive(Debug)]
+pub struct ReplayCmd {
+    server: String,
+    token: String,
+    query: ReplayQuery,
+}
+
+impl ReplayCmd {
+    /// Returns an error if the query is empty, since the server would reject it anyway.
+    pub fn new(server: &str, token: &str, query: ReplayQuery) -> Result<Self, String> {
+        if query.is_empty() {
+            return Err("😥 Provide a delivery id, or a time range with --since and/or --until".into());
+        }
+        Ok(Self {
+            server: server.trim_end_matches('/').to_string(),
+            token: token.to_string(),
+            query,
+        })
+    }
+
+    pub async fn execute(self) -> Result<(), String> {
+        let url = format!("{}/admin/replay", self.server);
+        debug!("📓 Requesting replay from {url}: {:?}", self.query);
+        let res = reqwest::Client::new()
+            .post(url.as_str())
+            .bearer_auth(self.token.as_str())
+            .json(&self.query)
+            .send()
+            .await
+            .map_err(|e| format!("📓 Could not reach the Github Pilot server at {url}. {e}"))?;
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(format!("📓 Replay request failed ({status}). {body}"));
+        }
+        let summary: ReplaySummary = serde_json::from_str(body.as_str())
+            .map_err(|e| format!("📓 The server sent an unexpected response to the replay request. {e}"))?;
+        println!(
+            "📓 {} deliveries matched. {} replayed.",
+            summary.matched, summary.replayed
+        );
+        if !summary.skipped.is_empty() {
+            println!("📓 These deliveries could not be replayed:");
+            summary.skipped.iter().for_each(|id| println!("  {id}"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use ghp_server::journal::ReplayQuery;
+
+    use super::ReplayCmd;
+
+    #[test]
+    fn empty_queries_are_rejected() {
+        assert!(ReplayCmd::new("http://localhost:8330", "token", ReplayQuery::default()).is_err());
+        let cmd = ReplayCmd::new("http://localhost:8330/", "token", ReplayQuery::delivery("abc")).unwrap();
+        assert_eq!(cmd.server, "http://localhost:8330");
+    }
+}
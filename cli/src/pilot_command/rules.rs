@@ -0,0 +1,109 @@
+use std::path::{Path, PathBuf};
+
+use ghp_server::{
+    lint::{lint_dir, lint_yaml},
+    pub_sub::GithubEventMessage,
+    rule_set::RuleSet,
+    simulator::simulate,
+};
+use github_pilot_api::{provider_traits::RepoProvider, wrappers::RepoId, GithubEvent};
+use log::*;
+use serde::Serialize;
+
+#[derive(Debug)]
+pub enum RulesCmd {
+    /// Evaluate the rules in a rule set file against a saved webhook payload
+    Test {
+        rules: String,
+        event_name: String,
+        payload: String,
+    },
+    /// Check a rule set file for errors, optionally checking label names against a repo
+    Lint { rules: String, repo: Option<RepoId> },
+}
+
+impl RulesCmd {
+    pub async fn execute(self, provider: &dyn RepoProvider) -> Result<(), String> {
+        match self {
+            RulesCmd::Test {
+                rules,
+                event_name,
+                payload,
+            } => test_rules(rules.as_str(), event_name.as_str(), payload.as_str()),
+            RulesCmd::Lint { rules, repo } => lint_rules(provider, rules.as_str(), repo.as_ref()).await,
+        }
+    }
+}
+
+fn test_rules(rules_path: &str, event_name: &str, payload_path: &str) -> Result<(), String> {
+    let rules = load_rule_set(rules_path)?.to_rules();
+    let payload =
+        std::fs::read_to_string(payload_path).map_err(|e| format!("😥 Could not read {payload_path}. {e}"))?;
+    let event = GithubEvent::try_from_webhook_info(event_name, payload.as_str())
+        .map_err(|e| format!("😥 {payload_path} is not a valid `{event_name}` payload. {e}"))?;
+    debug!(
+        "🧪 Evaluating {} rules against {event_name} event from {payload_path}",
+        rules.len()
+    );
+    let matches = simulate(&rules, &GithubEventMessage::new(event_name, event));
+    for m in &matches {
+        println!("✅ Rule \"{}\" matched on {}", m.rule, describe(&m.predicate));
+        m.actions.iter().for_each(|a| println!("    execute: {}", describe(a)));
+        m.then_actions
+            .iter()
+            .for_each(|a| println!("    then:    {}", describe(a)));
+    }
+    println!(
+        "🧪 {} of {} rules matched the `{event_name}` event.",
+        matches.len(),
+        rules.len()
+    );
+    Ok(())
+}
+
+async fn lint_rules(provider: &dyn RepoProvider, rules_path: &str, repo: Option<&RepoId>) -> Result<(), String> {
+    let reports = if Path::new(rules_path).is_dir() {
+        lint_dir(rules_path).map_err(|e| format!("😥 Could not read the rule files in {rules_path}. {e}"))?
+    } else {
+        let source = std::fs::read_to_string(rules_path).map_err(|e| format!("😥 Could not read {rules_path}. {e}"))?;
+        vec![(PathBuf::from(rules_path), lint_yaml(source.as_str()))]
+    };
+    if repo.is_none() {
+        info!("🔍 No repo was given, so label names were not checked");
+    }
+    let mut has_errors = false;
+    let mut warnings = 0;
+    for (file, mut report) in reports {
+        if let Some(repo) = repo {
+            report
+                .check_labels(provider, repo)
+                .await
+                .map_err(|e| format!("😥 Could not fetch the labels for {repo}. {e}"))?;
+        }
+        for issue in report.issues() {
+            println!("{}: {issue}", file.display());
+        }
+        has_errors |= report.has_errors();
+        warnings += report.issues().len();
+    }
+    if has_errors {
+        return Err(format!("😥 {rules_path} has errors"));
+    }
+    println!("🔍 {rules_path} is valid. {warnings} warnings.");
+    Ok(())
+}
+
+fn load_rule_set(path: &str) -> Result<RuleSet, String> {
+    let is_json = Path::new(path).extension().map(|ext| ext == "json").unwrap_or(false);
+    let rule_set = if is_json {
+        RuleSet::from_json(path)
+    } else {
+        RuleSet::load(path)
+    };
+    rule_set.map_err(|e| format!("😥 Could not load rules from {path}. {e}"))
+}
+
+/// Renders a predicate or action as a single line of JSON, which reads much like its definition in a rules file.
+fn describe<T: Serialize + std::fmt::Debug>(item: &T) -> String {
+    serde_json::to_string(item).unwrap_or_else(|_| format!("{item:?}"))
+}
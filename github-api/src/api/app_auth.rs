@@ -0,0 +1,206 @@
+//! Authentication as a Github App.
+//!
+//! A Github App authenticates in two steps. It first signs a short-lived JSON Web Token (JWT) with its private key,
+//! and then exchanges the JWT for an access token for a single installation of the app (i.e. the org or user account
+//! the app has been installed on). Installation tokens are valid for an hour, so [`GithubAppAuth`] caches them and
+//! fetches a new one shortly before the old one expires.
+//!
+//! See <https://docs.github.com/en/apps/creating-github-apps/authenticating-with-a-github-app/about-authentication-with-a-github-app>
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::{Arc, Mutex},
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, Duration, Utc};
+use log::*;
+use openssl::{
+    hash::MessageDigest,
+    pkey::{PKey, Private},
+    sign::Signer,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{AuthToken, ClientProxy, GithubApiError};
+
+/// Github rejects JWTs that are valid for more than 10 minutes.
+const JWT_LIFETIME_SECS: i64 = 540;
+/// Allows for some clock drift between us and Github.
+const JWT_BACKDATE_SECS: i64 = 60;
+/// Installation tokens are refreshed when they have less than this long left to live.
+const TOKEN_REFRESH_MARGIN_SECS: i64 = 300;
+
+#[derive(Serialize)]
+struct JwtClaims {
+    iat: i64,
+    exp: i64,
+    iss: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct InstallationToken {
+    token: String,
+    expires_at: DateTime<Utc>,
+}
+
+impl InstallationToken {
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at - now < Duration::seconds(TOKEN_REFRESH_MARGIN_SECS)
+    }
+}
+
+#[derive(Deserialize)]
+struct Installation {
+    id: i64,
+}
+
+#[derive(Clone)]
+pub struct GithubAppAuth {
+    app_id: String,
+    key: PKey<Private>,
+    client: ClientProxy,
+    tokens: Arc<Mutex<HashMap<i64, InstallationToken>>>,
+    installations: Arc<Mutex<HashMap<String, i64>>>,
+}
+
+impl Debug for GithubAppAuth {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "GithubAppAuth(app_id: {})", self.app_id)
+    }
+}
+
+impl GithubAppAuth {
+    /// Creates a new instance from the app's id and its PEM-encoded private key, as downloaded from the app's settings
+    /// page.
+    pub fn new<S: Into<String>>(app_id: S, private_key_pem: &[u8]) -> Result<Self, GithubApiError> {
+        let key = PKey::private_key_from_pem(private_key_pem)
+            .map_err(|e| GithubApiError::AppAuthError(format!("Invalid app private key. {e}")))?;
+        Ok(Self {
+            app_id: app_id.into(),
+            key,
+            client: ClientProxy::default(),
+            tokens: Arc::new(Mutex::new(HashMap::new())),
+            installations: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    /// Sets the base URL for the app's own requests. See [`ClientProxy::with_base_url`].
+    pub fn with_base_url(&mut self, base_url: &str) {
+        self.client.with_base_url(base_url);
+    }
+
+    pub fn app_id(&self) -> &str {
+        self.app_id.as_str()
+    }
+
+    /// Creates a JWT that identifies the app itself. This is only good for calling the `/app` endpoints.
+    pub fn jwt(&self) -> Result<String, GithubApiError> {
+        self.jwt_at(Utc::now())
+    }
+
+    fn jwt_at(&self, now: DateTime<Utc>) -> Result<String, GithubApiError> {
+        let sign_err = |e: openssl::error::ErrorStack| GithubApiError::AppAuthError(format!("Could not sign JWT. {e}"));
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","typ":"JWT"}"#);
+        let claims = JwtClaims {
+            iat: now.timestamp() - JWT_BACKDATE_SECS,
+            exp: now.timestamp() + JWT_LIFETIME_SECS,
+            iss: self.app_id.clone(),
+        };
+        let claims = serde_json::to_string(&claims).map_err(|e| GithubApiError::SerializationError(e.to_string()))?;
+        let message = format!("{header}.{}", URL_SAFE_NO_PAD.encode(claims));
+        let mut signer = Signer::new(MessageDigest::sha256(), &self.key).map_err(sign_err)?;
+        signer.update(message.as_bytes()).map_err(sign_err)?;
+        let signature = signer.sign_to_vec().map_err(sign_err)?;
+        Ok(format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature)))
+    }
+
+    /// Returns an access token for the given installation of the app, fetching a new one from Github if there isn't a
+    /// cached token, or if it is about to expire.
+    pub async fn installation_token(&self, installation_id: i64) -> Result<AuthToken, GithubApiError> {
+        let cached = self.tokens.lock().unwrap().get(&installation_id).cloned();
+        if let Some(token) = cached.filter(|t| !t.needs_refresh(Utc::now())) {
+            return Ok(AuthToken::from(token.token));
+        }
+        debug!(
+            "🐙 Fetching a new access token for installation {installation_id} of app {}",
+            self.app_id
+        );
+        let client = self.app_client()?;
+        let req = client.post(format!("/app/installations/{installation_id}/access_tokens"));
+        let token = client.send::<InstallationToken>(req).await?;
+        let result = AuthToken::from(token.token.as_str());
+        self.tokens.lock().unwrap().insert(installation_id, token);
+        Ok(result)
+    }
+
+    /// Returns the id of the app installation that covers the given repository.
+    pub async fn installation_for_repo(&self, owner: &str, repo: &str) -> Result<i64, GithubApiError> {
+        let full_name = format!("{owner}/{repo}").to_lowercase();
+        if let Some(id) = self.installations.lock().unwrap().get(&full_name) {
+            return Ok(*id);
+        }
+        let client = self.app_client()?;
+        let req = client.get(format!("/repos/{owner}/{repo}/installation"), true);
+        let installation = client.send::<Installation>(req).await?;
+        self.installations.lock().unwrap().insert(full_name, installation.id);
+        Ok(installation.id)
+    }
+
+    /// A client that is authenticated as the app itself.
+    fn app_client(&self) -> Result<ClientProxy, GithubApiError> {
+        let mut client = self.client.clone();
+        client.with_bearer_auth(&format!("app {}", self.app_id), AuthToken::from(self.jwt()?));
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use chrono::{Duration, TimeZone, Utc};
+    use openssl::{hash::MessageDigest, pkey::PKey, rsa::Rsa, sign::Verifier};
+    use serde_json::Value;
+
+    use super::{GithubAppAuth, InstallationToken};
+
+    #[test]
+    fn sign_jwt() {
+        let key = PKey::from_rsa(Rsa::generate(2048).unwrap()).unwrap();
+        let auth = GithubAppAuth::new("12345", &key.private_key_to_pem_pkcs8().unwrap()).unwrap();
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap();
+        let jwt = auth.jwt_at(now).unwrap();
+        let parts = jwt.split('.').collect::<Vec<_>>();
+        assert_eq!(parts.len(), 3);
+        let header: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[0]).unwrap()).unwrap();
+        assert_eq!(header["alg"], "RS256");
+        let claims: Value = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(parts[1]).unwrap()).unwrap();
+        assert_eq!(claims["iss"], "12345");
+        assert_eq!(claims["iat"], now.timestamp() - 60);
+        assert_eq!(claims["exp"], now.timestamp() + 540);
+        let signature = URL_SAFE_NO_PAD.decode(parts[2]).unwrap();
+        let mut verifier = Verifier::new(MessageDigest::sha256(), &key).unwrap();
+        verifier
+            .update(format!("{}.{}", parts[0], parts[1]).as_bytes())
+            .unwrap();
+        assert!(verifier.verify(&signature).unwrap());
+    }
+
+    #[test]
+    fn invalid_key() {
+        assert!(GithubAppAuth::new("12345", b"not a key").is_err());
+    }
+
+    #[test]
+    fn token_refresh() {
+        let now = Utc::now();
+        let token = InstallationToken {
+            token: "ghs_abc".into(),
+            expires_at: now + Duration::minutes(60),
+        };
+        assert!(!token.needs_refresh(now));
+        assert!(!token.needs_refresh(now + Duration::minutes(54)));
+        assert!(token.needs_refresh(now + Duration::minutes(56)));
+        assert!(token.needs_refresh(now + Duration::minutes(61)));
+    }
+}
@@ -1,23 +1,76 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Instant,
+};
+
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use log::*;
-use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use reqwest::{
+    header::{CONTENT_ENCODING, CONTENT_LENGTH},
+    Client,
+    Method,
+    RequestBuilder,
+    Response,
+    StatusCode,
+};
 use serde::de::DeserializeOwned;
 
-use crate::api::{AuthToken, GithubApiError, GithubQuery, GithubQueryExec, Page};
+use crate::api::{
+    observer::{self, RequestStats},
+    rate_limit::{
+        backoff,
+        is_idempotent,
+        is_rate_limit_message,
+        rate_limited_until,
+        secondary_rate_limit_until,
+        MAX_RATE_LIMIT_WAIT,
+        MAX_RETRIES,
+    },
+    response_cache::ResponseCache,
+    AuthToken,
+    GithubApiError,
+    GithubQuery,
+    GithubQueryExec,
+    Page,
+    RateLimit,
+};
 
 pub const USER_AGENT: &str = "GithubPilot_v1.0";
 pub const BASE_URL: &str = "https://api.github.com";
+pub const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
+#[derive(Clone)]
+enum Auth {
+    /// Basic auth with a username and personal access token
+    Basic(String, AuthToken),
+    /// A bearer token, such as a Github App JWT or installation access token, and who it identifies
+    Bearer(String, AuthToken),
+}
 
 #[derive(Clone)]
 pub struct ClientProxy {
     client: Client,
-    auth: Option<(String, AuthToken)>,
+    auth: Option<Auth>,
+    base_url: String,
+    graphql_url: String,
+    /// The most recent API quota for each identity (see [`ClientProxy::identity`])
+    rate_limits: Arc<Mutex<HashMap<String, RateLimit>>>,
+    cache: Arc<ResponseCache>,
 }
 
 impl Default for ClientProxy {
     fn default() -> Self {
         let client = Client::builder().user_agent(USER_AGENT).build().unwrap(); // FIXME! Deal with this
-        ClientProxy { client, auth: None }
+        ClientProxy {
+            client,
+            auth: None,
+            base_url: BASE_URL.into(),
+            graphql_url: GRAPHQL_URL.into(),
+            rate_limits: Arc::new(Mutex::new(HashMap::new())),
+            cache: Arc::new(ResponseCache::default()),
+        }
     }
 }
 
@@ -29,18 +82,59 @@ impl ClientProxy {
     }
 
     pub fn with_auth(&mut self, user: &str, auth: AuthToken) {
-        self.auth = Some((user.into(), auth));
+        self.auth = Some(Auth::Basic(user.into(), auth));
+    }
+
+    /// Authenticates with a bearer token. `identity` says who the token belongs to, e.g. `installation 42`, and should
+    /// stay the same when the token is refreshed.
+    pub fn with_bearer_auth(&mut self, identity: &str, token: AuthToken) {
+        self.auth = Some(Auth::Bearer(identity.into(), token));
+    }
+
+    /// Who requests are authenticated as: the username, the identity given to [`ClientProxy::with_bearer_auth`], or
+    /// `anonymous`. Github gives each identity its own quota, so the quota is tracked separately for each of them.
+    pub fn identity(&self) -> &str {
+        match &self.auth {
+            Some(Auth::Basic(user, _)) => user.as_str(),
+            Some(Auth::Bearer(identity, _)) => identity.as_str(),
+            None => "anonymous",
+        }
+    }
+
+    /// Sets the base URL for REST API requests, e.g. `https://github.example.com/api/v3` for a Github Enterprise
+    /// Server. The GraphQL endpoint is derived from it (`https://github.example.com/api/graphql` in this case); use
+    /// [`ClientProxy::with_graphql_url`] afterwards if yours is somewhere else.
+    pub fn with_base_url(&mut self, base_url: &str) {
+        let base_url = base_url.trim_end_matches('/');
+        self.graphql_url = match base_url.strip_suffix("/v3") {
+            Some(api_root) => format!("{api_root}/graphql"),
+            None => format!("{base_url}/graphql"),
+        };
+        self.base_url = base_url.into();
+    }
+
+    pub fn with_graphql_url(&mut self, graphql_url: &str) {
+        self.graphql_url = graphql_url.into();
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_str()
+    }
+
+    pub fn graphql_url(&self) -> &str {
+        self.graphql_url.as_str()
     }
 
     pub fn apply_auth(&self, request: RequestBuilder) -> RequestBuilder {
         match &self.auth {
-            Some((user, auth)) => request.basic_auth(user, Some(auth)),
+            Some(Auth::Basic(user, auth)) => request.basic_auth(user, Some(auth)),
+            Some(Auth::Bearer(_, token)) => request.bearer_auth(token),
             None => request,
         }
     }
 
     pub fn request<S: AsRef<str>>(&self, method: Method, path: S, auth: bool) -> RequestBuilder {
-        let url = [BASE_URL, path.as_ref()].concat();
+        let url = [self.base_url.as_str(), path.as_ref()].concat();
         let request = self.client.request(method, url);
 
         if auth {
@@ -58,6 +152,11 @@ impl ClientProxy {
         self.request(Method::POST, url, true)
     }
 
+    /// An authenticated POST request to the GraphQL endpoint.
+    pub fn graphql(&self) -> RequestBuilder {
+        self.apply_auth(self.client.post(self.graphql_url.as_str()))
+    }
+
     pub fn delete<S: AsRef<str>>(&self, url: S) -> RequestBuilder {
         self.request(Method::DELETE, url, true)
     }
@@ -70,11 +169,125 @@ impl ClientProxy {
         self.request(Method::PATCH, url, true)
     }
 
+    /// The API quota for this client's [identity](ClientProxy::identity), as reported in the most recent response
+    /// from Github. Clones of this client share the quota, as long as they authenticate as the same identity.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.rate_limits.lock().unwrap().get(self.identity()).cloned()
+    }
+
+    /// The number of responses held in the conditional request cache. Clones of this client share the cache.
+    pub fn cached_responses(&self) -> usize {
+        self.cache.len()
+    }
+
+    /// Sends the request, keeping track of the API quota.
+    ///
+    /// `GET` requests are made conditional if an earlier response to the same request, made as the same
+    /// [identity](ClientProxy::identity), is cached, and a `304 Not Modified` response is replaced with the cached one
+    /// (see [`ResponseCache`]).
+    ///
+    /// Idempotent requests (`GET`, `HEAD`, `PUT` and `DELETE`) that fail with a 5xx error are retried up to
+    /// [`MAX_RETRIES`] times, with exponential backoff. Other requests, like posting a comment, may already have taken
+    /// effect when Github reports an error, so they are not retried.
+    ///
+    /// Requests that hit a rate limit are retried once the limit resets, if that is within [`MAX_RATE_LIMIT_WAIT`].
+    /// Secondary rate limits that don't say when they reset are waited out for a minute the first time, and for twice
+    /// as long every time after that (see [`crate::api::rate_limit`]). Otherwise, or if the limit is still in effect
+    /// after the last retry, [`GithubApiError::RateLimited`] is returned. Requests with streaming bodies can't be
+    /// retried, and are only sent once.
+    pub async fn execute(&self, request: RequestBuilder) -> Result<Response, GithubApiError> {
+        match ResponseCache::key(self.identity(), &request) {
+            Some(key) => {
+                let request = self.cache.add_validators(&key, request);
+                let response = self.execute_with_retries(request).await?;
+                self.cache.handle_response(key, response).await
+            },
+            None => self.execute_with_retries(request).await,
+        }
+    }
+
+    async fn execute_with_retries(&self, request: RequestBuilder) -> Result<Response, GithubApiError> {
+        let idempotent = request
+            .try_clone()
+            .and_then(|r| r.build().ok())
+            .map(|r| is_idempotent(r.method()))
+            .unwrap_or(false);
+        let mut attempt = 0;
+        let last_attempt = loop {
+            let retry = if attempt < MAX_RETRIES {
+                request.try_clone()
+            } else {
+                None
+            };
+            let this_attempt = match retry {
+                Some(req) => req,
+                None => break request,
+            };
+            let response = self.send_once(this_attempt).await?;
+            let status = response.status();
+            let now = Utc::now();
+            let (response, limited) = check_rate_limit(response, now, attempt).await?;
+            let wait = if let Some(until) = limited {
+                let wait = (until - now).to_std().unwrap_or_default();
+                if wait > MAX_RATE_LIMIT_WAIT {
+                    warn!("Github rate limit exceeded. The quota resets at {until}");
+                    return Err(GithubApiError::RateLimited(until));
+                }
+                wait
+            } else if status.is_server_error() && idempotent {
+                backoff(attempt)
+            } else {
+                return Ok(response);
+            };
+            attempt += 1;
+            info!(
+                "Github responded with {status}. Retrying in {}s (attempt {attempt} of {MAX_RETRIES})",
+                wait.as_secs()
+            );
+            tokio::time::sleep(wait).await;
+        };
+        let response = self.send_once(last_attempt).await?;
+        match check_rate_limit(response, Utc::now(), attempt).await? {
+            (_, Some(until)) => {
+                warn!("Github rate limit exceeded. The quota resets at {until}");
+                Err(GithubApiError::RateLimited(until))
+            },
+            (response, None) => Ok(response),
+        }
+    }
+
+    async fn send_once(&self, request: RequestBuilder) -> Result<Response, GithubApiError> {
+        let started = Instant::now();
+        let response = request.send().await.map_err(|e| {
+            observer::notify(&RequestStats {
+                status: None,
+                duration: started.elapsed(),
+                identity: self.identity(),
+                rate_limit: None,
+            });
+            GithubApiError::HttpClientError(e.to_string())
+        })?;
+        let quota = RateLimit::from_headers(response.headers());
+        observer::notify(&RequestStats {
+            status: Some(response.status()),
+            duration: started.elapsed(),
+            identity: self.identity(),
+            rate_limit: quota.as_ref(),
+        });
+        if let Some(quota) = quota {
+            trace!(
+                "Github API quota for {}: {} of {} remaining",
+                self.identity(),
+                quota.remaining,
+                quota.limit
+            );
+            self.rate_limits.lock().unwrap().insert(self.identity().into(), quota);
+        }
+        Ok(response)
+    }
+
     pub async fn send<T: DeserializeOwned>(&self, request: RequestBuilder) -> Result<T, GithubApiError> {
-        let response = request
-            .send()
-            .await
-            .map_err(|e| GithubApiError::HttpClientError(e.to_string()))?;
+        let response = self.execute(request).await?;
         trace!("Response status: {}", response.status());
         trace!("Response headers: {:?}", response.headers());
         match response.status() {
@@ -132,17 +345,12 @@ impl ClientProxy {
                 .try_clone()
                 .ok_or_else(|| GithubApiError::HttpClientError("Could not clone request".into()))?
                 .query(&q);
-            let response = match request.send().await {
+            let response = match self.execute(request).await {
                 Ok(res) => res,
                 Err(e) => {
-                    let url = e.url().map(|u| u.as_str()).unwrap_or_default();
-                    warn!("Error fetching paged result. {e}. The request was {url}");
+                    warn!("Error fetching page {page} of paged result. {e}");
                     // If we already have results, return what we have
-                    return if result.is_empty() {
-                        Err(GithubApiError::HttpClientError(e.to_string()))
-                    } else {
-                        Ok(result)
-                    };
+                    return if result.is_empty() { Err(e) } else { Ok(result) };
                 },
             };
             match response.json::<Vec<T>>().await {
@@ -173,6 +381,36 @@ impl ClientProxy {
     }
 }
 
+/// Returns the response, and if it was rejected by a rate limit, the time after which the request can be retried. A 403
+/// whose headers don't mention a rate limit may still be a secondary rate limit, so its body is read to check, and the
+/// response is rebuilt around it.
+async fn check_rate_limit(
+    response: Response,
+    now: DateTime<Utc>,
+    attempt: u32,
+) -> Result<(Response, Option<DateTime<Utc>>), GithubApiError> {
+    let status = response.status();
+    if let Some(until) = rate_limited_until(status, response.headers(), now, attempt) {
+        return Ok((response, Some(until)));
+    }
+    if status != StatusCode::FORBIDDEN {
+        return Ok((response, None));
+    }
+    let mut headers = response.headers().clone();
+    // The body has already been decoded, and its length may change when it is rebuilt
+    headers.remove(CONTENT_ENCODING);
+    headers.remove(CONTENT_LENGTH);
+    let body = response
+        .bytes()
+        .await
+        .map_err(|e| GithubApiError::HttpClientError(e.to_string()))?;
+    let limited = is_rate_limit_message(&body).then(|| secondary_rate_limit_until(now, attempt));
+    let mut rebuilt = http::Response::new(body);
+    *rebuilt.status_mut() = status;
+    *rebuilt.headers_mut() = headers;
+    Ok((Response::from(rebuilt), limited))
+}
+
 #[async_trait]
 impl GithubQueryExec for ClientProxy {
     type Error = GithubApiError;
@@ -182,3 +420,30 @@ impl GithubQueryExec for ClientProxy {
         self.send::<T>(req).await
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::ClientProxy;
+
+    #[test]
+    fn base_urls() {
+        let mut proxy = ClientProxy::default();
+        assert_eq!(proxy.base_url(), "https://api.github.com");
+        assert_eq!(proxy.graphql_url(), "https://api.github.com/graphql");
+        proxy.with_base_url("https://github.example.com/api/v3/");
+        assert_eq!(proxy.base_url(), "https://github.example.com/api/v3");
+        assert_eq!(proxy.graphql_url(), "https://github.example.com/api/graphql");
+        let req = proxy.get("/repos/tari-project/tari", false).build().unwrap();
+        assert_eq!(
+            req.url().as_str(),
+            "https://github.example.com/api/v3/repos/tari-project/tari"
+        );
+        proxy.with_base_url("http://127.0.0.1:8080");
+        assert_eq!(proxy.graphql_url(), "http://127.0.0.1:8080/graphql");
+        proxy.with_graphql_url("http://127.0.0.1:8081/graphql");
+        assert_eq!(
+            proxy.graphql().build().unwrap().url().as_str(),
+            "http://127.0.0.1:8081/graphql"
+        );
+    }
+}
@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 
+use chrono::{DateTime, Utc};
 use reqwest::StatusCode;
 use thiserror::Error;
 
@@ -20,6 +21,8 @@ pub enum GithubApiError {
     NotFound(String),
     #[error("HTTP Code response. {0}")]
     HttpResponse(StatusCode),
+    #[error("The Github API rate limit was exceeded. Requests can be made again after {0}")]
+    RateLimited(DateTime<Utc>),
     #[error("Could not parse {0} as a valid timestamp")]
     InvalidTimestamp(String),
     #[error("Multiple errors occurred: {0:?}")]
@@ -30,6 +33,8 @@ pub enum GithubApiError {
     GraphQLError(String),
     #[error("The PR could not be merged: {0}")]
     MergeError(String),
+    #[error("Could not authenticate as a Github App. {0}")]
+    AppAuthError(String),
 }
 
 #[derive(Clone)]
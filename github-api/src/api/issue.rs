@@ -1,8 +1,9 @@
+use reqwest::{RequestBuilder, StatusCode};
 use serde::Serialize;
 
 use crate::{
     api::{pagination::Page, ClientProxy, GithubApiError},
-    models::{Issue, IssueComment, Label},
+    models::{Issue, IssueComment, Label, LockReason, State, StateReason},
     wrappers::IssueId,
 };
 
@@ -24,6 +25,31 @@ impl From<&[&str]> for LabelBody {
     }
 }
 
+#[derive(Serialize)]
+struct AssigneesBody {
+    assignees: Vec<String>,
+}
+
+impl From<&[&str]> for AssigneesBody {
+    fn from(src: &[&str]) -> Self {
+        let assignees = src.iter().map(|&s| s.to_string()).collect();
+        Self { assignees }
+    }
+}
+
+#[derive(Serialize)]
+struct StateBody {
+    state: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_reason: Option<StateReason>,
+}
+
+#[derive(Serialize)]
+struct LockBody {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lock_reason: Option<LockReason>,
+}
+
 #[derive(Serialize)]
 struct NewCommentBody {
     body: String,
@@ -56,6 +82,14 @@ impl IssueRequest {
         format!("/repos/{}/{}/issues/{}/comments", self.owner, self.repo, self.number)
     }
 
+    fn assignees_path(&self) -> String {
+        format!("/repos/{}/{}/issues/{}/assignees", self.owner, self.repo, self.number)
+    }
+
+    fn lock_path(&self) -> String {
+        format!("/repos/{}/{}/issues/{}/lock", self.owner, self.repo, self.number)
+    }
+
     fn remove_label_path(&self, label: &str) -> String {
         format!(
             "/repos/{}/{}/issues/{}/labels/{label}",
@@ -85,6 +119,65 @@ impl IssueRequest {
         proxy.send(req).await
     }
 
+    /// Adds the users to the issue's assignees. Users that are already assigned are left alone. Github silently
+    /// ignores users that do not have permission to be assigned to the issue.
+    pub async fn add_assignees(&self, assignees: &[&str], proxy: &ClientProxy) -> Result<Issue, GithubApiError> {
+        let body = AssigneesBody::from(assignees);
+        let body = serde_json::to_string(&body).map_err(|e| GithubApiError::SerializationError(e.to_string()))?;
+        let req = proxy.post(self.assignees_path().as_str()).body(body);
+        proxy.send(req).await
+    }
+
+    pub async fn remove_assignees(&self, assignees: &[&str], proxy: &ClientProxy) -> Result<Issue, GithubApiError> {
+        let body = AssigneesBody::from(assignees);
+        let body = serde_json::to_string(&body).map_err(|e| GithubApiError::SerializationError(e.to_string()))?;
+        let req = proxy.delete(self.assignees_path().as_str()).body(body);
+        proxy.send(req).await
+    }
+
+    /// Closes or reopens the issue. Pull requests can also be closed and reopened this way, but Github ignores the
+    /// state reason for them.
+    pub async fn set_state(
+        &self,
+        state: State,
+        reason: Option<StateReason>,
+        proxy: &ClientProxy,
+    ) -> Result<Issue, GithubApiError> {
+        let state = match state {
+            State::Open => "open",
+            State::Closed => "closed",
+        };
+        let body = StateBody {
+            state,
+            state_reason: reason,
+        };
+        let req = proxy.patch(self.fetch_path().as_str()).json(&body);
+        proxy.send(req).await
+    }
+
+    /// Locks the conversation on the issue (or pull request), so that only collaborators can comment on it.
+    pub async fn lock(&self, reason: Option<LockReason>, proxy: &ClientProxy) -> Result<(), GithubApiError> {
+        let body = LockBody { lock_reason: reason };
+        let req = proxy.put(self.lock_path().as_str()).json(&body);
+        Self::send_no_content(proxy, req).await
+    }
+
+    pub async fn unlock(&self, proxy: &ClientProxy) -> Result<(), GithubApiError> {
+        let req = proxy.delete(self.lock_path().as_str());
+        Self::send_no_content(proxy, req).await
+    }
+
+    async fn send_no_content(proxy: &ClientProxy, req: RequestBuilder) -> Result<(), GithubApiError> {
+        let res = proxy.execute(req).await?;
+        match res.status() {
+            s if s.is_success() => Ok(()),
+            StatusCode::NOT_FOUND => Err(GithubApiError::NotFound(
+                res.text().await.unwrap_or_else(|_| "Not found".into()),
+            )),
+            code => Err(GithubApiError::HttpResponse(code)),
+        }
+    }
+
     pub async fn fetch_comments(&self, proxy: &ClientProxy, page: Page) -> Result<Vec<IssueComment>, GithubApiError> {
         let q = page.to_query();
         let req = proxy.get(self.comment_path().as_str(), false).query(&q);
@@ -1,21 +1,29 @@
+mod app_auth;
 mod auth;
 mod client_proxy;
 mod error;
 mod issue;
+mod observer;
 mod organization;
 mod pagination;
 mod profile;
 mod pull_request;
 mod query_trait;
+mod rate_limit;
 mod repo_request;
+mod response_cache;
 
+pub use app_auth::GithubAppAuth;
 pub use auth::AuthToken;
 pub use client_proxy::ClientProxy;
 pub use error::GithubApiError;
 pub use issue::IssueRequest;
+pub use observer::{set_request_observer, RequestObserver, RequestStats};
 pub use organization::OrganizationRequest;
 pub use pagination::Page;
 pub use profile::UserRequest;
 pub use pull_request::PullRequestRequest;
 pub use query_trait::{GithubQuery, GithubQueryExec};
+pub use rate_limit::RateLimit;
 pub use repo_request::RepoRequest;
+pub use response_cache::ResponseCache;
@@ -0,0 +1,38 @@
+//! A hook for watching the requests that are made to Github, e.g. to export metrics about them.
+//!
+//! The observer is global, since clients are created (and cloned) all over the place, and it is set once, at startup.
+use std::time::Duration;
+
+use once_cell::sync::OnceCell;
+use reqwest::StatusCode;
+
+use crate::api::RateLimit;
+
+/// What happened when a request was sent to Github.
+#[derive(Debug)]
+pub struct RequestStats<'a> {
+    /// The response status, or `None` if no response was received
+    pub status: Option<StatusCode>,
+    /// How long it took to receive the response (or to give up)
+    pub duration: Duration,
+    /// Who the request was authenticated as (see [`crate::api::ClientProxy::identity`])
+    pub identity: &'a str,
+    /// The API quota, if the response reported it
+    pub rate_limit: Option<&'a RateLimit>,
+}
+
+pub type RequestObserver = fn(&RequestStats<'_>);
+
+static OBSERVER: OnceCell<RequestObserver> = OnceCell::new();
+
+/// Sets the function that is called after every request to Github, including retries. Returns false if an observer
+/// had already been set, in which case it is left in place.
+pub fn set_request_observer(observer: RequestObserver) -> bool {
+    OBSERVER.set(observer).is_ok()
+}
+
+pub(crate) fn notify(stats: &RequestStats<'_>) {
+    if let Some(observer) = OBSERVER.get() {
+        observer(stats);
+    }
+}
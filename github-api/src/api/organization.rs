@@ -48,7 +48,7 @@ impl OrganizationRequest {
             after,
         };
         let body = OrgActivityQL::build_query(vars);
-        let req = proxy.post("/graphql").json(&body);
+        let req = proxy.graphql().json(&body);
         let response: Response<org_activity_ql::ResponseData> = proxy.send(req).await?;
         if let Some(data) = response.data {
             let result = OrgActivitySearch::from(data);
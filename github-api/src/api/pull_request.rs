@@ -1,20 +1,37 @@
 use graphql_client::{GraphQLQuery, Response};
 use reqwest::StatusCode;
+use serde::Serialize;
 
 use crate::{
     api::{ClientProxy, GithubApiError, IssueRequest},
     graphql::{
+        convert_to_draft::{convert_to_draft_ql, ConvertToDraftQL},
         pr_comments::{pull_request_comments_ql, PullRequestCommentsQL},
         review_counts::{pull_request_review_counts_ql, PullRequestReviewCountsQL, ReviewCounts},
         run_status::{check_run_status_ql, CheckRunStatusQL},
         CheckRunStatus,
         PullRequestComments,
     },
-    models::{IssueComment, Label, PullRequest},
+    models::{IssueComment, Label, PullRequest, PullRequestFile},
     models_plus::{MergeParameters, MergeResult, MergeValidationError},
     wrappers::IssueId,
 };
 
+#[derive(Serialize)]
+struct ReviewersBody {
+    reviewers: Vec<String>,
+    team_reviewers: Vec<String>,
+}
+
+impl ReviewersBody {
+    fn new(reviewers: &[&str], team_reviewers: &[&str]) -> Self {
+        Self {
+            reviewers: reviewers.iter().map(|&s| s.to_string()).collect(),
+            team_reviewers: team_reviewers.iter().map(|&s| s.to_string()).collect(),
+        }
+    }
+}
+
 pub struct PullRequestRequest {
     owner: String,
     repo: String,
@@ -78,7 +95,7 @@ impl PullRequestRequest {
             pr_number: self.pull as i64,
         };
         let body = PullRequestCommentsQL::build_query(vars);
-        let req = proxy.post("/graphql").json(&body);
+        let req = proxy.graphql().json(&body);
         let response: Response<pull_request_comments_ql::ResponseData> = proxy.send(req).await?;
         if let Some(data) = response.data {
             Ok(data.into())
@@ -107,6 +124,69 @@ impl PullRequestRequest {
         issue.add_comment(comment, proxy).await
     }
 
+    /// Fetches the list of files changed by the pull request. Github caps this list at 3000 files.
+    pub async fn fetch_files(&self, proxy: &ClientProxy) -> Result<Vec<PullRequestFile>, GithubApiError> {
+        let url = format!("{}/files", self.url);
+        let req = proxy.get(url.as_str(), false);
+        proxy.fetch_pages(req, |_| true, 100).await
+    }
+
+    /// Converts the pull request to a draft. The REST API does not support this, so it is done with GraphQL, which
+    /// needs the PR's node id. Returns the draft status of the PR after the mutation.
+    pub async fn convert_to_draft(&self, node_id: &str, proxy: &ClientProxy) -> Result<bool, GithubApiError> {
+        let vars = convert_to_draft_ql::Variables {
+            pr_id: node_id.to_string(),
+        };
+        let body = ConvertToDraftQL::build_query(vars);
+        let req = proxy.graphql().json(&body);
+        let response: Response<convert_to_draft_ql::ResponseData> = proxy.send(req).await?;
+        match (response.data, response.errors) {
+            (_, Some(errs)) if !errs.is_empty() => Err(GithubApiError::GraphQLError(
+                errs.into_iter()
+                    .map(|e| e.to_string())
+                    .collect::<Vec<String>>()
+                    .join("; "),
+            )),
+            (Some(data), _) => Ok(data
+                .convert_pull_request_to_draft
+                .and_then(|d| d.pull_request)
+                .map(|pr| pr.is_draft)
+                .unwrap_or_default()),
+            (None, _) => Err(GithubApiError::DeserializationError(
+                "No data came back in the convert to draft response".into(),
+            )),
+        }
+    }
+
+    fn requested_reviewers_path(&self) -> String {
+        format!("{}/requested_reviewers", self.url)
+    }
+
+    /// Requests reviews from the given users and/or teams. Teams are given by their slug, without the organization
+    /// prefix.
+    pub async fn request_reviewers(
+        &self,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+        proxy: &ClientProxy,
+    ) -> Result<PullRequest, GithubApiError> {
+        let body = ReviewersBody::new(reviewers, team_reviewers);
+        let req = proxy.post(self.requested_reviewers_path().as_str()).json(&body);
+        proxy.send(req).await
+    }
+
+    /// Removes review requests for the given users and/or teams.
+    pub async fn remove_requested_reviewers(
+        &self,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+        proxy: &ClientProxy,
+    ) -> Result<PullRequest, GithubApiError> {
+        let body = ReviewersBody::new(reviewers, team_reviewers);
+        let req = proxy.delete(self.requested_reviewers_path().as_str()).json(&body);
+        proxy.send(req).await
+    }
+
     pub async fn fetch_review_counts(&self, proxy: &ClientProxy) -> Result<ReviewCounts, GithubApiError> {
         let vars = pull_request_review_counts_ql::Variables {
             owner: self.owner.clone(),
@@ -114,7 +194,7 @@ impl PullRequestRequest {
             pr_number: self.pull as i64,
         };
         let body = PullRequestReviewCountsQL::build_query(vars);
-        let req = proxy.post("/graphql").json(&body);
+        let req = proxy.graphql().json(&body);
         let response: Response<pull_request_review_counts_ql::ResponseData> = proxy.send(req).await?;
         if let Some(data) = response.data {
             Ok(data.into())
@@ -140,7 +220,7 @@ impl PullRequestRequest {
             pr_number: self.pull as i64,
         };
         let body = CheckRunStatusQL::build_query(vars);
-        let req = proxy.post("/graphql").json(&body);
+        let req = proxy.graphql().json(&body);
         let response: Response<check_run_status_ql::ResponseData> = proxy.send(req).await?;
         if let Some(data) = response.data {
             Ok(data.into())
@@ -163,10 +243,7 @@ impl PullRequestRequest {
         let url = format!("{}/merge", self.url);
         let req = proxy.put(url.as_str()).json(&params);
 
-        let response = req
-            .send()
-            .await
-            .map_err(|e| GithubApiError::HttpClientError(e.to_string()))?;
+        let response = proxy.execute(req).await?;
         match response.status() {
             StatusCode::OK => {
                 let r: MergeResult = response
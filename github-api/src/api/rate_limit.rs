@@ -0,0 +1,195 @@
+//! Github's rate limit headers, and the retry policy that [`crate::api::ClientProxy`] follows when it hits a limit.
+//!
+//! Github has a _primary_ rate limit (a number of requests per hour), and _secondary_ rate limits that kick in when
+//! too many requests are made in a short time. Exceeding either returns a 403 or 429. Primary rate limit responses
+//! have `x-ratelimit-remaining: 0` and say when the quota resets in `x-ratelimit-reset`, while secondary rate limit
+//! responses usually say how long to wait in `retry-after`. When they don't, Github asks clients to wait at least a
+//! minute, and longer if the limit persists. A 403 secondary rate limit response without `retry-after` can only be
+//! told apart from a permissions error by its message (see [`is_rate_limit_message`]).
+//!
+//! See <https://docs.github.com/en/rest/using-the-rest-api/rate-limits-for-the-rest-api>
+use std::time::Duration;
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::{header::HeaderMap, Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// The number of times a request is retried after a 5xx response (for idempotent requests) or a short rate limit wait.
+pub const MAX_RETRIES: u32 = 3;
+/// Rate limits that reset further in the future than this are reported as [`crate::api::GithubApiError::RateLimited`]
+/// rather than waited out.
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+const BASE_BACKOFF: Duration = Duration::from_secs(1);
+/// How long to wait after a secondary rate limit that doesn't say how long to wait, the first time it happens.
+pub const SECONDARY_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// The API quota, as reported by the most recent response from Github.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// The maximum number of requests allowed per hour
+    pub limit: u32,
+    /// The number of requests left in the current window
+    pub remaining: u32,
+    /// When the current window resets
+    pub reset: DateTime<Utc>,
+}
+
+impl RateLimit {
+    /// Reads the `x-ratelimit-*` headers from a response. Returns `None` if any of them are missing or invalid.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let limit = header_value(headers, "x-ratelimit-limit")?;
+        let remaining = header_value(headers, "x-ratelimit-remaining")?;
+        let reset = header_value::<i64>(headers, "x-ratelimit-reset")?;
+        Some(Self {
+            limit,
+            remaining,
+            reset: Utc.timestamp_opt(reset, 0).single()?,
+        })
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+}
+
+fn header_value<T: std::str::FromStr>(headers: &HeaderMap, name: &str) -> Option<T> {
+    headers.get(name)?.to_str().ok()?.trim().parse().ok()
+}
+
+/// If the response was rejected because of a rate limit, returns the time after which the request can be retried.
+///
+/// A 429 that says neither when to retry nor that the quota is exhausted is a secondary rate limit, and the wait
+/// doubles with every `attempt` (see [`secondary_backoff`]). A 403 like that is only a rate limit if its message says
+/// so, and the caller should check with [`is_rate_limit_message`].
+pub fn rate_limited_until(
+    status: StatusCode,
+    headers: &HeaderMap,
+    now: DateTime<Utc>,
+    attempt: u32,
+) -> Option<DateTime<Utc>> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+    if let Some(secs) = header_value::<i64>(headers, "retry-after") {
+        return Some(now + chrono::Duration::seconds(secs));
+    }
+    match RateLimit::from_headers(headers) {
+        Some(quota) if quota.is_exhausted() => Some(quota.reset.max(now)),
+        _ if status == StatusCode::TOO_MANY_REQUESTS => Some(secondary_rate_limit_until(now, attempt)),
+        _ => None,
+    }
+}
+
+/// When to retry after a secondary rate limit that didn't say how long to wait.
+pub fn secondary_rate_limit_until(now: DateTime<Utc>, attempt: u32) -> DateTime<Utc> {
+    now + chrono::Duration::seconds(secondary_backoff(attempt).as_secs() as i64)
+}
+
+/// How long to wait after a secondary rate limit that didn't say how long to wait. Doubles with every attempt.
+pub fn secondary_backoff(attempt: u32) -> Duration {
+    SECONDARY_RATE_LIMIT_WAIT.saturating_mul(2u32.saturating_pow(attempt))
+}
+
+/// Whether the body of a 403 response says that a rate limit was exceeded, rather than that access was denied.
+pub fn is_rate_limit_message(body: &[u8]) -> bool {
+    String::from_utf8_lossy(body).to_lowercase().contains("rate limit")
+}
+
+/// How long to wait before retrying a request that failed with a server error. Doubles with every attempt.
+pub fn backoff(attempt: u32) -> Duration {
+    BASE_BACKOFF * 2u32.pow(attempt)
+}
+
+/// Whether a request with this method can be sent again after a server error without risking a duplicate change.
+pub fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE)
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use chrono::{TimeZone, Utc};
+    use reqwest::{header::HeaderMap, Method, StatusCode};
+
+    use super::{backoff, is_idempotent, is_rate_limit_message, rate_limited_until, secondary_backoff, RateLimit};
+
+    fn headers(values: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        values.iter().for_each(|(k, v)| {
+            headers.insert(*k, v.parse().unwrap());
+        });
+        headers
+    }
+
+    #[test]
+    fn parse_rate_limit() {
+        let h = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4987"),
+            ("x-ratelimit-reset", "1677672000"),
+        ]);
+        let quota = RateLimit::from_headers(&h).unwrap();
+        assert_eq!(quota.limit, 5000);
+        assert_eq!(quota.remaining, 4987);
+        assert_eq!(quota.reset, Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap());
+        assert!(!quota.is_exhausted());
+        assert!(RateLimit::from_headers(&headers(&[("x-ratelimit-limit", "5000")])).is_none());
+    }
+
+    #[test]
+    fn detect_rate_limits() {
+        let now = Utc.with_ymd_and_hms(2023, 3, 1, 11, 59, 0).unwrap();
+        let secondary = headers(&[("retry-after", "30")]);
+        let until = rate_limited_until(StatusCode::FORBIDDEN, &secondary, now, 0).unwrap();
+        assert_eq!(until, now + chrono::Duration::seconds(30));
+        assert!(rate_limited_until(StatusCode::OK, &secondary, now, 0).is_none());
+
+        let primary = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", "1677672000"),
+        ]);
+        let until = rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &primary, now, 0).unwrap();
+        assert_eq!(until, Utc.with_ymd_and_hms(2023, 3, 1, 12, 0, 0).unwrap());
+
+        let forbidden = headers(&[
+            ("x-ratelimit-limit", "5000"),
+            ("x-ratelimit-remaining", "4000"),
+            ("x-ratelimit-reset", "1677672000"),
+        ]);
+        // A 403 with quota left may just be a permissions error, so the caller has to check the message
+        assert!(rate_limited_until(StatusCode::FORBIDDEN, &forbidden, now, 0).is_none());
+        // ..but a 429 is always a rate limit, and the wait doubles with each attempt
+        let until = rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &forbidden, now, 0).unwrap();
+        assert_eq!(until, now + chrono::Duration::seconds(60));
+        let until = rate_limited_until(StatusCode::TOO_MANY_REQUESTS, &HeaderMap::new(), now, 2).unwrap();
+        assert_eq!(until, now + chrono::Duration::seconds(240));
+    }
+
+    #[test]
+    fn secondary_rate_limits() {
+        assert_eq!(secondary_backoff(0), Duration::from_secs(60));
+        assert_eq!(secondary_backoff(1), Duration::from_secs(120));
+        let message = br#"{"message":"You have exceeded a secondary rate limit."}"#;
+        assert!(is_rate_limit_message(message));
+        assert!(!is_rate_limit_message(
+            br#"{"message":"Resource not accessible by integration"}"#
+        ));
+    }
+
+    #[test]
+    fn exponential_backoff() {
+        assert_eq!(backoff(0), Duration::from_secs(1));
+        assert_eq!(backoff(1), Duration::from_secs(2));
+        assert_eq!(backoff(3), Duration::from_secs(8));
+    }
+
+    #[test]
+    fn idempotent_methods() {
+        assert!(is_idempotent(&Method::GET));
+        assert!(is_idempotent(&Method::PUT));
+        assert!(!is_idempotent(&Method::POST));
+        assert!(!is_idempotent(&Method::PATCH));
+    }
+}
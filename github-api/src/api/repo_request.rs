@@ -3,7 +3,7 @@ use reqwest::StatusCode;
 
 use crate::{
     api::{error::ErrorItem, ClientProxy, GithubApiError},
-    models::{Contributor, DateTime, Event, Label, Repository, UserType},
+    models::{Contributor, DateTime, Event, Issue, Label, Repository, UserType},
     wrappers::NewLabel,
 };
 
@@ -34,6 +34,21 @@ impl RepoRequest {
         proxy.send(req).await
     }
 
+    /// Fetches the raw contents of the file at `path` on the repository's default branch.
+    pub async fn fetch_file_contents(&self, proxy: &ClientProxy, path: &str) -> Result<String, GithubApiError> {
+        let url = format!("/repos/{}/{}/contents/{path}", self.owner, self.repo);
+        let req = proxy.get(&url, true).header("Accept", "application/vnd.github.raw");
+        let res = proxy.execute(req).await?;
+        match res.status() {
+            StatusCode::OK => res
+                .text()
+                .await
+                .map_err(|e| GithubApiError::DeserializationError(e.to_string())),
+            StatusCode::NOT_FOUND => Err(GithubApiError::NotFound(format!("{path} does not exist"))),
+            code => Err(GithubApiError::HttpResponse(code)),
+        }
+    }
+
     pub async fn fetch_labels(
         &self,
         proxy: &ClientProxy,
@@ -47,11 +62,7 @@ impl RepoRequest {
 
     pub async fn delete_label(&self, proxy: &ClientProxy, label: &str) -> Result<bool, GithubApiError> {
         let url = format!("/repos/{}/{}/labels/{label}", self.owner, self.repo);
-        let res = proxy
-            .delete(url)
-            .send()
-            .await
-            .map_err(|e| GithubApiError::ReqwestError(e.to_string()))?;
+        let res = proxy.execute(proxy.delete(url)).await?;
         Ok(res.status().is_success())
     }
 
@@ -60,10 +71,7 @@ impl RepoRequest {
         let mut errors = vec![];
         for label in labels {
             let req = proxy.post(url.as_str()).json(label);
-            let res = req
-                .send()
-                .await
-                .map_err(|e| GithubApiError::ReqwestError(e.to_string()))?;
+            let res = proxy.execute(req).await?;
             let status = res.status();
             // Any success code, or 422 (already exists) is ok, just carry on.
             if status.is_success() || status == StatusCode::UNPROCESSABLE_ENTITY {
@@ -116,6 +124,16 @@ impl RepoRequest {
         let req = proxy.get(url.as_str(), false);
         proxy.fetch_pages(req, filter, 100).await
     }
+
+    /// Fetches all open issues in the repository, least recently updated first. Github treats pull requests as
+    /// issues, so these are included too; use [`Issue::pull_request`] to tell them apart.
+    pub async fn fetch_open_issues(&self, proxy: &ClientProxy) -> Result<Vec<Issue>, GithubApiError> {
+        let url = format!("/repos/{}/{}/issues", self.owner, self.repo);
+        let req = proxy
+            .get(url.as_str(), false)
+            .query(&[("state", "open"), ("sort", "updated"), ("direction", "asc")]);
+        proxy.fetch_pages(req, |_: &Issue| true, 100).await
+    }
 }
 
 // <https://api.github.com/repositories/136459099/events?per_page=50&page=5>; rel="prev",
@@ -0,0 +1,261 @@
+//! An HTTP cache for conditional requests.
+//!
+//! Github sends an `ETag` and/or `Last-Modified` header with most `GET` responses. If we send them back in
+//! `If-None-Match` and `If-Modified-Since` headers, Github replies with `304 Not Modified` and an empty body when the
+//! resource hasn't changed, and the request doesn't count against the rate limit. [`ResponseCache`] keeps the most
+//! recent responses that had these headers, so that a 304 can be turned back into the full response.
+//!
+//! See <https://docs.github.com/en/rest/using-the-rest-api/best-practices-for-using-the-rest-api#use-conditional-requests-if-appropriate>
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use log::*;
+use reqwest::{
+    header::{
+        HeaderMap,
+        ACCEPT,
+        CONTENT_ENCODING,
+        CONTENT_LENGTH,
+        ETAG,
+        IF_MODIFIED_SINCE,
+        IF_NONE_MATCH,
+        LAST_MODIFIED,
+    },
+    Method,
+    RequestBuilder,
+    Response,
+    StatusCode,
+};
+
+use crate::api::GithubApiError;
+
+/// The default number of responses to keep.
+pub const DEFAULT_CACHE_CAPACITY: usize = 500;
+
+struct CachedResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Vec<u8>,
+    /// Used to evict the oldest entry when the cache is full
+    sequence: u64,
+}
+
+impl CachedResponse {
+    fn to_response(&self) -> Response {
+        let mut response = http::Response::new(self.body.clone());
+        *response.status_mut() = self.status;
+        *response.headers_mut() = self.headers.clone();
+        Response::from(response)
+    }
+}
+
+pub struct ResponseCache {
+    capacity: usize,
+    entries: Mutex<HashMap<String, CachedResponse>>,
+    sequence: AtomicU64,
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+impl ResponseCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(HashMap::new()),
+            sequence: AtomicU64::new(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The cache key for a request, or `None` if the request can't be cached. Only `GET` requests are cached. The
+    /// `Accept` header is part of the key, since some endpoints return different representations of the same URL, and
+    /// so is the `identity` the request is authenticated as (see [`crate::api::ClientProxy::identity`]), so that one
+    /// installation is never served a response that was fetched with another installation's permissions.
+    pub fn key(identity: &str, request: &RequestBuilder) -> Option<String> {
+        let request = request.try_clone()?.build().ok()?;
+        if request.method() != Method::GET {
+            return None;
+        }
+        let accept = request
+            .headers()
+            .get(ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default();
+        Some(format!("{identity} {} {accept}", request.url()))
+    }
+
+    /// Adds the validators of the cached response for `key`, if there is one, to the request.
+    pub fn add_validators(&self, key: &str, mut request: RequestBuilder) -> RequestBuilder {
+        if let Some(cached) = self.entries.lock().unwrap().get(key) {
+            if let Some(etag) = cached.headers.get(ETAG) {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(modified) = cached.headers.get(LAST_MODIFIED) {
+                request = request.header(IF_MODIFIED_SINCE, modified);
+            }
+        }
+        request
+    }
+
+    /// Handles the response to a request with the given key. A `304 Not Modified` is replaced with the cached
+    /// response. Successful responses with an `ETag` or `Last-Modified` header are stored. Anything else is returned
+    /// as is.
+    pub async fn handle_response(&self, key: String, response: Response) -> Result<Response, GithubApiError> {
+        let status = response.status();
+        if status == StatusCode::NOT_MODIFIED {
+            return match self.entries.lock().unwrap().get(&key) {
+                Some(cached) => {
+                    trace!("Using cached response for {key}");
+                    Ok(cached.to_response())
+                },
+                None => Err(GithubApiError::HttpResponse(status)),
+            };
+        }
+        let headers = response.headers();
+        if status != StatusCode::OK || !(headers.contains_key(ETAG) || headers.contains_key(LAST_MODIFIED)) {
+            return Ok(response);
+        }
+        let mut headers = headers.clone();
+        // The body has already been decoded, and its length may change when it is rebuilt
+        headers.remove(CONTENT_ENCODING);
+        headers.remove(CONTENT_LENGTH);
+        let body = response
+            .bytes()
+            .await
+            .map_err(|e| GithubApiError::HttpClientError(e.to_string()))?
+            .to_vec();
+        let cached = CachedResponse {
+            status,
+            headers,
+            body,
+            sequence: self.sequence.fetch_add(1, Ordering::Relaxed),
+        };
+        let response = cached.to_response();
+        self.insert(key, cached);
+        Ok(response)
+    }
+
+    fn insert(&self, key: String, response: CachedResponse) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.sequence)
+                .map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert(key, response);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::{
+        header::{ETAG, IF_NONE_MATCH},
+        Client,
+        Response,
+        StatusCode,
+    };
+
+    use super::ResponseCache;
+
+    fn response(status: u16, etag: Option<&str>, body: &str) -> Response {
+        let mut builder = http::Response::builder().status(status);
+        if let Some(etag) = etag {
+            builder = builder.header(ETAG, etag);
+        }
+        Response::from(builder.body(body.to_string()).unwrap())
+    }
+
+    #[test]
+    fn cache_keys() {
+        let client = Client::new();
+        let req = client.get("https://api.github.com/repos/tari-project/tari");
+        assert_eq!(
+            ResponseCache::key("installation 42", &req).unwrap(),
+            "installation 42 https://api.github.com/repos/tari-project/tari "
+        );
+        let raw = req.try_clone().unwrap().header("Accept", "application/vnd.github.raw");
+        assert_ne!(ResponseCache::key("bob", &raw), ResponseCache::key("bob", &req));
+        assert_ne!(
+            ResponseCache::key("installation 42", &req),
+            ResponseCache::key("installation 43", &req)
+        );
+        assert!(ResponseCache::key("bob", &client.post("https://api.github.com/graphql")).is_none());
+    }
+
+    #[tokio::test]
+    async fn revalidate_cached_response() {
+        let cache = ResponseCache::new(10);
+        let client = Client::new();
+        let req = client.get("https://api.github.com/repos/tari-project/tari/contributors");
+        let key = ResponseCache::key("bob", &req).unwrap();
+        // Nothing is cached yet, so no validators are added
+        let first = cache.add_validators(&key, req.try_clone().unwrap()).build().unwrap();
+        assert!(first.headers().get(IF_NONE_MATCH).is_none());
+
+        let res = cache
+            .handle_response(key.clone(), response(200, Some("\"abc\""), "[1,2,3]"))
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "[1,2,3]");
+        assert_eq!(cache.len(), 1);
+
+        let second = cache.add_validators(&key, req).build().unwrap();
+        assert_eq!(second.headers().get(IF_NONE_MATCH).unwrap(), "\"abc\"");
+        let res = cache.handle_response(key, response(304, None, "")).await.unwrap();
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await.unwrap(), "[1,2,3]");
+    }
+
+    #[tokio::test]
+    async fn only_cache_responses_with_validators() {
+        let cache = ResponseCache::new(10);
+        let res = cache
+            .handle_response("a".into(), response(200, None, "{}"))
+            .await
+            .unwrap();
+        assert_eq!(res.text().await.unwrap(), "{}");
+        let res = cache
+            .handle_response("b".into(), response(404, Some("\"abc\""), "{}"))
+            .await
+            .unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert!(cache.is_empty());
+        assert!(cache
+            .handle_response("c".into(), response(304, None, ""))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn evict_oldest() {
+        let cache = ResponseCache::new(2);
+        for key in ["a", "b", "c"] {
+            cache
+                .handle_response(key.into(), response(200, Some(key), key))
+                .await
+                .unwrap();
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(!cache.entries.lock().unwrap().contains_key("a"));
+    }
+}
@@ -3,8 +3,10 @@ use serde::{Deserialize, Serialize};
 use crate::{
     error::GithubProviderError,
     models::{
+        CheckRunEvent,
         CheckSuiteEvent,
         CommitCommentEvent,
+        CommonEventFields,
         IssueCommentEvent,
         IssuesEvent,
         LabelEvent,
@@ -15,12 +17,14 @@ use crate::{
         PushEvent,
         StatusEvent,
     },
+    models_plus::SlashCommand,
     wrappers::IssueId,
 };
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum GithubEvent {
+    CheckRun(CheckRunEvent),
     CheckSuiteEvent(CheckSuiteEvent),
     CommitComment(CommitCommentEvent),
     IssueComment(IssueCommentEvent),
@@ -42,6 +46,10 @@ pub enum GithubEvent {
 impl GithubEvent {
     pub fn try_from_webhook_info(event: &str, body: &str) -> Result<Self, GithubProviderError> {
         match event {
+            "check_run" => {
+                let value: CheckRunEvent = serde_json::from_str(body)?;
+                Ok(Self::CheckRun(value))
+            },
             "check_suite" => {
                 let event: CheckSuiteEvent = serde_json::from_str(body)?;
                 Ok(GithubEvent::CheckSuiteEvent(event))
@@ -92,6 +100,7 @@ impl GithubEvent {
 
     pub fn summary(&self) -> String {
         match self {
+            Self::CheckRun(e) => e.check_run.summary(),
             Self::CheckSuiteEvent(e) => e.check_suite.summary(),
             Self::CommitComment(c) => format!("Commit comment from {}: {}", c.info.sender.login, c.comment.body),
             Self::IssueComment(c) => format!(
@@ -130,6 +139,7 @@ impl GithubEvent {
     /// If this event is related to a pull request, this method conveniently returns the PR details as an [`IssueId`].
     pub fn related_pull_request(&self) -> Option<IssueId> {
         match &self {
+            GithubEvent::CheckRun(ev) => ev.first_related_pr(),
             GithubEvent::CheckSuiteEvent(ev) => ev.first_related_pr(),
             GithubEvent::PullRequest(ev) => Some(ev.as_issue_id()),
             GithubEvent::PullRequestReview(ev) => Some(ev.related_pull_request()),
@@ -144,6 +154,58 @@ impl GithubEvent {
             GithubEvent::UnknownEvent { .. } => None,
         }
     }
+
+    /// If this event relates to an issue _or_ a pull request, returns its [`IssueId`]. Since Github treats every pull
+    /// request as an issue, this id can be used with any of the `IssueProvider` methods, like adding a comment.
+    pub fn related_issue(&self) -> Option<IssueId> {
+        match &self {
+            GithubEvent::Issues(ev) => Some(IssueId::new(ev.owner(), ev.repo(), ev.number())),
+            GithubEvent::IssueComment(ev) => {
+                let repo = &ev.info.repository;
+                Some(IssueId::new(
+                    repo.owner.login.as_str(),
+                    repo.name.as_str(),
+                    ev.issue.number,
+                ))
+            },
+            _ => self.related_pull_request(),
+        }
+    }
+
+    /// Returns the fields common to all webhook events, such as the sender and repository.
+    pub fn common_fields(&self) -> Option<&CommonEventFields> {
+        match &self {
+            GithubEvent::CheckRun(ev) => Some(&ev.info),
+            GithubEvent::CheckSuiteEvent(ev) => Some(&ev.info),
+            GithubEvent::CommitComment(ev) => Some(&ev.info),
+            GithubEvent::IssueComment(ev) => Some(&ev.info),
+            GithubEvent::Issues(ev) => Some(&ev.info),
+            GithubEvent::Label(ev) => Some(&ev.info),
+            GithubEvent::Ping(ev) => Some(&ev.info),
+            GithubEvent::PullRequest(ev) => Some(&ev.info),
+            GithubEvent::PullRequestReview(ev) => Some(&ev.info),
+            GithubEvent::PullRequestReviewComment(ev) => Some(&ev.info),
+            GithubEvent::Push(ev) => Some(&ev.info),
+            GithubEvent::Status(ev) => Some(&ev.info),
+            GithubEvent::UnknownEvent { .. } => None,
+        }
+    }
+
+    /// If this event is a comment event, returns the body of the comment.
+    pub fn comment_body(&self) -> Option<&str> {
+        match &self {
+            GithubEvent::CommitComment(ev) => Some(ev.comment.body.as_str()),
+            GithubEvent::IssueComment(ev) => ev.comment.body.as_deref(),
+            GithubEvent::PullRequestReviewComment(ev) => Some(ev.comment.body.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns all the `/command arg1 arg2` style instructions contained in this event's comment body. If this is not a
+    /// comment event, or there are no commands in the comment, the vector is empty.
+    pub fn slash_commands(&self) -> Vec<SlashCommand> {
+        self.comment_body().map(SlashCommand::parse_all).unwrap_or_default()
+    }
 }
 
 #[cfg(test)]
@@ -159,10 +221,51 @@ mod test {
             PullRequestReviewCommentAction,
             ReviewState,
             State,
+            StatusEventState,
             UserType,
         },
     };
 
+    #[test]
+    fn status_event() {
+        let data = include_str!("test_data/status_event.json");
+        let event = GithubEvent::try_from_webhook_info("status", data).unwrap();
+        match &event {
+            GithubEvent::Status(status) => {
+                assert_eq!(status.context, "ci/circleci: run-integration-tests");
+                assert_eq!(status.state, StatusEventState::Failure);
+                assert_eq!(status.branches[0].name, "base-node-return-utxo-info-for-template-reg");
+                assert_eq!(status.commit.commit.author.name, "Stan Bondi");
+            },
+            _ => panic!("Not a status event"),
+        }
+        assert_eq!(event.summary(), "Status event (failure) on tari");
+    }
+
+    #[test]
+    fn check_run_related_pr() {
+        let data = include_str!("test_data/check_run_event.json");
+        let event = GithubEvent::try_from_webhook_info("check_run", data).unwrap();
+        assert_eq!(
+            event.related_pull_request().unwrap().to_string(),
+            "tari-project/tari#4821"
+        );
+        assert!(event.summary().starts_with("Check run clippy: completed: failure."));
+    }
+
+    #[test]
+    fn comment_body_and_slash_commands() {
+        let data = include_str!("test_data/issue_comment_event1.json");
+        let event = GithubEvent::try_from_webhook_info("issue_comment", data).unwrap();
+        assert_eq!(event.comment_body(), Some("utACK"));
+        assert!(event.slash_commands().is_empty());
+
+        let data = include_str!("test_data/push_event.json");
+        let event = GithubEvent::try_from_webhook_info("push", data).unwrap();
+        assert!(event.comment_body().is_none());
+        assert!(event.slash_commands().is_empty());
+    }
+
     #[test]
     fn push_event() {
         let data = include_str!("test_data/push_event.json");
@@ -314,6 +417,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn related_issue() {
+        let data = include_str!("test_data/issue_event.json");
+        let event = GithubEvent::try_from_webhook_info("issues", data).unwrap();
+        assert!(event.related_pull_request().is_none());
+        assert_eq!(event.related_issue().unwrap().to_string(), "tari-project/tari#4630");
+        assert_eq!(event.common_fields().unwrap().sender.login, "sdbondi");
+    }
+
     /// Fix bug in deserializing the `GithubEvent::PullRequestReviewComment` event - 15/9/2022
     #[test]
     fn pr_review_comment() {
@@ -10,10 +10,12 @@ use crate::{
     api::{
         AuthToken,
         ClientProxy,
+        GithubAppAuth,
         IssueRequest,
         OrganizationRequest,
         Page,
         PullRequestRequest,
+        RateLimit,
         RepoRequest,
         UserRequest,
     },
@@ -24,7 +26,21 @@ use crate::{
         CheckRunStatus,
         PullRequestComments,
     },
-    models::{Contributor, DateTime, Event, Issue, IssueComment, Label, PullRequest, Repository, SimpleUser},
+    models::{
+        Contributor,
+        DateTime,
+        Event,
+        Issue,
+        IssueComment,
+        Label,
+        LockReason,
+        PullRequest,
+        PullRequestFile,
+        Repository,
+        SimpleUser,
+        State,
+        StateReason,
+    },
     models_plus::{MergeParameters, MergeResult},
     provider_traits::{
         CheckRunStatusProvider,
@@ -38,14 +54,20 @@ use crate::{
         UserProvider,
     },
     wrappers::{GithubHandle, IssueId, NewLabel},
+    GithubEvent,
 };
 
 pub const GITHUB_USER_ENVAR_NAME: &str = "GH_PILOT_USERNAME";
 pub const GITHUB_AUTH_TOKEN_ENVAR_NAME: &str = "GH_PILOT_AUTH_TOKEN";
+pub const GITHUB_APP_ID_ENVAR_NAME: &str = "GH_PILOT_APP_ID";
+pub const GITHUB_APP_KEY_PATH_ENVAR_NAME: &str = "GH_PILOT_APP_PRIVATE_KEY_PATH";
+pub const GITHUB_API_URL_ENVAR_NAME: &str = "GH_PILOT_API_URL";
+pub const GITHUB_GRAPHQL_URL_ENVAR_NAME: &str = "GH_PILOT_GRAPHQL_URL";
 
 #[derive(Clone)]
 pub struct GithubProvider {
     client: ClientProxy,
+    app: Option<GithubAppAuth>,
 }
 
 impl Debug for GithubProvider {
@@ -64,7 +86,7 @@ impl Default for GithubProvider {
                  credentials to blank (and almost certainly incorrect) values.",
                 err.to_string()
             );
-            GithubProvider::new("user", "")
+            GithubProvider::new("user", "").with_urls_from_environment()
         })
     }
 }
@@ -72,13 +94,60 @@ impl Default for GithubProvider {
 impl GithubProvider {
     pub fn new(username: &str, auth_token: &str) -> Self {
         let client = ClientProxy::new(username, auth_token.into());
-        Self { client }
+        Self { client, app: None }
+    }
+
+    /// Create a GithubProvider that authenticates as a Github App. On its own, the provider can only access public
+    /// resources. Use [`GithubProvider::for_installation`], [`GithubProvider::for_repo`] or
+    /// [`GithubProvider::for_event`] to get a provider that acts on behalf of one of the app's installations.
+    pub fn with_app(app: GithubAppAuth) -> Self {
+        Self {
+            client: ClientProxy::default(),
+            app: Some(app),
+        }
     }
 
-    /// Create a GithubProvider instance by reading in variables from the environment. If the variables are missing,
-    /// this method will return an error. The `Default` implementation uses this method, but will not fail,
-    /// substituting blank values for username and auth token (which will typically fail).
+    /// Points the provider at a different Github API, such as a Github Enterprise Server
+    /// (`https://github.example.com/api/v3`) or a mock server in tests. See [`ClientProxy::with_base_url`].
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.client.with_base_url(base_url);
+        if let Some(app) = self.app.as_mut() {
+            app.with_base_url(base_url);
+        }
+        self
+    }
+
+    /// Overrides the GraphQL endpoint, if it can't be derived from the base URL.
+    pub fn with_graphql_url(mut self, graphql_url: &str) -> Self {
+        self.client.with_graphql_url(graphql_url);
+        self
+    }
+
+    /// Create a GithubProvider instance by reading in variables from the environment. If `GH_PILOT_APP_ID` is set,
+    /// the provider authenticates as a Github App, using the private key at `GH_PILOT_APP_PRIVATE_KEY_PATH`.
+    /// Otherwise it uses basic auth with `GH_PILOT_USERNAME` and `GH_PILOT_AUTH_TOKEN`. The API URLs can be changed
+    /// with `GH_PILOT_API_URL` and `GH_PILOT_GRAPHQL_URL`. If the variables are missing, this method will return an
+    /// error. The `Default` implementation uses this method, but will not fail, substituting blank values for
+    /// username and auth token (which will typically fail).
     pub fn from_environment() -> Result<Self, GithubProviderError> {
+        let provider = match env::var(GITHUB_APP_ID_ENVAR_NAME) {
+            Ok(app_id) => Self::app_from_environment(app_id)?,
+            Err(_) => Self::user_from_environment()?,
+        };
+        Ok(provider.with_urls_from_environment())
+    }
+
+    fn with_urls_from_environment(mut self) -> Self {
+        if let Ok(url) = env::var(GITHUB_API_URL_ENVAR_NAME) {
+            self = self.with_base_url(url.as_str());
+        }
+        if let Ok(url) = env::var(GITHUB_GRAPHQL_URL_ENVAR_NAME) {
+            self = self.with_graphql_url(url.as_str());
+        }
+        self
+    }
+
+    fn user_from_environment() -> Result<Self, GithubProviderError> {
         let username = env::var(GITHUB_USER_ENVAR_NAME).map_err(|_| {
             GithubProviderError::ConfigurationError(format!(
                 "Cannot set github user name. Missing {} environment variable",
@@ -94,7 +163,79 @@ impl GithubProvider {
                 ))
             })?;
         let client = ClientProxy::new(username.as_str(), token);
-        Ok(Self { client })
+        Ok(Self { client, app: None })
+    }
+
+    fn app_from_environment(app_id: String) -> Result<Self, GithubProviderError> {
+        let key_path = env::var(GITHUB_APP_KEY_PATH_ENVAR_NAME).map_err(|_| {
+            GithubProviderError::ConfigurationError(format!(
+                "{GITHUB_APP_ID_ENVAR_NAME} is set, but the app private key is missing. Set the \
+                 {GITHUB_APP_KEY_PATH_ENVAR_NAME} environment variable"
+            ))
+        })?;
+        let key = std::fs::read(&key_path).map_err(|e| {
+            GithubProviderError::ConfigurationError(format!("Could not read the app private key at {key_path}. {e}"))
+        })?;
+        let app = GithubAppAuth::new(app_id, &key)?;
+        Ok(Self::with_app(app))
+    }
+
+    /// The API quota for this provider's token, as reported in the most recent response from Github, if any requests
+    /// have been made with it yet. Each app installation has its own quota.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.client.rate_limit()
+    }
+
+    /// True if this provider authenticates as a Github App, rather than with a personal access token.
+    pub fn is_app(&self) -> bool {
+        self.app.is_some()
+    }
+
+    /// Returns a provider that is authenticated with an access token for the given installation of the Github App.
+    /// If this provider does not authenticate as an app, a copy of it is returned.
+    pub async fn for_installation(&self, installation_id: i64) -> Result<Self, GithubProviderError> {
+        let mut result = self.clone();
+        if let Some(app) = &self.app {
+            let token = app.installation_token(installation_id).await?;
+            result
+                .client
+                .with_bearer_auth(&format!("installation {installation_id}"), token);
+        }
+        Ok(result)
+    }
+
+    /// Returns a provider that is authenticated for the Github App installation that covers the given repository.
+    /// If this provider does not authenticate as an app, a copy of it is returned.
+    pub async fn for_repo(&self, owner: &str, repo: &str) -> Result<Self, GithubProviderError> {
+        match &self.app {
+            Some(app) => {
+                let installation_id = app.installation_for_repo(owner, repo).await?;
+                self.for_installation(installation_id).await
+            },
+            None => Ok(self.clone()),
+        }
+    }
+
+    /// Returns a provider that is authenticated for the Github App installation that sent the webhook event. If the
+    /// event does not say which installation it came from, the installation is looked up from the event's repository.
+    /// If this provider does not authenticate as an app, a copy of it is returned.
+    pub async fn for_event(&self, event: &GithubEvent) -> Result<Self, GithubProviderError> {
+        if self.app.is_none() {
+            return Ok(self.clone());
+        }
+        let info = event.common_fields().ok_or_else(|| {
+            GithubProviderError::GeneralError(format!(
+                "Cannot tell which app installation this event belongs to. {}",
+                event.summary()
+            ))
+        })?;
+        match &info.installation {
+            Some(installation) => self.for_installation(installation.id).await,
+            None => {
+                let repo = &info.repository;
+                self.for_repo(repo.owner.login.as_str(), repo.name.as_str()).await
+            },
+        }
     }
 }
 
@@ -115,6 +256,49 @@ impl PullRequestProvider for GithubProvider {
         let result = pr.merge(&self.client, params).await?;
         Ok(result)
     }
+
+    async fn convert_to_draft(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        let pr = PullRequestRequest::from(id);
+        let node_id = pr.fetch(&self.client).await?.node_id;
+        let is_draft = pr.convert_to_draft(&node_id, &self.client).await?;
+        if is_draft {
+            Ok(())
+        } else {
+            Err(GithubProviderError::GeneralError(format!(
+                "PR {id} is still not a draft after converting it"
+            )))
+        }
+    }
+
+    async fn fetch_pull_request_files(&self, id: &IssueId) -> Result<Vec<PullRequestFile>, GithubProviderError> {
+        let pr = PullRequestRequest::from(id);
+        let result = pr.fetch_files(&self.client).await?;
+        Ok(result)
+    }
+
+    async fn request_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let pr = PullRequestRequest::from(id);
+        let result = pr.request_reviewers(reviewers, team_reviewers, &self.client).await?;
+        Ok(result)
+    }
+
+    async fn remove_requested_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let pr = PullRequestRequest::from(id);
+        let result = pr
+            .remove_requested_reviewers(reviewers, team_reviewers, &self.client)
+            .await?;
+        Ok(result)
+    }
 }
 
 #[async_trait]
@@ -169,6 +353,41 @@ impl IssueProvider for GithubProvider {
         let comment = issue.add_comment(comment, &self.client).await?;
         Ok(comment)
     }
+
+    async fn add_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        let issue = IssueRequest::from(id);
+        let result = issue.add_assignees(assignees, &self.client).await?;
+        Ok(result)
+    }
+
+    async fn remove_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        let issue = IssueRequest::from(id);
+        let result = issue.remove_assignees(assignees, &self.client).await?;
+        Ok(result)
+    }
+
+    async fn set_state(
+        &self,
+        id: &IssueId,
+        state: State,
+        reason: Option<StateReason>,
+    ) -> Result<Issue, GithubProviderError> {
+        let issue = IssueRequest::from(id);
+        let result = issue.set_state(state, reason, &self.client).await?;
+        Ok(result)
+    }
+
+    async fn lock(&self, id: &IssueId, reason: Option<LockReason>) -> Result<(), GithubProviderError> {
+        let issue = IssueRequest::from(id);
+        issue.lock(reason, &self.client).await?;
+        Ok(())
+    }
+
+    async fn unlock(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        let issue = IssueRequest::from(id);
+        issue.unlock(&self.client).await?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -242,6 +461,18 @@ impl RepoProvider for GithubProvider {
         let result = repo.fetch_events(&self.client, since).await?;
         Ok(result)
     }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, GithubProviderError> {
+        let repo = RepoRequest::new(owner, repo);
+        let result = repo.fetch_open_issues(&self.client).await?;
+        Ok(result)
+    }
+
+    async fn fetch_file_contents(&self, owner: &str, repo: &str, path: &str) -> Result<String, GithubProviderError> {
+        let repo = RepoRequest::new(owner, repo);
+        let result = repo.fetch_file_contents(&self.client, path).await?;
+        Ok(result)
+    }
 }
 
 #[async_trait]
@@ -0,0 +1,10 @@
+use graphql_client::GraphQLQuery;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "src/graphql/data/schema.graphql",
+    query_path = "src/graphql/data/convert_to_draft.graphql",
+    deprecated = "warn",
+    response_derives = "Debug, Clone, PartialEq, Eq"
+)]
+pub struct ConvertToDraftQL;
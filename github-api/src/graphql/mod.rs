@@ -1,3 +1,4 @@
+pub mod convert_to_draft;
 pub mod org_activity;
 pub mod pr_comments;
 pub mod review_counts;
@@ -11,12 +11,14 @@ use crate::{models::DateTime, wrappers::IssueId};
 )]
 pub struct PullRequestReviewCountsQL;
 
+#[derive(Clone)]
 pub struct ReviewSummary {
     pub author: String,
     pub created_at: DateTime,
     state: pull_request_review_counts_ql::PullRequestReviewState,
 }
 
+#[derive(Clone)]
 pub struct ReviewCounts {
     total_count: usize,
     id: IssueId,
@@ -25,6 +27,30 @@ pub struct ReviewCounts {
 }
 
 impl ReviewCounts {
+    /// A summary of a PR with no reviews. Use [`ReviewCounts::add_review`] to add some.
+    pub fn new<S: Into<String>>(id: IssueId, title: S) -> Self {
+        Self {
+            total_count: 0,
+            id,
+            title: title.into(),
+            reviews: Vec::new(),
+        }
+    }
+
+    pub fn add_review<S: Into<String>>(
+        &mut self,
+        author: S,
+        state: pull_request_review_counts_ql::PullRequestReviewState,
+    ) -> &mut Self {
+        self.reviews.push(ReviewSummary {
+            author: author.into(),
+            created_at: DateTime::now(),
+            state,
+        });
+        self.total_count += 1;
+        self
+    }
+
     pub fn total(&self) -> usize {
         self.total_count
     }
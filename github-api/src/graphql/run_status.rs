@@ -70,6 +70,17 @@ impl CheckRunStatus {
         self.overall_status.as_ref()
     }
 
+    pub fn set_overall_status(&mut self, status: Option<check_run_status_ql::StatusState>) -> &mut Self {
+        self.overall_status = status;
+        self
+    }
+
+    /// Adds a check run, replacing any earlier run with the same name.
+    pub fn add_check(&mut self, check: RunStatus) -> &mut Self {
+        self.checks.insert(check.name.clone(), check);
+        self
+    }
+
     pub fn checks(&self) -> Values<String, RunStatus> {
         self.checks.values()
     }
@@ -7,6 +7,8 @@ mod macros;
 pub mod models;
 pub mod models_plus;
 pub mod provider_traits;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
 pub mod wrappers;
 
 pub use github_event::GithubEvent;
@@ -75,6 +75,28 @@ pub struct Issue {
     pub reactions: Option<Reactions>,
 }
 
+/// The reason given when closing or reopening an issue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StateReason {
+    Completed,
+    NotPlanned,
+    Reopened,
+}
+
+/// The reason given when locking an issue or pull request conversation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum LockReason {
+    #[serde(rename = "off-topic")]
+    OffTopic,
+    #[serde(rename = "too heated")]
+    TooHeated,
+    #[serde(rename = "resolved")]
+    Resolved,
+    #[serde(rename = "spam")]
+    Spam,
+}
+
 impl Issue {
     pub fn user(&self) -> Option<&SimpleUser> {
         self.user.as_deref()
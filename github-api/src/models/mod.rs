@@ -36,13 +36,13 @@ pub use comment_events::*;
 pub use comments::*;
 pub use commits::*;
 pub use common::*;
-pub use date_time::DateTime;
+pub use date_time::{DateTime, Timestamp};
 pub use event::*;
 pub use events_common::*;
 pub use git::GitReference;
 pub use installation::*;
 pub use issue_event::*;
-pub use issues::Issue;
+pub use issues::{Issue, LockReason, StateReason};
 pub use label_event::*;
 pub use labels::{Label, *};
 pub use links::Links;
@@ -133,6 +133,20 @@ pub struct CheckRunPullRequest {
     pub url: Url,
 }
 
+/// A file changed by a pull request, as returned by the `pulls/{number}/files` endpoint.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullRequestFile {
+    pub sha: Option<String>,
+    pub filename: String,
+    /// One of `added`, `removed`, `modified`, `renamed`, `copied`, `changed` or `unchanged`
+    pub status: String,
+    pub additions: u64,
+    pub deletions: u64,
+    pub changes: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub previous_filename: Option<String>,
+}
+
 #[cfg(test)]
 mod test {
     use crate::models::{Link, PullRequest, Repository, SimpleUser};
@@ -54,6 +54,62 @@ pub struct CheckSuite {
     pub url: Url,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub enum CheckRunAction {
+    #[serde(rename = "completed")]
+    Completed,
+    #[serde(rename = "created")]
+    Created,
+    #[serde(rename = "requested_action")]
+    RequestedAction,
+    #[serde(rename = "rerequested")]
+    Rerequested,
+}
+
+/// The [check_run](https://docs.github.com/en/rest/reference/checks#runs).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckRunEvent {
+    pub action: CheckRunAction,
+    pub check_run: CheckRun,
+    #[serde(flatten)]
+    pub info: CommonEventFields,
+}
+
+/// A single check run, e.g. one job in a CI workflow.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckRun {
+    pub app: App,
+    pub check_suite: CheckRunSuite,
+    pub completed_at: Option<String>,
+    /// The result of the completed check run. This value will be `null` until the check run has `completed`.
+    pub conclusion: Option<CheckSuiteConclusion>,
+    pub details_url: Option<String>,
+    pub external_id: Option<String>,
+    /// The SHA of the commit that is being checked.
+    pub head_sha: String,
+    pub html_url: Option<String>,
+    pub id: i64,
+    /// The name of the check, e.g. `clippy`.
+    pub name: String,
+    pub node_id: String,
+    pub pull_requests: Vec<CheckRunPullRequest>,
+    pub started_at: Option<String>,
+    /// The current status of the check run. Can be `queued`, `in_progress`, or `completed`.
+    pub status: Option<CheckSuiteStatus>,
+    pub url: Url,
+}
+
+/// The check suite a check run belongs to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CheckRunSuite {
+    pub id: i64,
+    pub head_branch: Option<String>,
+    pub head_sha: String,
+    pub status: Option<CheckSuiteStatus>,
+    pub conclusion: Option<CheckSuiteConclusion>,
+    pub url: Url,
+}
+
 /// The summary conclusion for all check runs that are part of the check suite. Can be one of `success`, `failure`,
 /// `neutral`, `cancelled`, `timed_out`, `action_required` or `stale`. This value will be `null` until the check run has
 /// `completed`.
@@ -122,6 +178,23 @@ mod test {
         models::status_check_events::{CheckSuiteConclusion, CheckSuiteStatus},
     };
 
+    #[test]
+    fn check_run_event() {
+        let data = include_str!("../test_data/check_run_event.json");
+        let event = GithubEvent::try_from_webhook_info("check_run", data).unwrap();
+        match event {
+            GithubEvent::CheckRun(ev) => {
+                let run = ev.check_run;
+                assert_eq!(run.name, "clippy");
+                assert_eq!(run.app.slug.as_deref(), Some("github-actions"));
+                assert!(matches!(run.status, Some(CheckSuiteStatus::Completed)));
+                assert!(matches!(run.conclusion, Some(CheckSuiteConclusion::Failure)));
+                assert_eq!(run.pull_requests[0].number, 4821);
+            },
+            _ => panic!("Not a check run event"),
+        }
+    }
+
     #[test]
     fn check_suite_event() {
         let data = include_str!("../test_data/check_suite_event.json");
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::models::{Branch, Commit, CommonEventFields, Url};
+use crate::models::{Committer, CommonEventFields, Url};
 
 //---------------------------------------    Status event   ------------------------------------------------------------
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -9,8 +9,8 @@ pub struct StatusEvent {
     pub avatar_url: Option<String>,
     /// An array of branch objects containing the status' SHA. Each branch contains the given SHA, but the SHA
     /// may or may not be the head of the branch. The array includes a maximum of 10 branches.
-    pub branches: Vec<Branch>,
-    pub commit: Commit,
+    pub branches: Vec<StatusBranch>,
+    pub commit: StatusCommit,
     pub context: String,
     pub created_at: String,
     /// The optional human-readable description added to the status.
@@ -29,6 +29,37 @@ pub struct StatusEvent {
     pub info: CommonEventFields,
 }
 
+/// The commit that a status event refers to.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusCommit {
+    pub sha: String,
+    pub node_id: String,
+    pub commit: StatusCommitDetails,
+    pub url: Url,
+    pub html_url: Url,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusCommitDetails {
+    pub author: Committer,
+    pub committer: Committer,
+    pub message: String,
+}
+
+/// A branch containing the status' SHA.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusBranch {
+    pub name: String,
+    pub commit: StatusBranchCommit,
+    pub protected: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StatusBranchCommit {
+    pub sha: String,
+    pub url: Url,
+}
+
 /// The new state. Can be `pending`, `success`, `failure`, or `error`.
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub enum StatusEventState {
@@ -0,0 +1,49 @@
+use log::{debug, trace};
+
+use crate::{
+    models::{CheckRun, CheckRunEvent},
+    wrappers::IssueId,
+};
+
+impl CheckRun {
+    pub fn summary(&self) -> String {
+        let status = self.status.map(|s| s.to_string()).unwrap_or_else(|| "N/A".to_string());
+        let conclusion = self
+            .conclusion
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "N/A".to_string());
+        format!("Check run {}: {status}: {conclusion}. {}", self.name, self.url)
+    }
+
+    /// The slug of the app that ran this check, e.g. `github-actions`
+    pub fn app_slug(&self) -> Option<&str> {
+        self.app.slug.as_deref()
+    }
+}
+
+impl CheckRunEvent {
+    /// Returns the set of associated PRs as a vector of IssueId
+    pub fn related_pull_requests(&self) -> Vec<IssueId> {
+        let owner = match self.info.organization {
+            Some(ref org) => org.login.as_str(),
+            None => {
+                trace!("No organization was attached in the check run event, so can't pull owner.");
+                return vec![];
+            },
+        };
+        let repo = self.info.repository.name.as_str();
+        self.check_run
+            .pull_requests
+            .iter()
+            .map(|pr| IssueId::new(owner, repo, pr.number))
+            .collect()
+    }
+
+    pub fn first_related_pr(&self) -> Option<IssueId> {
+        let prs = self.related_pull_requests();
+        if prs.len() > 1 {
+            debug!("CheckRunEvent related to {} PRs. Returning the first one", prs.len());
+        }
+        prs.first().cloned()
+    }
+}
@@ -0,0 +1,257 @@
+use std::fmt::Display;
+
+use log::warn;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// The places Github looks for a CODEOWNERS file, in order of precedence.
+pub const CODEOWNERS_PATHS: [&str; 3] = [".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// An owner listed in a CODEOWNERS file.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeOwner {
+    /// A Github user, written as `@user`
+    User(String),
+    /// A team, written as `@org/team`
+    Team { org: String, team: String },
+    /// A user identified by their email address
+    Email(String),
+}
+
+impl CodeOwner {
+    /// Parse an owner token, e.g. `@sdbondi`, `@tari-project/core-devs` or `someone@example.com`.
+    pub fn parse(token: &str) -> Option<Self> {
+        match token.strip_prefix('@') {
+            Some(handle) => match handle.split_once('/') {
+                Some((org, team)) if !org.is_empty() && !team.is_empty() => Some(Self::Team {
+                    org: org.to_string(),
+                    team: team.to_string(),
+                }),
+                Some(_) => None,
+                None if handle.is_empty() => None,
+                None => Some(Self::User(handle.to_string())),
+            },
+            None if token.contains('@') => Some(Self::Email(token.to_string())),
+            None => None,
+        }
+    }
+}
+
+impl Display for CodeOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::User(user) => write!(f, "@{user}"),
+            Self::Team { org, team } => write!(f, "@{org}/{team}"),
+            Self::Email(email) => f.write_str(email),
+        }
+    }
+}
+
+/// A single `pattern owner1 owner2 ...` line in a CODEOWNERS file.
+#[derive(Clone, Debug)]
+pub struct CodeOwnersRule {
+    pattern: String,
+    owners: Vec<CodeOwner>,
+    regex: Regex,
+}
+
+impl CodeOwnersRule {
+    pub fn pattern(&self) -> &str {
+        self.pattern.as_str()
+    }
+
+    pub fn owners(&self) -> &[CodeOwner] {
+        self.owners.as_slice()
+    }
+
+    /// Returns true if the path (relative to the repository root, without a leading `/`) matches this rule's pattern.
+    pub fn is_match(&self, path: &str) -> bool {
+        self.regex.is_match(path.trim_start_matches('/'))
+    }
+}
+
+/// A parsed [CODEOWNERS](https://docs.github.com/en/repositories/managing-your-repositorys-settings-and-features/customizing-your-repository/about-code-owners)
+/// file.
+///
+/// Patterns follow the same rules as Github's implementation: they are `.gitignore`-style globs, and when several
+/// patterns match a file, the _last_ one in the file wins. A pattern with no owners means that the matching files
+/// have no code owners.
+#[derive(Clone, Debug, Default)]
+pub struct CodeOwners {
+    rules: Vec<CodeOwnersRule>,
+}
+
+impl CodeOwners {
+    /// Parse the contents of a CODEOWNERS file. Blank lines and comments are skipped, as are lines with invalid
+    /// patterns or owners (a warning is logged for these).
+    pub fn parse(contents: &str) -> Self {
+        let rules = contents
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    return None;
+                }
+                let line = line.split_once(" #").map(|(l, _)| l).unwrap_or(line);
+                let mut tokens = line.split_whitespace();
+                let pattern = tokens.next()?;
+                let owners = tokens.map(CodeOwner::parse).collect::<Option<Vec<CodeOwner>>>();
+                match (pattern_to_regex(pattern), owners) {
+                    (Some(regex), Some(owners)) => Some(CodeOwnersRule {
+                        pattern: pattern.to_string(),
+                        owners,
+                        regex,
+                    }),
+                    _ => {
+                        warn!("Skipping invalid CODEOWNERS entry on line {}: {line}", i + 1);
+                        None
+                    },
+                }
+            })
+            .collect();
+        Self { rules }
+    }
+
+    pub fn rules(&self) -> &[CodeOwnersRule] {
+        self.rules.as_slice()
+    }
+
+    /// Returns the owners of the given file. This is an empty slice if no rule matches the file.
+    pub fn owners_of(&self, path: &str) -> &[CodeOwner] {
+        self.rules
+            .iter()
+            .rev()
+            .find(|r| r.is_match(path))
+            .map(CodeOwnersRule::owners)
+            .unwrap_or_default()
+    }
+
+    /// Returns the combined owners of all the given files, without duplicates, in the order they are first
+    /// encountered.
+    pub fn owners_for<'a, I: IntoIterator<Item = &'a str>>(&self, paths: I) -> Vec<CodeOwner> {
+        let mut result = Vec::new();
+        for path in paths {
+            for owner in self.owners_of(path) {
+                if !result.contains(owner) {
+                    result.push(owner.clone());
+                }
+            }
+        }
+        result
+    }
+}
+
+/// Converts a CODEOWNERS glob into an anchored regular expression.
+fn pattern_to_regex(pattern: &str) -> Option<Regex> {
+    // Patterns containing a slash anywhere except at the end are relative to the repository root.
+    let anchored = pattern.trim_end_matches('/').contains('/');
+    let dir_only = pattern.ends_with('/');
+    let glob = pattern.trim_start_matches('/').trim_end_matches('/');
+    if glob.is_empty() {
+        return None;
+    }
+    let mut re = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let mut chars = glob.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' if chars.peek() == Some(&'*') => {
+                chars.next();
+                if chars.peek() == Some(&'/') {
+                    chars.next();
+                    re.push_str("(?:.*/)?");
+                } else {
+                    re.push_str(".*");
+                }
+            },
+            '*' => re.push_str("[^/]*"),
+            '?' => re.push_str("[^/]"),
+            c => re.push_str(&regex::escape(&c.to_string())),
+        }
+    }
+    // Directories match everything beneath them. Github does _not_ apply this to patterns whose last segment is a
+    // wildcard, so `docs/*` only matches files directly inside `docs`.
+    let last_segment = glob.rsplit('/').next().unwrap_or_default();
+    if dir_only {
+        re.push_str("/.*");
+    } else if !last_segment.contains('*') {
+        re.push_str("(?:/.*)?");
+    }
+    re.push('$');
+    Regex::new(&re).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CodeOwner, CodeOwners};
+
+    const CODEOWNERS: &str = r#"
+# Default owners for everything in the repo
+*       @tari-project/core-devs
+
+# Javascript, anywhere
+*.js    @stringhandler
+
+/base_layer/core/   @sdbondi @CjS77
+docs/*  docs@tari.com
+apps/   @hansieodendaal
+**/logs @tari-project/ops
+/applications/tari_app_grpc/proto
+"#;
+
+    #[test]
+    fn parse_owners() {
+        assert_eq!(CodeOwner::parse("@bob"), Some(CodeOwner::User("bob".into())));
+        assert_eq!(
+            CodeOwner::parse("@tari-project/core-devs"),
+            Some(CodeOwner::Team {
+                org: "tari-project".into(),
+                team: "core-devs".into()
+            })
+        );
+        assert_eq!(
+            CodeOwner::parse("docs@tari.com"),
+            Some(CodeOwner::Email("docs@tari.com".into()))
+        );
+        assert!(CodeOwner::parse("bob").is_none());
+        assert!(CodeOwner::parse("@").is_none());
+        assert!(CodeOwner::parse("@org/").is_none());
+        assert_eq!(CodeOwner::parse("@org/team").unwrap().to_string(), "@org/team");
+    }
+
+    #[test]
+    fn parse_file() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        assert_eq!(owners.rules().len(), 7);
+        assert_eq!(owners.rules()[2].pattern(), "/base_layer/core/");
+        assert!(owners.rules()[6].owners().is_empty());
+    }
+
+    #[test]
+    fn last_match_wins() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        let team = |p: &str| owners.owners_of(p).iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        assert_eq!(team("README.md"), vec!["@tari-project/core-devs"]);
+        assert_eq!(team("web/src/index.js"), vec!["@stringhandler"]);
+        assert_eq!(team("base_layer/core/src/lib.rs"), vec!["@sdbondi", "@CjS77"]);
+        assert_eq!(team("base_layer/core/src/blocks/x.js"), vec!["@sdbondi", "@CjS77"]);
+        // Anchored, so nested base_layer/core directories don't match
+        assert_eq!(team("other/base_layer/core/lib.rs"), vec!["@tari-project/core-devs"]);
+        assert_eq!(team("docs/index.md"), vec!["docs@tari.com"]);
+        assert_eq!(team("docs/nested/index.md"), vec!["@tari-project/core-devs"]);
+        assert_eq!(team("applications/apps/main.rs"), vec!["@hansieodendaal"]);
+        assert_eq!(team("logs/today.log"), vec!["@tari-project/ops"]);
+        assert_eq!(team("deep/path/logs/today.log"), vec!["@tari-project/ops"]);
+        assert!(team("applications/tari_app_grpc/proto/base_node.proto").is_empty());
+    }
+
+    #[test]
+    fn owners_for_many_files() {
+        let owners = CodeOwners::parse(CODEOWNERS);
+        let result = owners.owners_for(["base_layer/core/src/lib.rs", "web/a.js", "base_layer/core/Cargo.toml"]);
+        let result = result.iter().map(|o| o.to_string()).collect::<Vec<_>>();
+        assert_eq!(result, vec!["@sdbondi", "@CjS77", "@stringhandler"]);
+        assert!(CodeOwners::parse("").owners_for(["a.rs"]).is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 //! This module provides additional functionality and helper functions to the structs in the `models` module.
 //! The code is kept separate to avoid messing with the code generation tools.
 
+mod check_run_event;
 mod check_suite_event;
+mod codeowners;
 mod deserializers;
 mod issue;
 mod issue_comment;
@@ -9,5 +11,8 @@ mod pull_request;
 mod pull_request_event;
 mod pull_request_review_comment_event;
 mod pull_request_review_event;
+mod slash_command;
+pub use codeowners::{CodeOwner, CodeOwners, CodeOwnersRule, CODEOWNERS_PATHS};
 pub use deserializers::*;
 pub use pull_request::*;
+pub use slash_command::SlashCommand;
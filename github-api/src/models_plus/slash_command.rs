@@ -0,0 +1,102 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+/// A `/command arg1 arg2` style instruction found in a comment body.
+///
+/// Commands must start at the beginning of a line (leading whitespace is ignored), and the command name may only
+/// contain alphanumeric characters, `-` and `_`. Everything following the command name on the same line is split on
+/// whitespace into arguments. For example, `/label T-foo` yields the command `label` with the single argument `T-foo`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SlashCommand {
+    pub command: String,
+    pub args: Vec<String>,
+}
+
+impl SlashCommand {
+    /// Parse a single line of text as a slash command.
+    pub fn parse(line: &str) -> Option<Self> {
+        let line = line.trim().strip_prefix('/')?;
+        if line.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let mut parts = line.split_whitespace();
+        let command = parts.next()?;
+        let valid = command.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return None;
+        }
+        let args = parts.map(String::from).collect();
+        Some(Self {
+            command: command.to_lowercase(),
+            args,
+        })
+    }
+
+    /// Extract all slash commands from a (possibly multi-line) comment body, in the order they appear.
+    pub fn parse_all(body: &str) -> Vec<Self> {
+        body.lines().filter_map(Self::parse).collect()
+    }
+
+    /// Returns true if this command has the given name. The comparison is case-insensitive.
+    pub fn is(&self, command: &str) -> bool {
+        self.command.eq_ignore_ascii_case(command.trim_start_matches('/'))
+    }
+
+    /// The arguments with any leading `@` removed, which is convenient for commands that take user handles, like
+    /// `/assign @bob`.
+    pub fn handles(&self) -> Vec<&str> {
+        self.args.iter().map(|a| a.trim_start_matches('@')).collect()
+    }
+}
+
+impl Display for SlashCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "/{}", self.command)?;
+        for arg in &self.args {
+            write!(f, " {arg}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SlashCommand;
+
+    #[test]
+    fn parse_simple_commands() {
+        let cmd = SlashCommand::parse("/merge").unwrap();
+        assert_eq!(cmd.command, "merge");
+        assert!(cmd.args.is_empty());
+
+        let cmd = SlashCommand::parse("  /label T-foo  ").unwrap();
+        assert!(cmd.is("label"));
+        assert!(cmd.is("/label"));
+        assert_eq!(cmd.args, vec!["T-foo"]);
+        assert_eq!(cmd.to_string(), "/label T-foo");
+
+        let cmd = SlashCommand::parse("/Assign @bob alice").unwrap();
+        assert_eq!(cmd.command, "assign");
+        assert_eq!(cmd.handles(), vec!["bob", "alice"]);
+    }
+
+    #[test]
+    fn reject_non_commands() {
+        assert!(SlashCommand::parse("merge").is_none());
+        assert!(SlashCommand::parse("/").is_none());
+        assert!(SlashCommand::parse("/ merge").is_none());
+        assert!(SlashCommand::parse("/usr/bin/bash").is_none());
+        assert!(SlashCommand::parse("please /merge").is_none());
+    }
+
+    #[test]
+    fn parse_multiline_body() {
+        let body = "Looks good to me.\r\n/label P-merge\r\n\r\nThanks!\n/merge";
+        let cmds = SlashCommand::parse_all(body);
+        assert_eq!(cmds.len(), 2);
+        assert_eq!(cmds[0].to_string(), "/label P-merge");
+        assert_eq!(cmds[1].to_string(), "/merge");
+        assert!(SlashCommand::parse_all("utACK").is_empty());
+    }
+}
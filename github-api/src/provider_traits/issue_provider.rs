@@ -2,12 +2,12 @@ use async_trait::async_trait;
 
 use crate::{
     error::GithubProviderError,
-    models::{Issue, IssueComment, Label},
+    models::{Issue, IssueComment, Label, LockReason, State, StateReason},
     wrappers::IssueId,
 };
 
 #[async_trait]
-pub trait IssueProvider {
+pub trait IssueProvider: Send + Sync {
     async fn fetch_issue(&self, id: &IssueId) -> Result<Issue, GithubProviderError>;
 
     async fn add_label(&self, id: &IssueId, label: &str) -> Result<Vec<Label>, GithubProviderError>;
@@ -21,4 +21,20 @@ pub trait IssueProvider {
     async fn fetch_issue_comments(&self, id: &IssueId) -> Result<Vec<IssueComment>, GithubProviderError>;
 
     async fn add_comment(&self, id: &IssueId, comment: &str) -> Result<IssueComment, GithubProviderError>;
+
+    async fn add_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError>;
+
+    async fn remove_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError>;
+
+    /// Close or reopen an issue or pull request. The reason is ignored for pull requests.
+    async fn set_state(
+        &self,
+        id: &IssueId,
+        state: State,
+        reason: Option<StateReason>,
+    ) -> Result<Issue, GithubProviderError>;
+
+    async fn lock(&self, id: &IssueId, reason: Option<LockReason>) -> Result<(), GithubProviderError>;
+
+    async fn unlock(&self, id: &IssueId) -> Result<(), GithubProviderError>;
 }
@@ -3,13 +3,13 @@ use async_trait::async_trait;
 use crate::{
     error::GithubProviderError,
     graphql::{review_counts::ReviewCounts, CheckRunStatus, PullRequestComments},
-    models::PullRequest,
+    models::{PullRequest, PullRequestFile},
     models_plus::{MergeParameters, MergeResult},
     wrappers::IssueId,
 };
 
 #[async_trait]
-pub trait PullRequestProvider {
+pub trait PullRequestProvider: Send + Sync {
     async fn fetch_pull_request(&self, id: &IssueId) -> Result<PullRequest, GithubProviderError>;
 
     async fn merge_pull_request(
@@ -17,6 +17,28 @@ pub trait PullRequestProvider {
         id: &IssueId,
         params: MergeParameters,
     ) -> Result<MergeResult, GithubProviderError>;
+
+    /// Convert the pull request to a draft.
+    async fn convert_to_draft(&self, id: &IssueId) -> Result<(), GithubProviderError>;
+
+    /// Fetch the files changed by the pull request.
+    async fn fetch_pull_request_files(&self, id: &IssueId) -> Result<Vec<PullRequestFile>, GithubProviderError>;
+
+    /// Request reviews from individual users (`reviewers`) and/or teams (`team_reviewers`, given by team slug).
+    async fn request_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError>;
+
+    /// Withdraw pending review requests from individual users and/or teams.
+    async fn remove_requested_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError>;
 }
 
 #[async_trait]
@@ -1,13 +1,15 @@
 use async_trait::async_trait;
 
 use crate::{
+    api::GithubApiError,
     error::GithubProviderError,
-    models::{Contributor, DateTime, Event, Label, Repository},
+    models::{Contributor, DateTime, Event, Issue, Label, Repository},
+    models_plus::{CodeOwners, CODEOWNERS_PATHS},
     wrappers::NewLabel,
 };
 
 #[async_trait]
-pub trait RepoProvider {
+pub trait RepoProvider: Sync {
     async fn fetch_repository(&self, owner: &str, repo: &str) -> Result<Repository, GithubProviderError>;
     // Label functionality
     async fn fetch_labels(
@@ -27,6 +29,22 @@ pub trait RepoProvider {
         new: &NewLabel,
     ) -> Result<bool, GithubProviderError>;
     async fn fetch_events(&self, owner: &str, repo: &str, since: DateTime) -> Result<Vec<Event>, GithubProviderError>;
+    /// Fetch every open issue and pull request in the repository, least recently updated first.
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, GithubProviderError>;
+    /// Fetch the raw contents of a file on the repository's default branch.
+    async fn fetch_file_contents(&self, owner: &str, repo: &str, path: &str) -> Result<String, GithubProviderError>;
+    /// Fetch and parse the repository's CODEOWNERS file, checking each of the locations Github supports in turn.
+    /// Returns `None` if the repository does not have a CODEOWNERS file.
+    async fn fetch_code_owners(&self, owner: &str, repo: &str) -> Result<Option<CodeOwners>, GithubProviderError> {
+        for path in CODEOWNERS_PATHS {
+            match self.fetch_file_contents(owner, repo, path).await {
+                Ok(contents) => return Ok(Some(CodeOwners::parse(&contents))),
+                Err(GithubProviderError::GithubApiError(GithubApiError::NotFound(_))) => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(None)
+    }
 }
 
 #[async_trait]
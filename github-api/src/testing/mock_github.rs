@@ -0,0 +1,1006 @@
+use std::{
+    collections::HashMap,
+    sync::{Mutex, MutexGuard},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    api::GithubApiError,
+    error::GithubProviderError,
+    graphql::{
+        org_activity::{org_activity_ql::pageInfoFields, OrgActivity, OrgActivitySearch},
+        review_counts::{pull_request_review_counts_ql::PullRequestReviewState, ReviewCounts},
+        run_status::check_run_status_ql::StatusState,
+        CheckResult,
+        CheckRunStatus,
+        PullRequestComments,
+        RunStatus,
+    },
+    models::{
+        Contributor,
+        DateTime,
+        Event,
+        Issue,
+        IssueComment,
+        IssuePullRequest,
+        Label,
+        LockReason,
+        PullRequest,
+        PullRequestFile,
+        Repository,
+        SimpleUser,
+        State,
+        StateReason,
+        UserType,
+    },
+    models_plus::{MergeMethod, MergeParameters, MergeResult},
+    provider_traits::{
+        CheckRunStatusProvider,
+        Contributors,
+        IssueProvider,
+        OrganizationProvider,
+        PullRequestCommentsProvider,
+        PullRequestProvider,
+        PullRequestReviewSummary,
+        RepoProvider,
+        UserProvider,
+    },
+    wrappers::{GithubHandle, IssueId, NewLabel},
+};
+
+/// The login that comments made through the mock are attributed to.
+pub const MOCK_USER: &str = "gh-pilot";
+
+/// A change that was made to the mock's state through one of the provider traits.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Mutation {
+    AddLabel {
+        id: IssueId,
+        label: String,
+    },
+    RemoveLabel {
+        id: IssueId,
+        label: String,
+    },
+    AddComment {
+        id: IssueId,
+        body: String,
+    },
+    AddAssignees {
+        id: IssueId,
+        assignees: Vec<String>,
+    },
+    RemoveAssignees {
+        id: IssueId,
+        assignees: Vec<String>,
+    },
+    SetState {
+        id: IssueId,
+        state: State,
+        reason: Option<StateReason>,
+    },
+    Lock {
+        id: IssueId,
+        reason: Option<LockReason>,
+    },
+    Unlock {
+        id: IssueId,
+    },
+    ConvertToDraft {
+        id: IssueId,
+    },
+    RequestReviewers {
+        id: IssueId,
+        reviewers: Vec<String>,
+        teams: Vec<String>,
+    },
+    RemoveRequestedReviewers {
+        id: IssueId,
+        reviewers: Vec<String>,
+        teams: Vec<String>,
+    },
+    Merge {
+        id: IssueId,
+        method: MergeMethod,
+    },
+    CreateLabel {
+        repo: String,
+        label: String,
+    },
+    EditLabel {
+        repo: String,
+        label: String,
+        new_name: String,
+    },
+    DeleteLabel {
+        repo: String,
+        label: String,
+    },
+}
+
+#[derive(Default)]
+struct RepoState {
+    labels: Vec<Label>,
+    files: HashMap<String, String>,
+    contributors: Vec<String>,
+}
+
+struct PullRequestState {
+    pr: PullRequest,
+    files: Vec<PullRequestFile>,
+    comments: PullRequestComments,
+    reviews: ReviewCounts,
+    checks: CheckRunStatus,
+}
+
+#[derive(Default)]
+struct MockState {
+    repos: HashMap<String, RepoState>,
+    issues: HashMap<String, Issue>,
+    issue_comments: HashMap<String, Vec<IssueComment>>,
+    pull_requests: HashMap<String, PullRequestState>,
+    users: HashMap<String, SimpleUser>,
+    mutations: Vec<Mutation>,
+    next_id: u64,
+}
+
+impl MockState {
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn issue_mut(&mut self, id: &IssueId) -> Result<&mut Issue, GithubProviderError> {
+        self.issues.get_mut(&id.to_string()).ok_or_else(|| not_found(id))
+    }
+
+    fn pr_mut(&mut self, id: &IssueId) -> Result<&mut PullRequestState, GithubProviderError> {
+        self.pull_requests.get_mut(&id.to_string()).ok_or_else(|| not_found(id))
+    }
+
+    fn repo_mut(&mut self, owner: &str, repo: &str) -> &mut RepoState {
+        self.repos.entry(repo_name(owner, repo)).or_default()
+    }
+
+    /// The pull request, with the fields that it shares with its issue brought up to date.
+    fn pull_request(&self, id: &IssueId) -> Result<PullRequest, GithubProviderError> {
+        let key = id.to_string();
+        let mut pr = self.pull_requests.get(&key).ok_or_else(|| not_found(id))?.pr.clone();
+        if let Some(issue) = self.issues.get(&key) {
+            pr.labels = issue.labels.clone();
+            pr.state = issue.state.clone();
+            pr.locked = issue.locked;
+            pr.active_lock_reason = issue.active_lock_reason.clone();
+            pr.assignees = issue.assignees.clone().unwrap_or_default();
+        }
+        Ok(pr)
+    }
+}
+
+/// An in-memory stand-in for Github that implements all the provider traits.
+///
+/// Set up the repositories, issues and pull requests that a test needs with the `with_*` methods, and hand the mock
+/// to the code under test (usually wrapped in an `Arc`). Every change that is made through the provider traits
+/// updates the mock's state, and is recorded as a [`Mutation`], so tests can check what _would_ have happened on
+/// Github. Requests for issues, pull requests or files that were not set up fail with a `NotFound` error, like they
+/// would on Github.
+#[derive(Default)]
+pub struct MockGithub {
+    state: Mutex<MockState>,
+}
+
+impl MockGithub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Adds an open issue.
+    pub fn with_issue<S: Into<String>>(self, id: &IssueId, title: S) -> Self {
+        {
+            let mut state = self.state();
+            let issue = new_issue(id, title.into(), state.next_id());
+            state.repo_mut(id.owner(), id.repo());
+            state.issues.insert(id.to_string(), issue);
+        }
+        self
+    }
+
+    /// Adds an open, mergeable pull request (and its issue) with no reviews, comments or checks.
+    pub fn with_pull_request<S: Into<String>>(self, id: &IssueId, title: S) -> Self {
+        let title = title.into();
+        {
+            let mut state = self.state();
+            let mut issue = new_issue(id, title.clone(), state.next_id());
+            issue.pull_request = Some(Box::new(IssuePullRequest {
+                diff_url: None,
+                html_url: None,
+                patch_url: None,
+                url: None,
+                merged_at: None,
+            }));
+            let pr = PullRequestState {
+                pr: new_pull_request(id, title.clone(), state.next_id()),
+                files: Vec::new(),
+                comments: PullRequestComments::default(),
+                reviews: ReviewCounts::new(id.clone(), title),
+                checks: CheckRunStatus::default(),
+            };
+            state.repo_mut(id.owner(), id.repo());
+            state.issues.insert(id.to_string(), issue);
+            state.pull_requests.insert(id.to_string(), pr);
+        }
+        self
+    }
+
+    /// Adds a label to an existing issue or pull request.
+    pub fn with_label<S: Into<String>>(self, id: &IssueId, label: S) -> Self {
+        let label = label.into();
+        self.state()
+            .issue_mut(id)
+            .unwrap_or_else(|_| panic!("{id} must be added before its labels"))
+            .labels
+            .push(new_label(label.as_str()));
+        self
+    }
+
+    /// Adds a label definition to a repository.
+    pub fn with_repo_label<S: Into<String>>(self, owner: &str, repo: &str, label: S) -> Self {
+        let label = new_label(label.into().as_str());
+        self.state().repo_mut(owner, repo).labels.push(label);
+        self
+    }
+
+    /// Adds a file to the default branch of a repository, e.g. a `CODEOWNERS` file.
+    pub fn with_file<S: Into<String>>(self, owner: &str, repo: &str, path: &str, contents: S) -> Self {
+        let mut state = self.state();
+        state.repo_mut(owner, repo).files.insert(path.into(), contents.into());
+        drop(state);
+        self
+    }
+
+    pub fn with_contributors(self, owner: &str, repo: &str, logins: &[&str]) -> Self {
+        let logins = logins.iter().map(|s| s.to_string());
+        self.state().repo_mut(owner, repo).contributors.extend(logins);
+        self
+    }
+
+    pub fn with_user(self, login: &str) -> Self {
+        self.state().users.insert(login.into(), new_user(login));
+        self
+    }
+
+    /// Marks a file as changed by a pull request.
+    pub fn with_pr_file(self, id: &IssueId, filename: &str) -> Self {
+        let file = PullRequestFile {
+            sha: None,
+            filename: filename.into(),
+            status: "modified".into(),
+            additions: 1,
+            deletions: 1,
+            changes: 2,
+            previous_filename: None,
+        };
+        self.with_pr(id, |pr| pr.files.push(file))
+    }
+
+    /// Adds a comment to a pull request's conversation.
+    pub fn with_pr_comment(self, id: &IssueId, author: &str, body: &str) -> Self {
+        self.with_pr(id, |pr| {
+            pr.comments.add_comment(author, body);
+        })
+    }
+
+    pub fn with_review(self, id: &IssueId, author: &str, state: PullRequestReviewState) -> Self {
+        self.with_pr(id, |pr| {
+            pr.reviews.add_review(author, state);
+        })
+    }
+
+    pub fn with_check(self, id: &IssueId, name: &str, result: CheckResult, is_required: bool) -> Self {
+        let check = RunStatus {
+            name: name.into(),
+            completed_at: DateTime::now(),
+            result,
+            is_required,
+        };
+        self.with_pr(id, |pr| {
+            pr.checks.add_check(check);
+        })
+    }
+
+    /// Sets the rolled up status of a pull request's checks.
+    pub fn with_overall_status(self, id: &IssueId, status: StatusState) -> Self {
+        self.with_pr(id, |pr| {
+            pr.checks.set_overall_status(Some(status));
+        })
+    }
+
+    pub fn with_merge_conflicts(self, id: &IssueId) -> Self {
+        self.with_pr(id, |pr| pr.pr.mergeable = Some(false))
+    }
+
+    fn with_pr<F: FnOnce(&mut PullRequestState)>(self, id: &IssueId, f: F) -> Self {
+        let mut state = self.state();
+        let pr = state
+            .pr_mut(id)
+            .unwrap_or_else(|_| panic!("Pull request {id} must be added first"));
+        f(pr);
+        drop(state);
+        self
+    }
+
+    /// All the changes made through the provider traits so far, in order.
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state().mutations.clone()
+    }
+
+    pub fn clear_mutations(&self) {
+        self.state().mutations.clear();
+    }
+
+    pub fn issue(&self, id: &IssueId) -> Option<Issue> {
+        self.state().issues.get(&id.to_string()).cloned()
+    }
+
+    pub fn pull_request(&self, id: &IssueId) -> Option<PullRequest> {
+        self.state().pull_request(id).ok()
+    }
+
+    /// The names of the labels on an issue or pull request.
+    pub fn labels(&self, id: &IssueId) -> Vec<String> {
+        self.issue(id)
+            .map(|issue| issue.labels.into_iter().map(|l| l.name).collect())
+            .unwrap_or_default()
+    }
+
+    /// The bodies of the comments that have been added to an issue or pull request through the mock.
+    pub fn comments(&self, id: &IssueId) -> Vec<String> {
+        self.state()
+            .issue_comments
+            .get(&id.to_string())
+            .map(|comments| comments.iter().filter_map(|c| c.body.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    fn record(state: &mut MockState, mutation: Mutation) {
+        state.mutations.push(mutation);
+    }
+}
+
+#[async_trait]
+impl IssueProvider for MockGithub {
+    async fn fetch_issue(&self, id: &IssueId) -> Result<Issue, GithubProviderError> {
+        self.state().issue_mut(id).map(|issue| issue.clone())
+    }
+
+    async fn add_label(&self, id: &IssueId, label: &str) -> Result<Vec<Label>, GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        if !issue.labels.iter().any(|l| l.name == label) {
+            issue.labels.push(new_label(label));
+        }
+        let labels = issue.labels.clone();
+        Self::record(&mut state, Mutation::AddLabel {
+            id: id.clone(),
+            label: label.into(),
+        });
+        Ok(labels)
+    }
+
+    async fn remove_label(&self, id: &IssueId, label: &str, only_if_exists: bool) -> Result<bool, GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        match issue.labels.iter().position(|l| l.name == label) {
+            Some(i) => {
+                issue.labels.remove(i);
+                Self::record(&mut state, Mutation::RemoveLabel {
+                    id: id.clone(),
+                    label: label.into(),
+                });
+                Ok(true)
+            },
+            None if only_if_exists => Ok(false),
+            None => Err(GithubApiError::NotFound(format!("Label does not exist: {label}")).into()),
+        }
+    }
+
+    async fn label_exists(&self, label: &str, id: &IssueId) -> Result<bool, GithubProviderError> {
+        let mut state = self.state();
+        Ok(state.issue_mut(id)?.labels.iter().any(|l| l.name == label))
+    }
+
+    async fn fetch_issue_labels(&self, id: &IssueId) -> Result<Vec<Label>, GithubProviderError> {
+        let mut state = self.state();
+        Ok(state.issue_mut(id)?.labels.clone())
+    }
+
+    async fn fetch_issue_comments(&self, id: &IssueId) -> Result<Vec<IssueComment>, GithubProviderError> {
+        let mut state = self.state();
+        state.issue_mut(id)?;
+        Ok(state.issue_comments.get(&id.to_string()).cloned().unwrap_or_default())
+    }
+
+    async fn add_comment(&self, id: &IssueId, comment: &str) -> Result<IssueComment, GithubProviderError> {
+        let mut state = self.state();
+        state.issue_mut(id)?.comments += 1;
+        let new_comment = new_comment(id, comment, state.next_id());
+        state
+            .issue_comments
+            .entry(id.to_string())
+            .or_default()
+            .push(new_comment.clone());
+        if let Ok(pr) = state.pr_mut(id) {
+            pr.comments.add_comment(MOCK_USER, comment);
+        }
+        Self::record(&mut state, Mutation::AddComment {
+            id: id.clone(),
+            body: comment.into(),
+        });
+        Ok(new_comment)
+    }
+
+    async fn add_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        let current = issue.assignees.get_or_insert_with(Vec::new);
+        for &login in assignees {
+            if !current.iter().any(|u| u.login == login) {
+                current.push(new_user(login));
+            }
+        }
+        let issue = issue.clone();
+        Self::record(&mut state, Mutation::AddAssignees {
+            id: id.clone(),
+            assignees: to_strings(assignees),
+        });
+        Ok(issue)
+    }
+
+    async fn remove_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        if let Some(current) = issue.assignees.as_mut() {
+            current.retain(|u| !assignees.contains(&u.login.as_str()));
+        }
+        let issue = issue.clone();
+        Self::record(&mut state, Mutation::RemoveAssignees {
+            id: id.clone(),
+            assignees: to_strings(assignees),
+        });
+        Ok(issue)
+    }
+
+    async fn set_state(
+        &self,
+        id: &IssueId,
+        new_state: State,
+        reason: Option<StateReason>,
+    ) -> Result<Issue, GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        issue.state = new_state.clone();
+        issue.state_reason = reason.and_then(|r| serde_json::to_value(r).ok()?.as_str().map(String::from));
+        let issue = issue.clone();
+        Self::record(&mut state, Mutation::SetState {
+            id: id.clone(),
+            state: new_state,
+            reason,
+        });
+        Ok(issue)
+    }
+
+    async fn lock(&self, id: &IssueId, reason: Option<LockReason>) -> Result<(), GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        issue.locked = true;
+        issue.active_lock_reason = reason.and_then(|r| serde_json::to_value(r).ok()?.as_str().map(String::from));
+        Self::record(&mut state, Mutation::Lock { id: id.clone(), reason });
+        Ok(())
+    }
+
+    async fn unlock(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        let mut state = self.state();
+        let issue = state.issue_mut(id)?;
+        issue.locked = false;
+        issue.active_lock_reason = None;
+        Self::record(&mut state, Mutation::Unlock { id: id.clone() });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl PullRequestProvider for MockGithub {
+    async fn fetch_pull_request(&self, id: &IssueId) -> Result<PullRequest, GithubProviderError> {
+        self.state().pull_request(id)
+    }
+
+    async fn merge_pull_request(
+        &self,
+        id: &IssueId,
+        params: MergeParameters,
+    ) -> Result<MergeResult, GithubProviderError> {
+        let mut state = self.state();
+        let pr = state.pr_mut(id)?;
+        if pr.pr.merged == Some(true) || pr.pr.mergeable == Some(false) {
+            return Err(GithubApiError::MergeError(format!("Pull Request {id} is not mergeable")).into());
+        }
+        pr.pr.merged = Some(true);
+        pr.pr.merged_at = Some(DateTime::now());
+        state.issue_mut(id)?.state = State::Closed;
+        Self::record(&mut state, Mutation::Merge {
+            id: id.clone(),
+            method: params.merge_method,
+        });
+        Ok(MergeResult {
+            sha: format!("{:040x}", id.number),
+            merged: true,
+            message: "Pull Request successfully merged".into(),
+        })
+    }
+
+    async fn convert_to_draft(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        let mut state = self.state();
+        state.pr_mut(id)?.pr.draft = true;
+        Self::record(&mut state, Mutation::ConvertToDraft { id: id.clone() });
+        Ok(())
+    }
+
+    async fn fetch_pull_request_files(&self, id: &IssueId) -> Result<Vec<PullRequestFile>, GithubProviderError> {
+        Ok(self.state().pr_mut(id)?.files.clone())
+    }
+
+    async fn request_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let mut state = self.state();
+        let requested = &mut state.pr_mut(id)?.pr.requested_reviewers;
+        for &login in reviewers {
+            if !requested.iter().any(|u| u.login == login) {
+                requested.push(new_user(login));
+            }
+        }
+        Self::record(&mut state, Mutation::RequestReviewers {
+            id: id.clone(),
+            reviewers: to_strings(reviewers),
+            teams: to_strings(team_reviewers),
+        });
+        state.pull_request(id)
+    }
+
+    async fn remove_requested_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let mut state = self.state();
+        state
+            .pr_mut(id)?
+            .pr
+            .requested_reviewers
+            .retain(|u| !reviewers.contains(&u.login.as_str()));
+        Self::record(&mut state, Mutation::RemoveRequestedReviewers {
+            id: id.clone(),
+            reviewers: to_strings(reviewers),
+            teams: to_strings(team_reviewers),
+        });
+        state.pull_request(id)
+    }
+}
+
+#[async_trait]
+impl PullRequestCommentsProvider for MockGithub {
+    async fn fetch_pull_request_comments(&self, pr_id: &IssueId) -> Result<PullRequestComments, GithubProviderError> {
+        Ok(self.state().pr_mut(pr_id)?.comments.clone())
+    }
+}
+
+#[async_trait]
+impl PullRequestReviewSummary for MockGithub {
+    async fn fetch_review_summary(&self, pr_id: &IssueId) -> Result<ReviewCounts, GithubProviderError> {
+        Ok(self.state().pr_mut(pr_id)?.reviews.clone())
+    }
+}
+
+#[async_trait]
+impl CheckRunStatusProvider for MockGithub {
+    async fn fetch_check_run(&self, pr_id: &IssueId) -> Result<CheckRunStatus, GithubProviderError> {
+        Ok(self.state().pr_mut(pr_id)?.checks.clone())
+    }
+}
+
+#[async_trait]
+impl RepoProvider for MockGithub {
+    async fn fetch_repository(&self, owner: &str, repo: &str) -> Result<Repository, GithubProviderError> {
+        let name = repo_name(owner, repo);
+        if !self.state().repos.contains_key(&name) {
+            return Err(GithubApiError::NotFound(format!("Repository {name} does not exist")).into());
+        }
+        let mut repository: Repository = template(REPO_TEMPLATE);
+        repository.name = repo.into();
+        repository.full_name = name;
+        repository.owner = new_user(owner);
+        Ok(repository)
+    }
+
+    async fn fetch_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<Label>, GithubProviderError> {
+        let per_page = per_page.unwrap_or(30);
+        let skip = page.unwrap_or(1).saturating_sub(1) * per_page;
+        let mut state = self.state();
+        Ok(state
+            .repo_mut(owner, repo)
+            .labels
+            .iter()
+            .skip(skip)
+            .take(per_page)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_label(&self, owner: &str, repo: &str, label: &str) -> Result<bool, GithubProviderError> {
+        let mut state = self.state();
+        let labels = &mut state.repo_mut(owner, repo).labels;
+        let count = labels.len();
+        labels.retain(|l| l.name != label);
+        let deleted = labels.len() < count;
+        if deleted {
+            Self::record(&mut state, Mutation::DeleteLabel {
+                repo: repo_name(owner, repo),
+                label: label.into(),
+            });
+        }
+        Ok(deleted)
+    }
+
+    async fn assign_labels(&self, owner: &str, repo: &str, labels: &[NewLabel]) -> Result<(), GithubProviderError> {
+        let mut state = self.state();
+        for new in labels {
+            let repo_labels = &mut state.repo_mut(owner, repo).labels;
+            // Like Github, existing labels are left alone
+            if repo_labels.iter().any(|l| l.name == new.name) {
+                continue;
+            }
+            let mut label = new_label(new.name.as_str());
+            label.description = new.description.clone();
+            if let Some(color) = &new.color {
+                label.color = color.clone();
+            }
+            repo_labels.push(label);
+            Self::record(&mut state, Mutation::CreateLabel {
+                repo: repo_name(owner, repo),
+                label: new.name.clone(),
+            });
+        }
+        Ok(())
+    }
+
+    async fn edit_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        label: &str,
+        new: &NewLabel,
+    ) -> Result<bool, GithubProviderError> {
+        let mut state = self.state();
+        let existing = match state.repo_mut(owner, repo).labels.iter_mut().find(|l| l.name == label) {
+            Some(existing) => existing,
+            None => return Ok(false),
+        };
+        existing.name = new.name.clone();
+        existing.description = new.description.clone();
+        if let Some(color) = &new.color {
+            existing.color = color.clone();
+        }
+        Self::record(&mut state, Mutation::EditLabel {
+            repo: repo_name(owner, repo),
+            label: label.into(),
+            new_name: new.name.clone(),
+        });
+        Ok(true)
+    }
+
+    async fn fetch_events(
+        &self,
+        _owner: &str,
+        _repo: &str,
+        _since: DateTime,
+    ) -> Result<Vec<Event>, GithubProviderError> {
+        Ok(Vec::new())
+    }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, GithubProviderError> {
+        let prefix = format!("{}#", repo_name(owner, repo));
+        let state = self.state();
+        let mut issues = state
+            .issues
+            .iter()
+            .filter(|(key, issue)| key.starts_with(&prefix) && issue.state == State::Open)
+            .map(|(_, issue)| issue.clone())
+            .collect::<Vec<Issue>>();
+        issues.sort_by_key(|issue| issue.number);
+        Ok(issues)
+    }
+
+    async fn fetch_file_contents(&self, owner: &str, repo: &str, path: &str) -> Result<String, GithubProviderError> {
+        self.state()
+            .repo_mut(owner, repo)
+            .files
+            .get(path)
+            .cloned()
+            .ok_or_else(|| GithubApiError::NotFound(format!("{path} does not exist")).into())
+    }
+}
+
+#[async_trait]
+impl Contributors for MockGithub {
+    async fn fetch_contributors(&self, owner: &str, repo: &str) -> Result<Vec<Contributor>, GithubProviderError> {
+        let mut state = self.state();
+        let contributors = state
+            .repo_mut(owner, repo)
+            .contributors
+            .iter()
+            .map(|login| Contributor {
+                login: login.clone(),
+                user_type: Some(UserType::User),
+                ..Default::default()
+            })
+            .collect();
+        Ok(contributors)
+    }
+}
+
+#[async_trait]
+impl UserProvider for MockGithub {
+    async fn fetch_details(&self, handle: &GithubHandle) -> Result<Option<SimpleUser>, GithubProviderError> {
+        Ok(self.state().users.get(handle.as_ref()).cloned())
+    }
+
+    async fn fetch_events(
+        &self,
+        _handle: &GithubHandle,
+        _since: DateTime,
+        _auth: bool,
+    ) -> Result<Vec<Event>, GithubProviderError> {
+        Ok(Vec::new())
+    }
+}
+
+#[async_trait]
+impl OrganizationProvider for MockGithub {
+    async fn fetch_activity(
+        &self,
+        _owner: &str,
+        _from: &str,
+        _to: &str,
+        _n: usize,
+        _next_from: Option<pageInfoFields>,
+    ) -> Result<OrgActivitySearch, GithubProviderError> {
+        Ok(OrgActivitySearch {
+            page_info: pageInfoFields {
+                start_cursor: None,
+                end_cursor: None,
+                has_next_page: false,
+                has_previous_page: false,
+            },
+            total_count: 0,
+            search_result: OrgActivity::default(),
+        })
+    }
+}
+
+const ISSUE_TEMPLATE: &str = include_str!("../test_data/issue.json");
+const PR_TEMPLATE: &str = include_str!("../test_data/pr_1000.json");
+const COMMENT_TEMPLATE: &str = include_str!("../test_data/comment.json");
+const REPO_TEMPLATE: &str = include_str!("../test_data/tari_repo.json");
+
+fn template<T: serde::de::DeserializeOwned>(json: &str) -> T {
+    serde_json::from_str(json).expect("Mock Github templates are valid")
+}
+
+fn not_found(id: &IssueId) -> GithubProviderError {
+    GithubApiError::NotFound(format!("{id} does not exist")).into()
+}
+
+fn repo_name(owner: &str, repo: &str) -> String {
+    format!("{owner}/{repo}")
+}
+
+fn to_strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|s| s.to_string()).collect()
+}
+
+fn new_issue(id: &IssueId, title: String, db_id: u64) -> Issue {
+    let mut issue: Issue = template(ISSUE_TEMPLATE);
+    let url = format!(
+        "https://api.github.com/repos/{}/{}/issues/{}",
+        id.owner, id.repo, id.number
+    );
+    issue.id = db_id;
+    issue.number = id.number;
+    issue.title = title;
+    issue.url = url.as_str().into();
+    issue.html_url = format!("https://github.com/{}/{}/issues/{}", id.owner, id.repo, id.number).into();
+    issue.state = State::Open;
+    issue.state_reason = None;
+    issue.labels = Vec::new();
+    issue.assignee = None;
+    issue.assignees = Some(Vec::new());
+    issue.locked = false;
+    issue.active_lock_reason = None;
+    issue.comments = 0;
+    issue.pull_request = None;
+    issue.closed_at = None;
+    issue
+}
+
+fn new_pull_request(id: &IssueId, title: String, db_id: u64) -> PullRequest {
+    let mut pr: PullRequest = template(PR_TEMPLATE);
+    pr.id = db_id;
+    pr.number = id.number;
+    pr.title = title;
+    pr.url = format!(
+        "https://api.github.com/repos/{}/{}/pulls/{}",
+        id.owner, id.repo, id.number
+    )
+    .into();
+    pr.html_url = format!("https://github.com/{}/{}/pull/{}", id.owner, id.repo, id.number).into();
+    pr.state = State::Open;
+    pr.draft = false;
+    pr.merged = Some(false);
+    pr.merged_at = None;
+    pr.mergeable = Some(true);
+    pr.labels = Vec::new();
+    pr.assignee = None;
+    pr.assignees = Vec::new();
+    pr.requested_reviewers = Vec::new();
+    pr.requested_teams = Some(Vec::new());
+    pr
+}
+
+fn new_comment(id: &IssueId, body: &str, db_id: u64) -> IssueComment {
+    let mut comment: IssueComment = template(COMMENT_TEMPLATE);
+    comment.id = db_id as i64;
+    comment.body = Some(body.into());
+    comment.user = Some(new_user(MOCK_USER));
+    comment.issue_url = Some(
+        format!(
+            "https://api.github.com/repos/{}/{}/issues/{}",
+            id.owner, id.repo, id.number
+        )
+        .into(),
+    );
+    comment.pull_request_url = None;
+    comment
+}
+
+fn new_label(name: &str) -> Label {
+    Label {
+        id: 0,
+        node_id: String::new(),
+        url: format!("https://api.github.com/labels/{name}").into(),
+        name: name.into(),
+        description: None,
+        color: "ededed".into(),
+        default: false,
+    }
+}
+
+fn new_user(login: &str) -> SimpleUser {
+    let user = serde_json::json!({
+        "login": login,
+        "id": 0,
+        "node_id": "",
+        "avatar_url": "",
+        "url": format!("https://api.github.com/users/{login}"),
+        "type": "User",
+    });
+    serde_json::from_value(user).expect("Mock users are valid")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MockGithub, Mutation, MOCK_USER};
+    use crate::{
+        graphql::review_counts::pull_request_review_counts_ql::PullRequestReviewState,
+        models::State,
+        models_plus::{MergeMethod, MergeParameters},
+        provider_traits::{
+            Contributors,
+            IssueProvider,
+            PullRequestCommentsProvider,
+            PullRequestProvider,
+            PullRequestReviewSummary,
+            RepoProvider,
+        },
+        wrappers::IssueId,
+    };
+
+    #[tokio::test]
+    async fn labels_and_comments() {
+        let id = IssueId::new("tari-project", "tari", 42);
+        let github = MockGithub::new()
+            .with_pull_request(&id, "Fix the thing")
+            .with_label(&id, "T-bug");
+        assert!(github.label_exists("T-bug", &id).await.unwrap());
+        github.add_label(&id, "P-merge").await.unwrap();
+        assert!(github.remove_label(&id, "T-bug", false).await.unwrap());
+        assert!(!github.remove_label(&id, "T-bug", true).await.unwrap());
+        assert!(github.remove_label(&id, "T-bug", false).await.is_err());
+        github.add_comment(&id, "Thanks!").await.unwrap();
+
+        assert_eq!(github.labels(&id), vec!["P-merge"]);
+        assert_eq!(github.pull_request(&id).unwrap().labels[0].name, "P-merge");
+        assert_eq!(github.comments(&id), vec!["Thanks!"]);
+        let pr_comments = github.fetch_pull_request_comments(&id).await.unwrap();
+        assert_eq!(pr_comments.comments[0].author, MOCK_USER);
+        assert_eq!(github.mutations(), vec![
+            Mutation::AddLabel {
+                id: id.clone(),
+                label: "P-merge".into()
+            },
+            Mutation::RemoveLabel {
+                id: id.clone(),
+                label: "T-bug".into()
+            },
+            Mutation::AddComment {
+                id: id.clone(),
+                body: "Thanks!".into()
+            },
+        ]);
+    }
+
+    #[tokio::test]
+    async fn unknown_issues_are_not_found() {
+        let github = MockGithub::new();
+        let id = IssueId::new("tari-project", "tari", 1);
+        assert!(github.fetch_issue(&id).await.is_err());
+        assert!(github.add_label(&id, "T-bug").await.is_err());
+        assert!(github.mutations().is_empty());
+    }
+
+    #[tokio::test]
+    async fn merge() {
+        let id = IssueId::new("tari-project", "tari", 7);
+        let conflicted = IssueId::new("tari-project", "tari", 8);
+        let github = MockGithub::new()
+            .with_pull_request(&id, "Ready")
+            .with_pull_request(&conflicted, "Not ready")
+            .with_merge_conflicts(&conflicted)
+            .with_review(&id, "alice", PullRequestReviewState::APPROVED)
+            .with_contributors("tari-project", "tari", &["alice", "bob"]);
+        assert_eq!(github.fetch_review_summary(&id).await.unwrap().approvals(), 1);
+        assert_eq!(
+            github.fetch_contributors("tari-project", "tari").await.unwrap().len(),
+            2
+        );
+        let params = MergeParameters {
+            merge_method: MergeMethod::Squash,
+            ..Default::default()
+        };
+        assert!(github.merge_pull_request(&conflicted, params.clone()).await.is_err());
+        assert!(github.merge_pull_request(&id, params.clone()).await.unwrap().merged);
+        assert!(github.merge_pull_request(&id, params).await.is_err());
+        let pr = github.fetch_pull_request(&id).await.unwrap();
+        assert_eq!(pr.state, State::Closed);
+        assert_eq!(pr.merged, Some(true));
+        assert_eq!(github.fetch_open_issues("tari-project", "tari").await.unwrap().len(), 1);
+        assert_eq!(github.mutations(), vec![Mutation::Merge {
+            id,
+            method: MergeMethod::Squash
+        }]);
+    }
+}
@@ -0,0 +1,6 @@
+//! Test doubles for code that talks to Github. Only available in tests, or with the `testing` feature enabled.
+mod mock_github;
+#[cfg(any(test, feature = "stand-in"))]
+pub mod stand_in;
+
+pub use mock_github::{MockGithub, Mutation, MOCK_USER};
@@ -0,0 +1,472 @@
+//! A local stand-in for the Github API, for exercising [`ClientProxy`] and the GraphQL queries over real HTTP without
+//! network access.
+//!
+//! [`GithubStandIn`] serves canned responses that are read from fixture files. By default, fixtures are looked up in
+//! `github-api/src/test_data` and `github-api/src/graphql/data`, so the JSON samples used by the unit tests can be
+//! served as is. REST routes are matched on the method and path (the query string is ignored), and GraphQL requests
+//! are matched on the name of the operation, e.g. `PullRequestCommentsQL`. Requests that don't match anything get a
+//! Github-style 404.
+//!
+//! The server runs on its own thread and is shut down when the [`StandInServer`] handle is dropped.
+use std::{
+    collections::HashMap,
+    io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    thread::JoinHandle,
+};
+
+use actix_web::{
+    dev::ServerHandle,
+    http::header::{ETAG, IF_NONE_MATCH},
+    rt::System,
+    web,
+    App,
+    HttpRequest,
+    HttpResponse,
+    HttpServer,
+};
+use log::*;
+use reqwest::Method;
+use serde_json::{json, Value};
+
+use crate::api::ClientProxy;
+
+/// The path that GraphQL queries are posted to.
+pub const GRAPHQL_PATH: &str = "/graphql";
+
+/// A canned response from the stand-in server.
+#[derive(Clone, Debug)]
+pub struct CannedResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl CannedResponse {
+    /// A `200 OK` JSON response.
+    pub fn ok<S: Into<String>>(body: S) -> Self {
+        Self::with_status(200, body)
+    }
+
+    pub fn with_status<S: Into<String>>(status: u16, body: S) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Adds a header to the response. If an `ETag` header is given, requests with a matching `If-None-Match` header
+    /// get a `304 Not Modified`, like they would from Github.
+    pub fn with_header<K: Into<String>, V: Into<String>>(mut self, name: K, value: V) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    fn etag(&self) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(ETAG.as_str()))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A request that was received by the stand-in server.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    pub path: String,
+    pub query: String,
+    pub body: String,
+}
+
+impl RecordedRequest {
+    /// The GraphQL operation name, if this is a GraphQL request.
+    pub fn graphql_operation(&self) -> Option<String> {
+        if self.path != GRAPHQL_PATH {
+            return None;
+        }
+        let body: Value = serde_json::from_str(&self.body).ok()?;
+        body["operationName"].as_str().map(String::from)
+    }
+}
+
+enum Fixture {
+    File(String),
+    Response(CannedResponse),
+}
+
+/// Configures and starts a local stand-in for the Github API.
+pub struct GithubStandIn {
+    fixture_dirs: Vec<PathBuf>,
+    routes: Vec<(Method, String, Fixture)>,
+    graphql: Vec<(String, Fixture)>,
+}
+
+impl Default for GithubStandIn {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl GithubStandIn {
+    /// A stand-in that looks up fixtures in this crate's test data directories.
+    pub fn new() -> Self {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
+        Self {
+            fixture_dirs: vec![root.join("test_data"), root.join("graphql").join("data")],
+            routes: Vec::new(),
+            graphql: Vec::new(),
+        }
+    }
+
+    /// Adds a directory to look up fixtures in. Directories added later are searched first.
+    pub fn with_fixture_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.fixture_dirs.insert(0, dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// Serves the contents of a fixture file, e.g. `pr_1000.json`, for requests to `path`.
+    pub fn with_route<S: Into<String>>(mut self, method: Method, path: S, fixture: &str) -> Self {
+        self.routes.push((method, path.into(), Fixture::File(fixture.into())));
+        self
+    }
+
+    /// Serves the given response for requests to `path`.
+    pub fn with_response<S: Into<String>>(mut self, method: Method, path: S, response: CannedResponse) -> Self {
+        self.routes.push((method, path.into(), Fixture::Response(response)));
+        self
+    }
+
+    /// Serves the contents of a fixture file for the GraphQL operation with the given name. The fixture may be a full
+    /// GraphQL response, or just its `data`, like the `sample_*.json` files.
+    pub fn with_graphql<S: Into<String>>(mut self, operation: S, fixture: &str) -> Self {
+        self.graphql.push((operation.into(), Fixture::File(fixture.into())));
+        self
+    }
+
+    pub fn with_graphql_response<S: Into<String>>(mut self, operation: S, response: CannedResponse) -> Self {
+        self.graphql.push((operation.into(), Fixture::Response(response)));
+        self
+    }
+
+    /// Reads all the fixtures and starts the server on a free local port.
+    pub fn start(self) -> io::Result<StandInServer> {
+        let mut routes = HashMap::new();
+        for (method, path, fixture) in &self.routes {
+            routes.insert(route_key(method.as_str(), path), self.load(fixture, false)?);
+        }
+        let mut graphql = HashMap::new();
+        for (operation, fixture) in &self.graphql {
+            graphql.insert(operation.clone(), self.load(fixture, true)?);
+        }
+        let state = Arc::new(StandInState {
+            routes,
+            graphql,
+            requests: Mutex::new(Vec::new()),
+        });
+
+        let (tx, rx) = mpsc::channel();
+        let server_state = Arc::clone(&state);
+        let thread = std::thread::spawn(move || {
+            let system = System::new();
+            // The server must be started from within the actix runtime
+            let server = system.block_on(async move {
+                let server = HttpServer::new(move || {
+                    App::new()
+                        .app_data(web::Data::from(Arc::clone(&server_state)))
+                        .default_service(web::to(respond))
+                })
+                .workers(1)
+                .disable_signals()
+                .bind(("127.0.0.1", 0))?;
+                let addr = server.addrs()[0];
+                Ok::<_, io::Error>((addr, server.run()))
+            });
+            let (addr, server) = match server {
+                Ok(server) => server,
+                Err(e) => {
+                    let _ = tx.send(Err(e));
+                    return;
+                },
+            };
+            let _ = tx.send(Ok((addr, server.handle())));
+            if let Err(e) = system.block_on(server) {
+                warn!("🐙 Github stand-in server stopped with an error. {e}");
+            }
+        });
+        let (addr, handle) = rx.recv().map_err(|e| io::Error::new(io::ErrorKind::BrokenPipe, e))??;
+        debug!("🐙 Github stand-in server is listening on {addr}");
+        Ok(StandInServer {
+            addr,
+            handle,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    fn load(&self, fixture: &Fixture, graphql: bool) -> io::Result<CannedResponse> {
+        let name = match fixture {
+            Fixture::File(name) => name,
+            Fixture::Response(response) => return Ok(response.clone()),
+        };
+        let path = self
+            .fixture_dirs
+            .iter()
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("Fixture {name} does not exist")))?;
+        let body = std::fs::read_to_string(path)?;
+        if !graphql {
+            return Ok(CannedResponse::ok(body));
+        }
+        let value: Value =
+            serde_json::from_str(&body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+        let is_full_response = value.get("data").is_some() || value.get("errors").is_some();
+        let body = if is_full_response {
+            value
+        } else {
+            json!({ "data": value })
+        };
+        Ok(CannedResponse::ok(body.to_string()))
+    }
+}
+
+struct StandInState {
+    routes: HashMap<String, CannedResponse>,
+    graphql: HashMap<String, CannedResponse>,
+    requests: Mutex<Vec<RecordedRequest>>,
+}
+
+fn route_key(method: &str, path: &str) -> String {
+    format!("{method} {path}")
+}
+
+async fn respond(req: HttpRequest, body: web::Bytes, state: web::Data<StandInState>) -> HttpResponse {
+    let recorded = RecordedRequest {
+        method: req.method().to_string(),
+        path: req.path().to_string(),
+        query: req.query_string().to_string(),
+        body: String::from_utf8_lossy(&body).into_owned(),
+    };
+    trace!("🐙 Github stand-in received {} {}", recorded.method, recorded.path);
+    let canned = match recorded.graphql_operation() {
+        Some(operation) => state.graphql.get(&operation).cloned().ok_or(operation),
+        None => state
+            .routes
+            .get(&route_key(&recorded.method, &recorded.path))
+            .cloned()
+            .ok_or_else(|| recorded.path.clone()),
+    };
+    state.requests.lock().unwrap().push(recorded);
+    let canned = match canned {
+        Ok(canned) => canned,
+        Err(what) => {
+            debug!("🐙 Github stand-in has no response for {what}");
+            return HttpResponse::NotFound().json(json!({
+                "message": "Not Found",
+                "documentation_url": "https://docs.github.com/rest"
+            }));
+        },
+    };
+    let if_none_match = req.headers().get(IF_NONE_MATCH).and_then(|v| v.to_str().ok());
+    let status = match (canned.etag(), if_none_match) {
+        (Some(etag), Some(tag)) if etag == tag => 304,
+        _ => canned.status,
+    };
+    let status = actix_web::http::StatusCode::from_u16(status).unwrap_or(actix_web::http::StatusCode::OK);
+    let mut response = HttpResponse::build(status);
+    for (name, value) in &canned.headers {
+        response.insert_header((name.as_str(), value.as_str()));
+    }
+    if status.as_u16() == 304 || canned.body.is_empty() {
+        return response.finish();
+    }
+    response.content_type("application/json").body(canned.body)
+}
+
+/// A running stand-in server. The server is stopped when this is dropped.
+pub struct StandInServer {
+    addr: SocketAddr,
+    handle: ServerHandle,
+    state: Arc<StandInState>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl StandInServer {
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    pub fn graphql_url(&self) -> String {
+        format!("{}{GRAPHQL_PATH}", self.base_url())
+    }
+
+    /// An unauthenticated client that sends its REST and GraphQL requests to this server.
+    pub fn client(&self) -> ClientProxy {
+        let mut client = ClientProxy::default();
+        client.with_base_url(&self.base_url());
+        client
+    }
+
+    /// All the requests the server has received so far, in order.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.state.requests.lock().unwrap().clone()
+    }
+}
+
+impl Drop for StandInServer {
+    fn drop(&mut self) {
+        // The stop command is sent straight away; there's no need to wait for the returned future.
+        drop(self.handle.stop(false));
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use reqwest::Method;
+
+    use super::{CannedResponse, GithubStandIn};
+    use crate::{
+        api::{AuthToken, GithubApiError, PullRequestRequest},
+        provider_traits::{IssueProvider, PullRequestCommentsProvider, PullRequestProvider},
+        wrappers::IssueId,
+        GithubProvider,
+    };
+
+    #[tokio::test]
+    async fn rest_and_graphql_over_http() {
+        let server = GithubStandIn::new()
+            .with_route(Method::GET, "/repos/tari-project/tari/pulls/1000", "pr_1000.json")
+            .with_graphql("PullRequestCommentsQL", "sample_thread.json")
+            .start()
+            .unwrap();
+        let github = GithubProvider::new("user", "token").with_base_url(&server.base_url());
+        let id = IssueId::new("tari-project", "tari", 1000);
+
+        let pr = github.fetch_pull_request(&id).await.unwrap();
+        assert_eq!(pr.number, 1000);
+        let comments = github.fetch_pull_request_comments(&id).await.unwrap();
+        assert_eq!(comments.comments.len(), 3);
+        assert_eq!(comments.threads.len(), 2);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].path, "/repos/tari-project/tari/pulls/1000");
+        assert_eq!(requests[1].graphql_operation().unwrap(), "PullRequestCommentsQL");
+    }
+
+    #[tokio::test]
+    async fn not_found() {
+        let server = GithubStandIn::new().start().unwrap();
+        let github = GithubProvider::new("user", "token").with_base_url(&server.base_url());
+        let id = IssueId::new("tari-project", "tari", 1);
+        assert!(github.fetch_issue(&id).await.is_err());
+        assert!(github.fetch_pull_request_comments(&id).await.is_err());
+        assert!(GithubStandIn::new()
+            .with_route(Method::GET, "/nope", "no_such_fixture.json")
+            .start()
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn conditional_requests() {
+        let pr = include_str!("../test_data/pr_1000.json");
+        let server = GithubStandIn::new()
+            .with_response(
+                Method::GET,
+                "/repos/tari-project/tari/pulls/1000",
+                CannedResponse::ok(pr).with_header("ETag", "\"v1\""),
+            )
+            .with_response(
+                Method::GET,
+                "/repos/tari-project/tari/pulls/1",
+                CannedResponse::with_status(422, ""),
+            )
+            .start()
+            .unwrap();
+        let client = server.client();
+        let req = PullRequestRequest::new("tari-project", "tari", 1000);
+        for _ in 0..2 {
+            assert_eq!(req.fetch(&client).await.unwrap().number, 1000);
+        }
+        assert_eq!(client.cached_responses(), 1);
+        let requests = server.requests();
+        assert_eq!(requests.len(), 2);
+
+        let err = PullRequestRequest::new("tari-project", "tari", 1).fetch(&client).await;
+        assert!(matches!(err, Err(GithubApiError::HttpResponse(_))));
+    }
+
+    #[tokio::test]
+    async fn retries() {
+        let bad_gateway = CannedResponse::with_status(502, "");
+        let rate_limited = CannedResponse::with_status(429, "").with_header("Retry-After", "0");
+        let server = GithubStandIn::new()
+            .with_response(Method::POST, "/repos/tari-project/tari/issues/1/comments", bad_gateway)
+            .with_response(Method::GET, "/repos/tari-project/tari/pulls/1", rate_limited)
+            .start()
+            .unwrap();
+        let client = server.client();
+        // Server errors on POSTs are not retried, since the comment may have been posted anyway
+        let response = client
+            .execute(client.post("/repos/tari-project/tari/issues/1/comments"))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 502);
+        assert_eq!(server.requests().len(), 1);
+
+        let err = PullRequestRequest::new("tari-project", "tari", 1).fetch(&client).await;
+        assert!(matches!(err, Err(GithubApiError::RateLimited(_))));
+        // The GET is sent once, and then retried 3 times
+        assert_eq!(server.requests().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn forbidden_is_not_a_rate_limit() {
+        let forbidden = CannedResponse::with_status(403, r#"{"message":"Resource not accessible by integration"}"#)
+            .with_header("x-ratelimit-limit", "5000")
+            .with_header("x-ratelimit-remaining", "4000")
+            .with_header("x-ratelimit-reset", "1677672000");
+        let server = GithubStandIn::new()
+            .with_response(Method::GET, "/repos/tari-project/tari/pulls/1", forbidden)
+            .start()
+            .unwrap();
+        let client = server.client();
+        let response = client
+            .execute(client.get("/repos/tari-project/tari/pulls/1", true))
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 403);
+        assert!(response.text().await.unwrap().contains("Resource not accessible"));
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn quota_per_identity() {
+        let quota = CannedResponse::ok("{}")
+            .with_header("x-ratelimit-limit", "5000")
+            .with_header("x-ratelimit-remaining", "4000")
+            .with_header("x-ratelimit-reset", "1677672000");
+        let server = GithubStandIn::new()
+            .with_response(Method::GET, "/rate_limit", quota)
+            .start()
+            .unwrap();
+        let client = server.client();
+        let mut installation = client.clone();
+        installation.with_bearer_auth("installation 42", AuthToken::from("token"));
+        assert_eq!(installation.identity(), "installation 42");
+        installation
+            .execute(installation.get("/rate_limit", true))
+            .await
+            .unwrap();
+        assert_eq!(installation.rate_limit().unwrap().remaining, 4000);
+        // The original client shares the quota map, but authenticates as someone else
+        assert!(client.rate_limit().is_none());
+    }
+}
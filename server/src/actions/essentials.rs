@@ -1,10 +1,10 @@
-use github_pilot_api::GithubEvent;
+use github_pilot_api::{models::LockReason, GithubEvent};
 use log::warn;
 use serde::{Deserialize, Serialize};
 
 use crate::actions::{
     closure_action::ClosureActionParams,
-    github_action::GithubActionParams,
+    github_action::{GithubActionParams, ReviewRequest, StateChange},
     merge_action::MergeActionParamsBuilder,
     MergeActionParams,
 };
@@ -105,6 +105,80 @@ impl GithubActionBuilder {
         self
     }
 
+    /// Post a comment on the PR or issue. The body may contain `{{placeholders}}`, which are filled in from the
+    /// triggering event. See [`crate::actions::TemplateContext`] for details.
+    pub fn add_comment<S: Into<String>>(mut self, template: S) -> Self {
+        self.params = Some(GithubActionParams::add_comment(template));
+        self
+    }
+
+    /// Assign users to the PR or issue. User names may contain `{{placeholders}}`, e.g. `{{pr.author}}`.
+    pub fn assign<I: IntoIterator<Item = S>, S: Into<String>>(mut self, users: I) -> Self {
+        self.params = Some(GithubActionParams::assign(users));
+        self
+    }
+
+    /// Remove users from the PR or issue's assignees.
+    pub fn unassign<I: IntoIterator<Item = S>, S: Into<String>>(mut self, users: I) -> Self {
+        self.params = Some(GithubActionParams::unassign(users));
+        self
+    }
+
+    /// Request reviews on the PR from the given users and teams.
+    pub fn request_review(mut self, request: ReviewRequest) -> Self {
+        self.params = Some(GithubActionParams::request_review(request));
+        self
+    }
+
+    /// Withdraw pending review requests on the PR from the given users and teams.
+    pub fn remove_review_request(mut self, request: ReviewRequest) -> Self {
+        self.params = Some(GithubActionParams::remove_review_request(request));
+        self
+    }
+
+    /// Close the PR or issue, optionally with a (templated) comment and a reason.
+    pub fn close(mut self, change: StateChange) -> Self {
+        self.params = Some(GithubActionParams::close(change));
+        self
+    }
+
+    /// Reopen the PR or issue, optionally with a (templated) comment and a reason.
+    pub fn reopen(mut self, change: StateChange) -> Self {
+        self.params = Some(GithubActionParams::reopen(change));
+        self
+    }
+
+    /// Lock the conversation on the PR or issue.
+    pub fn lock(mut self, reason: Option<LockReason>) -> Self {
+        self.params = Some(GithubActionParams::lock(reason));
+        self
+    }
+
+    /// Unlock the conversation on the PR or issue.
+    pub fn unlock(mut self) -> Self {
+        self.params = Some(GithubActionParams::unlock());
+        self
+    }
+
+    /// Convert the PR to a draft.
+    pub fn convert_to_draft(mut self) -> Self {
+        self.params = Some(GithubActionParams::convert_to_draft());
+        self
+    }
+
+    /// Request reviews from the owners (according to the repo's CODEOWNERS file) of the files changed in the PR.
+    pub fn request_code_owner_reviews(mut self) -> Self {
+        self.params = Some(GithubActionParams::request_code_owner_reviews());
+        self
+    }
+
+    /// Post a comment mentioning the owners of the files changed in the PR. Use `{{code_owners}}` in the template
+    /// where the mentions should go.
+    pub fn mention_code_owners<S: Into<String>>(mut self, template: S) -> Self {
+        self.params = Some(GithubActionParams::mention_code_owners(template));
+        self
+    }
+
     pub fn build(self) -> Actions {
         match self.params {
             None => {
@@ -223,6 +297,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_github_action_builder_comment() {
+        let action = Actions::github().add_comment("Thanks @{{sender.login}}!").build();
+
+        match action {
+            Actions::Github(p) => assert_eq!(*p, GithubActionParams::AddComment("Thanks @{{sender.login}}!".into())),
+            _ => panic!("Expected an AddComment action"),
+        }
+    }
+
+    #[test]
+    fn test_github_action_builder_reviews() {
+        let action = Actions::github()
+            .request_review(ReviewRequest::new().reviewer("sdbondi").team("core-devs"))
+            .build();
+
+        match action {
+            Actions::Github(p) => match *p {
+                GithubActionParams::RequestReview(r) => {
+                    assert_eq!(r.reviewers, vec!["sdbondi"]);
+                    assert_eq!(r.teams, vec!["core-devs"]);
+                },
+                _ => panic!("Expected a RequestReview action"),
+            },
+            _ => panic!("Expected a github action"),
+        }
+
+        let action = Actions::github().assign(["{{pr.author}}"]).build();
+        match action {
+            Actions::Github(p) => assert_eq!(*p, GithubActionParams::Assign(vec!["{{pr.author}}".into()])),
+            _ => panic!("Expected an Assign action"),
+        }
+    }
+
     #[test]
     fn auto_merge_builder() {
         let action = Actions::auto_merge()
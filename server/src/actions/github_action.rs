@@ -21,8 +21,10 @@ use std::sync::Arc;
 
 use actix::{Actor, Context, Handler, Message, ResponseFuture, Running, Supervised, SystemService};
 use github_pilot_api::{
-    models::{IssuesEvent, PullRequestEvent},
-    provider_traits::IssueProvider,
+    error::GithubProviderError,
+    models::{IssuesEvent, LockReason, PullRequestEvent, State, StateReason},
+    models_plus::CodeOwner,
+    provider_traits::{IssueProvider, PullRequestProvider, RepoProvider},
     wrappers::IssueId,
     GithubEvent,
     GithubProvider,
@@ -30,17 +32,115 @@ use github_pilot_api::{
 use log::*;
 use serde::{Deserialize, Serialize};
 
-use crate::pub_sub::ActionResult;
+use crate::{
+    actions::TemplateContext,
+    dry_run,
+    dry_run::DryRunProvider,
+    events::BroadcastEvent,
+    pub_sub::ActionResult,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum GithubActionParams {
-    // Adds a label to the PR or Issue (context dependent)
+    // Adds a label to the PR or Issue (context dependent). The label is a template (see `TemplateContext`)
     AddLabel(String),
-    // Removes a label from the PR or Issue (context dependent)
+    // Removes a label from the PR or Issue (context dependent). The label is a template (see `TemplateContext`)
     RemoveLabel(String),
     // Adds or removes the `merge-conflict` label depending on whether the PR has merge conflicts
     CheckConflicts,
+    // Posts a comment on the PR or Issue. The body is a template that is rendered against the triggering event (see
+    // `TemplateContext` for the supported placeholders)
+    AddComment(String),
+    // Assigns the users to the PR or Issue. User names may be templates, e.g. `{{sender.login}}`
+    Assign(Vec<String>),
+    // Removes the users from the PR or Issue's assignees
+    Unassign(Vec<String>),
+    // Requests reviews on the PR from users and/or teams
+    RequestReview(ReviewRequest),
+    // Withdraws pending review requests on the PR from users and/or teams
+    RemoveReviewRequest(ReviewRequest),
+    // Resolves the code owners of the files changed in the PR (using the repo's CODEOWNERS file) and notifies them
+    CodeOwners(CodeOwnersAction),
+    // Closes the PR or Issue, optionally leaving a comment first
+    Close(StateChange),
+    // Reopens the PR or Issue, optionally leaving a comment afterwards
+    Reopen(StateChange),
+    // Locks the conversation on the PR or Issue
+    Lock(Option<LockReason>),
+    // Unlocks the conversation on the PR or Issue
+    Unlock,
+    // Converts the PR to a draft
+    ConvertToDraft,
+}
+
+/// Parameters for closing or reopening an issue or PR.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateChange {
+    /// A comment to post along with the change. This is a template (see [`TemplateContext`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// The reason for the change. Github only records this for issues.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<StateReason>,
+}
+
+impl StateChange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn with_reason(mut self, reason: StateReason) -> Self {
+        self.reason = Some(reason);
+        self
+    }
+}
+
+/// How code owners should be notified about changes to the files they own.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodeOwnersAction {
+    /// Request a review from every code owner (user or team), except the PR author.
+    RequestReview,
+    /// Post a comment that mentions the code owners. The comment is a template (see [`TemplateContext`]) with an
+    /// extra `{{code_owners}}` placeholder, which holds the space-separated list of owners.
+    Mention(String),
+}
+
+/// A set of users and teams to request (or withdraw requests for) reviews from.
+///
+/// Teams are given by their slug, without the organization prefix, e.g. `core-devs`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReviewRequest {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reviewers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub teams: Vec<String>,
+}
+
+impl ReviewRequest {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn reviewer<S: Into<String>>(mut self, user: S) -> Self {
+        self.reviewers.push(user.into());
+        self
+    }
+
+    pub fn team<S: Into<String>>(mut self, team: S) -> Self {
+        self.teams.push(team.into());
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.reviewers.is_empty() && self.teams.is_empty()
+    }
 }
 
 impl GithubActionParams {
@@ -55,6 +155,54 @@ impl GithubActionParams {
     pub fn check_conflicts() -> Self {
         GithubActionParams::CheckConflicts
     }
+
+    pub fn add_comment<S: Into<String>>(template: S) -> Self {
+        GithubActionParams::AddComment(template.into())
+    }
+
+    pub fn assign<I: IntoIterator<Item = S>, S: Into<String>>(users: I) -> Self {
+        GithubActionParams::Assign(users.into_iter().map(Into::into).collect())
+    }
+
+    pub fn unassign<I: IntoIterator<Item = S>, S: Into<String>>(users: I) -> Self {
+        GithubActionParams::Unassign(users.into_iter().map(Into::into).collect())
+    }
+
+    pub fn request_review(request: ReviewRequest) -> Self {
+        GithubActionParams::RequestReview(request)
+    }
+
+    pub fn remove_review_request(request: ReviewRequest) -> Self {
+        GithubActionParams::RemoveReviewRequest(request)
+    }
+
+    pub fn close(change: StateChange) -> Self {
+        GithubActionParams::Close(change)
+    }
+
+    pub fn reopen(change: StateChange) -> Self {
+        GithubActionParams::Reopen(change)
+    }
+
+    pub fn lock(reason: Option<LockReason>) -> Self {
+        GithubActionParams::Lock(reason)
+    }
+
+    pub fn unlock() -> Self {
+        GithubActionParams::Unlock
+    }
+
+    pub fn convert_to_draft() -> Self {
+        GithubActionParams::ConvertToDraft
+    }
+
+    pub fn request_code_owner_reviews() -> Self {
+        GithubActionParams::CodeOwners(CodeOwnersAction::RequestReview)
+    }
+
+    pub fn mention_code_owners<S: Into<String>>(template: S) -> Self {
+        GithubActionParams::CodeOwners(CodeOwnersAction::Mention(template.into()))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -63,6 +211,7 @@ pub struct GithubActionMessage {
     event_name: String,
     event: GithubEvent,
     params: GithubActionParams,
+    trigger: Option<BroadcastEvent>,
 }
 
 impl GithubActionMessage {
@@ -72,9 +221,16 @@ impl GithubActionMessage {
             event_name: event_name.into(),
             event,
             params,
+            trigger: None,
         }
     }
 
+    /// Attach the broadcast event that triggered this action, if any.
+    pub fn with_trigger(mut self, trigger: Option<BroadcastEvent>) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
     pub fn name(&self) -> &str {
         self.name.as_str()
     }
@@ -90,22 +246,88 @@ impl GithubActionMessage {
     pub fn params(&self) -> &GithubActionParams {
         &self.params
     }
+
+    pub fn trigger(&self) -> Option<&BroadcastEvent> {
+        self.trigger.as_ref()
+    }
+
+    /// Builds the context used to render templated action parameters.
+    pub fn template_context(&self) -> TemplateContext {
+        let ctx = TemplateContext::from_event(self.event_name(), self.event());
+        match self.trigger() {
+            Some(trigger) => ctx.with_broadcast_event(trigger),
+            None => ctx,
+        }
+    }
+
+    /// Renders each of the templates, dropping any that render to an empty string.
+    fn render_all(&self, templates: &[String]) -> Vec<String> {
+        let ctx = self.template_context();
+        templates
+            .iter()
+            .map(|t| ctx.render(t))
+            .filter(|s| !s.trim().is_empty())
+            .collect()
+    }
+
+    /// Renders a label template, so that labels can be taken from the triggering event, e.g. from a `/label T-foo`
+    /// comment with `{{command.arg1}}`.
+    fn render_label(&self, label: &str) -> String {
+        self.template_context().render(label).trim().to_string()
+    }
+
+    fn render_review_request(&self, request: &ReviewRequest) -> ReviewRequest {
+        ReviewRequest {
+            reviewers: self.render_all(&request.reviewers),
+            teams: self.render_all(&request.teams),
+        }
+    }
 }
 
 impl Message for GithubActionMessage {
     type Result = ActionResult;
 }
 
+/// The parts of the Github API that [`GithubActionExecutor`] uses.
+pub trait GithubActionProvider: IssueProvider + PullRequestProvider + RepoProvider + Send + Sync {}
+
+impl<T: IssueProvider + PullRequestProvider + RepoProvider + Send + Sync> GithubActionProvider for T {}
+
 pub struct GithubActionExecutor {
-    provider: Arc<GithubProvider>,
+    provider: Arc<dyn GithubActionProvider>,
+    /// Set when the executor uses the Github API directly, so that the provider can be swapped for one that uses the
+    /// credentials of the Github App installation that sent the event.
+    github: Option<Arc<GithubProvider>>,
 }
 
 impl Default for GithubActionExecutor {
     fn default() -> Self {
         // Will pull credentials from envars if possible
-        let github_provider = GithubProvider::default();
+        let github_provider = Arc::new(GithubProvider::default());
         Self {
-            provider: Arc::new(github_provider),
+            provider: github_provider.clone(),
+            github: Some(github_provider),
+        }
+    }
+}
+
+impl GithubActionExecutor {
+    /// Creates an executor that carries out actions with the given provider, e.g.
+    /// [`github_pilot_api::testing::MockGithub`] in tests.
+    pub fn new(provider: Arc<dyn GithubActionProvider>) -> Self {
+        Self { provider, github: None }
+    }
+
+    /// When running as a Github App, returns a provider that acts with the token of the installation that sent the
+    /// event. Otherwise the provider is returned unchanged.
+    async fn scoped_provider(
+        provider: Arc<dyn GithubActionProvider>,
+        github: Option<Arc<GithubProvider>>,
+        event: &GithubEvent,
+    ) -> Result<Arc<dyn GithubActionProvider>, GithubProviderError> {
+        match github {
+            Some(github) if github.is_app() => Ok(Arc::new(github.for_event(event).await?)),
+            _ => Ok(provider),
         }
     }
 }
@@ -140,25 +362,99 @@ impl Handler<GithubActionMessage> for GithubActionExecutor {
 
     fn handle(&mut self, msg: GithubActionMessage, _ctx: &mut Self::Context) -> Self::Result {
         let provider = Arc::clone(&self.provider);
+        let github = self.github.clone();
 
         let fut = async move {
+            let provider = match Self::scoped_provider(provider, github, &msg.event).await {
+                Ok(scoped) => scoped,
+                Err(e) => {
+                    warn!("🐙 Could not get Github credentials for {}. {e}", msg.event.summary());
+                    return ActionResult::Failed;
+                },
+            };
+            let provider: Arc<dyn GithubActionProvider> = match dry_run::is_enabled() {
+                true => Arc::new(DryRunProvider::new(provider)),
+                false => provider,
+            };
+            // Labels are templates, so render them once, up front. Other actions don't use `label`.
+            let label = match msg.params() {
+                GithubActionParams::AddLabel(template) | GithubActionParams::RemoveLabel(template) => {
+                    let label = msg.render_label(template);
+                    if label.is_empty() {
+                        info!(
+                            "🐙🏷 The label `{template}` is empty for {}. Skipping.",
+                            msg.event.summary()
+                        );
+                        return ActionResult::ConditionsNotMet;
+                    }
+                    label
+                },
+                _ => String::new(),
+            };
             if let Some(id) = msg.event.related_pull_request() {
-                match msg.params {
-                    GithubActionParams::AddLabel(label) => {
+                match msg.params() {
+                    GithubActionParams::AddLabel(_) => {
                         return Self::add_label_to_pr(&provider, &id, &label).await;
                     },
-                    GithubActionParams::RemoveLabel(label) => {
+                    GithubActionParams::RemoveLabel(_) => {
                         return Self::remove_label_from_pr(&provider, &id, &label).await;
                     },
                     _ => {}, // no-op
                 }
             }
+            let issue_id = msg.event().related_issue();
+            let pr_id = msg.event().related_pull_request();
+            match (msg.params(), issue_id, pr_id) {
+                (GithubActionParams::AddComment(template), Some(id), _) => {
+                    let body = msg.template_context().render(template);
+                    return Self::add_comment(&provider, &id, &body).await;
+                },
+                (GithubActionParams::Assign(users), Some(id), _) => {
+                    let users = msg.render_all(users);
+                    return Self::assign(&provider, &id, &users).await;
+                },
+                (GithubActionParams::Unassign(users), Some(id), _) => {
+                    let users = msg.render_all(users);
+                    return Self::unassign(&provider, &id, &users).await;
+                },
+                (GithubActionParams::RequestReview(request), _, Some(id)) => {
+                    let request = msg.render_review_request(request);
+                    return Self::request_review(&provider, &id, &request).await;
+                },
+                (GithubActionParams::RemoveReviewRequest(request), _, Some(id)) => {
+                    let request = msg.render_review_request(request);
+                    return Self::remove_review_request(&provider, &id, &request).await;
+                },
+                (GithubActionParams::CodeOwners(action), _, Some(id)) => {
+                    return Self::notify_code_owners(&provider, &id, action, &msg).await;
+                },
+                (GithubActionParams::Close(change), Some(id), pr_id) => {
+                    let comment = change.comment.as_ref().map(|c| msg.template_context().render(c));
+                    let reason = if pr_id.is_some() { None } else { change.reason };
+                    return Self::close(&provider, &id, comment, reason).await;
+                },
+                (GithubActionParams::Reopen(change), Some(id), pr_id) => {
+                    let comment = change.comment.as_ref().map(|c| msg.template_context().render(c));
+                    let reason = if pr_id.is_some() { None } else { change.reason };
+                    return Self::reopen(&provider, &id, comment, reason).await;
+                },
+                (GithubActionParams::Lock(reason), Some(id), _) => {
+                    return Self::lock(&provider, &id, *reason).await;
+                },
+                (GithubActionParams::Unlock, Some(id), _) => {
+                    return Self::unlock(&provider, &id).await;
+                },
+                (GithubActionParams::ConvertToDraft, _, Some(id)) => {
+                    return Self::convert_to_draft(&provider, &id).await;
+                },
+                _ => {}, // no-op
+            }
             match (msg.event(), msg.params()) {
-                (GithubEvent::Issues(event), GithubActionParams::AddLabel(label)) => {
-                    Self::add_label_to_issue(&provider, event, label).await
+                (GithubEvent::Issues(event), GithubActionParams::AddLabel(_)) => {
+                    Self::add_label_to_issue(&provider, event, &label).await
                 },
-                (GithubEvent::Issues(event), GithubActionParams::RemoveLabel(label)) => {
-                    Self::remove_label_from_issue(&provider, event, label).await
+                (GithubEvent::Issues(event), GithubActionParams::RemoveLabel(_)) => {
+                    Self::remove_label_from_issue(&provider, event, &label).await
                 },
                 (GithubEvent::PullRequest(event), GithubActionParams::CheckConflicts) => {
                     Self::check_and_label_merge_conflicts(provider, event).await
@@ -179,7 +475,11 @@ impl Handler<GithubActionMessage> for GithubActionExecutor {
 }
 
 impl GithubActionExecutor {
-    async fn add_label_to_issue(provider: &Arc<GithubProvider>, event: &IssuesEvent, label: &String) -> ActionResult {
+    async fn add_label_to_issue(
+        provider: &Arc<dyn GithubActionProvider>,
+        event: &IssuesEvent,
+        label: &String,
+    ) -> ActionResult {
         let repo = event.repo();
         let owner = event.owner();
         let issue_number = event.number();
@@ -197,7 +497,7 @@ impl GithubActionExecutor {
     }
 
     async fn remove_label_from_issue(
-        provider: &Arc<GithubProvider>,
+        provider: &Arc<dyn GithubActionProvider>,
         event: &IssuesEvent,
         label: &String,
     ) -> ActionResult {
@@ -219,7 +519,7 @@ impl GithubActionExecutor {
         }
     }
 
-    async fn add_label_to_pr(provider: &Arc<GithubProvider>, id: &IssueId, label: &String) -> ActionResult {
+    async fn add_label_to_pr(provider: &Arc<dyn GithubActionProvider>, id: &IssueId, label: &String) -> ActionResult {
         debug!("🐙🏷 Adding label {label} to PR {id}");
         let res = provider.add_label(id, label).await;
         ActionResult::from_result(
@@ -229,7 +529,11 @@ impl GithubActionExecutor {
         )
     }
 
-    async fn remove_label_from_pr(provider: &Arc<GithubProvider>, id: &IssueId, label: &String) -> ActionResult {
+    async fn remove_label_from_pr(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        label: &String,
+    ) -> ActionResult {
         debug!("🐙🏷 Removing label [{label}] from PR {id}");
         match provider.remove_label(id, label, false).await {
             Ok(true) => {
@@ -247,7 +551,234 @@ impl GithubActionExecutor {
         }
     }
 
-    async fn check_and_label_merge_conflicts(provider: Arc<GithubProvider>, event: &PullRequestEvent) -> ActionResult {
+    async fn add_comment(provider: &Arc<dyn GithubActionProvider>, id: &IssueId, body: &str) -> ActionResult {
+        debug!("🐙💬 Adding comment to {id}");
+        let res = provider.add_comment(id, body).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙💬 Added comment to {id}"),
+            |e| warn!("🐙💬 Failed to add comment to {id}: {e}"),
+        )
+    }
+
+    async fn assign(provider: &Arc<dyn GithubActionProvider>, id: &IssueId, users: &[String]) -> ActionResult {
+        if users.is_empty() {
+            info!("🐙👤 No users were given for {id}, so nobody was assigned");
+            return ActionResult::Success;
+        }
+        let users = users.iter().map(String::as_str).collect::<Vec<&str>>();
+        debug!("🐙👤 Assigning {} to {id}", users.join(", "));
+        let res = provider.add_assignees(id, &users).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙👤 Assigned {} to {id}", users.join(", ")),
+            |e| warn!("🐙👤 Failed to assign users to {id}: {e}"),
+        )
+    }
+
+    async fn unassign(provider: &Arc<dyn GithubActionProvider>, id: &IssueId, users: &[String]) -> ActionResult {
+        if users.is_empty() {
+            info!("🐙👤 No users were given for {id}, so nobody was unassigned");
+            return ActionResult::Success;
+        }
+        let users = users.iter().map(String::as_str).collect::<Vec<&str>>();
+        debug!("🐙👤 Unassigning {} from {id}", users.join(", "));
+        let res = provider.remove_assignees(id, &users).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙👤 Unassigned {} from {id}", users.join(", ")),
+            |e| warn!("🐙👤 Failed to unassign users from {id}: {e}"),
+        )
+    }
+
+    async fn request_review(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        request: &ReviewRequest,
+    ) -> ActionResult {
+        if request.is_empty() {
+            info!("🐙👀 No reviewers were given for PR {id}, so no reviews were requested");
+            return ActionResult::Success;
+        }
+        let reviewers = request.reviewers.iter().map(String::as_str).collect::<Vec<&str>>();
+        let teams = request.teams.iter().map(String::as_str).collect::<Vec<&str>>();
+        debug!("🐙👀 Requesting reviews on PR {id} from {request:?}");
+        let res = provider.request_reviewers(id, &reviewers, &teams).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙👀 Requested reviews on PR {id}"),
+            |e| warn!("🐙👀 Failed to request reviews on PR {id}: {e}"),
+        )
+    }
+
+    async fn remove_review_request(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        request: &ReviewRequest,
+    ) -> ActionResult {
+        if request.is_empty() {
+            return ActionResult::Success;
+        }
+        let reviewers = request.reviewers.iter().map(String::as_str).collect::<Vec<&str>>();
+        let teams = request.teams.iter().map(String::as_str).collect::<Vec<&str>>();
+        debug!("🐙👀 Removing review requests on PR {id} for {request:?}");
+        let res = provider.remove_requested_reviewers(id, &reviewers, &teams).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙👀 Removed review requests on PR {id}"),
+            |e| warn!("🐙👀 Failed to remove review requests on PR {id}: {e}"),
+        )
+    }
+
+    async fn close(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        comment: Option<String>,
+        reason: Option<StateReason>,
+    ) -> ActionResult {
+        // Comment first, so that the explanation appears before the issue is closed
+        if let Some(comment) = comment {
+            if matches!(Self::add_comment(provider, id, &comment).await, ActionResult::Failed) {
+                return ActionResult::Failed;
+            }
+        }
+        debug!("🐙🚪 Closing {id}");
+        let res = provider.set_state(id, State::Closed, reason).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙🚪 Closed {id}"),
+            |e| warn!("🐙🚪 Failed to close {id}: {e}"),
+        )
+    }
+
+    async fn reopen(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        comment: Option<String>,
+        reason: Option<StateReason>,
+    ) -> ActionResult {
+        debug!("🐙🚪 Reopening {id}");
+        let res = provider.set_state(id, State::Open, reason).await;
+        let result = ActionResult::from_result(
+            res,
+            || info!("🐙🚪 Reopened {id}"),
+            |e| warn!("🐙🚪 Failed to reopen {id}: {e}"),
+        );
+        match (result, comment) {
+            (ActionResult::Success, Some(comment)) => Self::add_comment(provider, id, &comment).await,
+            (result, _) => result,
+        }
+    }
+
+    async fn lock(provider: &Arc<dyn GithubActionProvider>, id: &IssueId, reason: Option<LockReason>) -> ActionResult {
+        debug!("🐙🔒 Locking conversation on {id}");
+        let res = provider.lock(id, reason).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙🔒 Locked conversation on {id}"),
+            |e| warn!("🐙🔒 Failed to lock conversation on {id}: {e}"),
+        )
+    }
+
+    async fn unlock(provider: &Arc<dyn GithubActionProvider>, id: &IssueId) -> ActionResult {
+        debug!("🐙🔓 Unlocking conversation on {id}");
+        let res = provider.unlock(id).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙🔓 Unlocked conversation on {id}"),
+            |e| warn!("🐙🔓 Failed to unlock conversation on {id}: {e}"),
+        )
+    }
+
+    async fn convert_to_draft(provider: &Arc<dyn GithubActionProvider>, id: &IssueId) -> ActionResult {
+        debug!("🐙📝 Converting PR {id} to draft");
+        let res = provider.convert_to_draft(id).await;
+        ActionResult::from_result(
+            res,
+            || info!("🐙📝 PR {id} converted to draft"),
+            |e| warn!("🐙📝 Failed to convert PR {id} to draft: {e}"),
+        )
+    }
+
+    async fn notify_code_owners(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+        action: &CodeOwnersAction,
+        msg: &GithubActionMessage,
+    ) -> ActionResult {
+        let owners = match Self::resolve_code_owners(provider, id).await {
+            Ok(owners) => owners,
+            Err(e) => {
+                warn!("🐙🧑‍⚖️ Could not determine the code owners for PR {id}. {e}");
+                return ActionResult::Failed;
+            },
+        };
+        if owners.is_empty() {
+            info!("🐙🧑‍⚖️ None of the files in PR {id} have code owners");
+            return ActionResult::Success;
+        }
+        match action {
+            CodeOwnersAction::RequestReview => {
+                let author = match provider.fetch_pull_request(id).await {
+                    Ok(pr) => pr.user.map(|u| u.login).unwrap_or_default(),
+                    Err(e) => {
+                        warn!("🐙🧑‍⚖️ Could not fetch PR {id}. {e}");
+                        return ActionResult::Failed;
+                    },
+                };
+                // Github refuses review requests to the PR author, and can't request reviews from email addresses or
+                // from teams outside the repository owner's organisation
+                let mut request = ReviewRequest::new();
+                for owner in owners {
+                    match owner {
+                        CodeOwner::User(user) if !user.eq_ignore_ascii_case(&author) => {
+                            request = request.reviewer(user);
+                        },
+                        CodeOwner::Team { org, team } if org.eq_ignore_ascii_case(id.owner()) => {
+                            request = request.team(team)
+                        },
+                        CodeOwner::Team { org, team } => {
+                            warn!(
+                                "🐙🧑‍⚖️ Not requesting a review from @{org}/{team} on PR {id}. Only teams in the {} \
+                                 organisation can be asked for reviews",
+                                id.owner()
+                            );
+                        },
+                        _ => {},
+                    }
+                }
+                Self::request_review(provider, id, &request).await
+            },
+            CodeOwnersAction::Mention(template) => {
+                let mentions = owners.iter().map(|o| o.to_string()).collect::<Vec<String>>().join(" ");
+                let body = msg.template_context().with("code_owners", mentions).render(template);
+                Self::add_comment(provider, id, &body).await
+            },
+        }
+    }
+
+    async fn resolve_code_owners(
+        provider: &Arc<dyn GithubActionProvider>,
+        id: &IssueId,
+    ) -> Result<Vec<CodeOwner>, GithubProviderError> {
+        let code_owners = match provider.fetch_code_owners(id.owner(), id.repo()).await? {
+            Some(c) => c,
+            None => {
+                debug!("🐙🧑‍⚖️ {}/{} does not have a CODEOWNERS file", id.owner(), id.repo());
+                return Ok(vec![]);
+            },
+        };
+        let files = provider.fetch_pull_request_files(id).await?;
+        let paths = files
+            .iter()
+            .flat_map(|f| std::iter::once(f.filename.as_str()).chain(f.previous_filename.as_deref()));
+        Ok(code_owners.owners_for(paths))
+    }
+
+    async fn check_and_label_merge_conflicts(
+        provider: Arc<dyn GithubActionProvider>,
+        event: &PullRequestEvent,
+    ) -> ActionResult {
         let id = IssueId::new(event.owner(), event.repo(), event.number());
         debug!("🐙🤺 Checking merge conflict status for PR {id}");
         let conflict_label = "P-conflicts";
@@ -39,6 +39,11 @@ impl MergeActionParams {
             .any(|pattern| comment.split('\n').any(|line| pattern.is_match(line)))
     }
 
+    /// The regular expressions that a comment line must match to count as an ACK.
+    pub fn ack_patterns(&self) -> &[String] {
+        self.ack_patterns.as_slice()
+    }
+
     pub fn min_acks_required(&self) -> usize {
         self.acks_required
     }
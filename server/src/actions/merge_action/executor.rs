@@ -21,6 +21,8 @@ use log::*;
 
 use crate::{
     actions::merge_action::{message::MergeActionMessage, MergeActionParams},
+    dry_run,
+    dry_run::DryRunProvider,
     events::{BroadcastEvent, BroadcastEventMessage, Progress},
     pub_sub::{ActionResult, PubSubActor},
 };
@@ -33,19 +35,17 @@ pub struct MergeExecutor {
     contributors: Arc<dyn Contributors>,
     checks: Arc<dyn CheckRunStatusProvider>,
     issues: Arc<dyn IssueProvider>,
+    /// Set when the executor uses the Github API directly, so that the providers can be swapped for ones that use
+    /// the credentials of the Github App installation that sent the event.
+    github: Option<Arc<GithubProvider>>,
 }
 
 impl Default for MergeExecutor {
     fn default() -> Self {
         let provider = Arc::new(GithubProvider::default());
-        MergeExecutor {
-            provider: provider.clone(),
-            comments: provider.clone(),
-            reviews: provider.clone(),
-            contributors: provider.clone(),
-            checks: provider.clone(),
-            issues: provider,
-        }
+        let mut executor = Self::from_provider(provider.clone());
+        executor.github = Some(provider);
+        executor
     }
 }
 
@@ -65,9 +65,52 @@ impl MergeExecutor {
             contributors,
             checks,
             issues,
+            github: None,
+        }
+    }
+
+    /// Creates an executor that uses a single provider for everything, e.g.
+    /// [`github_pilot_api::testing::MockGithub`] in tests.
+    pub fn from_provider<P>(provider: Arc<P>) -> Self
+    where P: PullRequestProvider
+            + PullRequestCommentsProvider
+            + PullRequestReviewSummary
+            + Contributors
+            + CheckRunStatusProvider
+            + IssueProvider
+            + 'static {
+        Self::new(
+            provider.clone(),
+            provider.clone(),
+            provider.clone(),
+            provider.clone(),
+            provider.clone(),
+            provider,
+        )
+    }
+
+    /// Returns an executor whose providers use the credentials of the Github App installation that sent the event.
+    /// Executors that were created with custom providers are returned unchanged.
+    async fn for_event(&self, event: &GithubEvent) -> Result<Self, GithubProviderError> {
+        match &self.github {
+            Some(github) if github.is_app() => {
+                let scoped = Arc::new(github.for_event(event).await?);
+                let mut result = Self::from_provider(scoped);
+                result.github = self.github.clone();
+                Ok(result)
+            },
+            _ => Ok(self.clone()),
         }
     }
 
+    /// Returns an executor that records the merges and labels it would make in the dry-run report, instead of making
+    /// them.
+    fn dry_run(mut self) -> Self {
+        self.provider = Arc::new(DryRunProvider::new(self.provider));
+        self.issues = Arc::new(DryRunProvider::new(self.issues));
+        self
+    }
+
     /// Obtains a list of contributors from the [`Contributors`] provider.
     async fn fetch_contributors(&self, id: &IssueId) -> Result<Vec<String>, GithubProviderError> {
         let contributors = self
@@ -315,6 +358,17 @@ impl Handler<MergeActionMessage> for MergeExecutor {
                     return ActionResult::Failed;
                 },
             };
+            let this = match this.for_event(&event).await {
+                Ok(scoped) => scoped,
+                Err(e) => {
+                    warn!("⏫ Could not get Github credentials for PR {id}. {e}");
+                    return ActionResult::Failed;
+                },
+            };
+            let this = match dry_run::is_enabled() {
+                true => this.dry_run(),
+                false => this,
+            };
             let contributors = match this.fetch_contributors(&id).await {
                 Ok(contributors) => {
                     trace!("⏫ Fetched {} contributors to {id}", contributors.len());
@@ -342,50 +396,21 @@ impl Handler<MergeActionMessage> for MergeExecutor {
 
 #[cfg(test)]
 mod test {
-    use async_trait::async_trait;
-    use github_pilot_api::{
-        error::GithubProviderError,
-        graphql::PullRequestComments,
-        models::Contributor,
-        provider_traits::Contributors,
-    };
-
-    use crate::actions::merge_action::{MergeActionParams, MergeExecutor};
-
-    pub struct MockProvider {
-        contributors: Vec<String>,
-    }
+    use std::sync::Arc;
 
-    impl MockProvider {
-        pub fn new<S: Into<String>>(contributors: Vec<S>) -> Self {
-            MockProvider {
-                contributors: contributors.into_iter().map(|s| s.into()).collect(),
-            }
-        }
-    }
+    use github_pilot_api::{graphql::PullRequestComments, testing::MockGithub, wrappers::IssueId};
 
-    #[async_trait]
-    impl Contributors for MockProvider {
-        async fn fetch_contributors(&self, _owner: &str, _repo: &str) -> Result<Vec<Contributor>, GithubProviderError> {
-            let contributors = self
-                .contributors
-                .iter()
-                .map(|c| Contributor {
-                    login: c.clone(),
-                    ..Default::default()
-                })
-                .collect();
-            Ok(contributors)
-        }
-    }
+    use crate::actions::merge_action::{MergeActionParams, MergeExecutor};
 
     #[tokio::test]
     async fn fetch_contributors() {
-        let provider = MockProvider::new(vec!["foo", "bar"]);
-        let contributors = provider.fetch_contributors("owner", "repo").await.unwrap();
-        assert_eq!(contributors.len(), 2);
-        assert_eq!(contributors[0].login, "foo");
-        assert_eq!(contributors[1].login, "bar");
+        let github = MockGithub::new().with_contributors("owner", "repo", &["foo", "bar"]);
+        let executor = MergeExecutor::from_provider(Arc::new(github));
+        let contributors = executor
+            .fetch_contributors(&IssueId::new("owner", "repo", 1))
+            .await
+            .unwrap();
+        assert_eq!(contributors, vec!["foo", "bar"]);
     }
 
     #[test]
@@ -20,8 +20,18 @@ mod closure_action;
 mod essentials;
 mod github_action;
 mod merge_action;
+mod template;
 
 pub use closure_action::{ClosureActionExecutor, ClosureActionMessage, ClosureActionParams};
 pub use essentials::Actions;
-pub use github_action::{GithubActionExecutor, GithubActionMessage, GithubActionParams};
+pub use github_action::{
+    CodeOwnersAction,
+    GithubActionExecutor,
+    GithubActionMessage,
+    GithubActionParams,
+    GithubActionProvider,
+    ReviewRequest,
+    StateChange,
+};
 pub use merge_action::{MergeActionMessage, MergeActionParams, MergeExecutor};
+pub use template::TemplateContext;
@@ -0,0 +1,256 @@
+//! A tiny, mustache-flavoured template renderer for action text, such as the bodies of comments posted by Github
+//! Pilot.
+//!
+//! Placeholders take the form `{{path.to.value}}` (whitespace inside the braces is ignored) and are replaced with
+//! values extracted from the triggering [`GithubEvent`] and, for subscriptions, the [`BroadcastEvent`] that fired
+//! them. The following values are available, where they make sense for the event:
+//!
+//! * `event.name` - the name of the webhook or broadcast event,
+//! * `sender.login` - the user that triggered the event,
+//! * `repo.name`, `repo.owner`, `repo.full_name` - the repository the event belongs to,
+//! * `pr.number`, `pr.title`, `pr.author`, `pr.url` - the related pull request,
+//! * `issue.number`, `issue.title`, `issue.author`, `issue.url` - the related issue (or pull request),
+//! * `acks.current`, `acks.total` - ACK progress, for `AcksNeeded` subscriptions,
+//! * `reviews.current`, `reviews.total` - review progress, for `ReviewsNeeded` subscriptions,
+//! * `command.name`, `command.args`, `command.arg1`, `command.arg2`, ... - the first slash command in the triggering
+//!   comment, e.g. `/label T-foo` gives `label` and `T-foo`. `command.args` is all the arguments, separated by spaces,
+//! * `commands.<name>.args`, `commands.<name>.arg1`, ... - the arguments of the first `/<name>` command in the comment.
+//!
+//! Unknown placeholders render as an empty string.
+//!
+//! ```
+//! # use ghp_server::actions::TemplateContext;
+//! let ctx = TemplateContext::new()
+//!     .with("sender.login", "bob")
+//!     .with("pr.number", 42);
+//! assert_eq!(
+//!     ctx.render("Thanks for #{{pr.number}}, @{{ sender.login }}!"),
+//!     "Thanks for #42, @bob!"
+//! );
+//! ```
+use std::{collections::HashMap, fmt::Display};
+
+use github_pilot_api::{models::Issue, models_plus::SlashCommand, wrappers::RepoId, GithubEvent};
+use log::debug;
+use regex::{Captures, Regex};
+
+use crate::events::BroadcastEvent;
+
+/// The set of values that a template can be rendered against.
+#[derive(Clone, Debug, Default)]
+pub struct TemplateContext {
+    values: HashMap<String, String>,
+}
+
+impl TemplateContext {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new context populated with the values extracted from the given Github event.
+    pub fn from_event(event_name: &str, event: &GithubEvent) -> Self {
+        let mut ctx = Self::new().with("event.name", event_name);
+        if let Some(info) = event.common_fields() {
+            let repo = &info.repository;
+            ctx.insert("sender.login", &info.sender.login);
+            ctx.insert("repo.name", &repo.name);
+            ctx.insert("repo.owner", &repo.owner.login);
+            ctx.insert("repo.full_name", &repo.full_name);
+        }
+        if let Some(id) = event.related_issue() {
+            ctx.insert("issue.number", id.number());
+        }
+        if let Some(id) = event.related_pull_request() {
+            ctx.insert("pr.number", id.number());
+        }
+        let pr = match event {
+            GithubEvent::PullRequest(ev) => Some(&ev.pull_request),
+            GithubEvent::PullRequestReview(ev) => Some(&ev.pull_request),
+            GithubEvent::PullRequestReviewComment(ev) => Some(&ev.pull_request),
+            _ => None,
+        };
+        if let Some(pr) = pr {
+            let author = pr.user.as_ref().map(|u| u.login.as_str()).unwrap_or_default();
+            for prefix in ["pr", "issue"] {
+                ctx.insert(format!("{prefix}.title"), &pr.title);
+                ctx.insert(format!("{prefix}.author"), author);
+                ctx.insert(format!("{prefix}.url"), &pr.html_url);
+            }
+        }
+        let issue = match event {
+            GithubEvent::Issues(ev) => Some(&ev.issue),
+            GithubEvent::IssueComment(ev) => Some(&ev.issue),
+            _ => None,
+        };
+        if let Some(issue) = issue {
+            let author = issue.user.as_ref().map(|u| u.login.as_str()).unwrap_or_default();
+            ctx.insert("issue.title", &issue.title);
+            ctx.insert("issue.author", author);
+            ctx.insert("issue.url", &issue.html_url);
+            if issue.is_pull_request() {
+                ctx.insert("pr.title", &issue.title);
+                ctx.insert("pr.author", author);
+                ctx.insert("pr.url", &issue.html_url);
+            }
+        }
+        let commands = event.slash_commands();
+        if let Some(command) = commands.first() {
+            ctx.insert("command.name", &command.command);
+            ctx.insert_args("command", command);
+        }
+        for command in &commands {
+            let prefix = format!("commands.{}", command.command);
+            if ctx.get(&format!("{prefix}.args")).is_none() {
+                ctx.insert_args(&prefix, command);
+            }
+        }
+        ctx
+    }
+
+    /// Creates a new context populated with the values of an issue (or pull request) fetched from the API, for use
+    /// when there is no triggering event, such as in scheduled jobs.
+    pub fn from_issue(repo: &RepoId, issue: &Issue) -> Self {
+        let author = issue.user().map(|u| u.login.as_str()).unwrap_or_default();
+        let mut ctx = Self::new()
+            .with("repo.name", repo.repo())
+            .with("repo.owner", repo.owner())
+            .with("repo.full_name", repo);
+        let prefixes: &[&str] = if issue.pull_request.is_some() {
+            &["issue", "pr"]
+        } else {
+            &["issue"]
+        };
+        for prefix in prefixes {
+            ctx.insert(format!("{prefix}.number"), issue.number);
+            ctx.insert(format!("{prefix}.title"), &issue.title);
+            ctx.insert(format!("{prefix}.author"), author);
+            ctx.insert(format!("{prefix}.url"), &issue.html_url);
+        }
+        ctx
+    }
+
+    /// Adds the progress data carried by a broadcast event (if any) to the context. The broadcast event name
+    /// replaces `event.name`.
+    pub fn with_broadcast_event(mut self, event: &BroadcastEvent) -> Self {
+        let (name, progress) = match event {
+            BroadcastEvent::AcksNeeded(p) => ("AcksNeeded", Some(("acks", p))),
+            BroadcastEvent::ReviewsNeeded(p) => ("ReviewsNeeded", Some(("reviews", p))),
+            BroadcastEvent::AcksThresholdReached => ("AcksThresholdReached", None),
+            BroadcastEvent::ReviewsThresholdReached => ("ReviewsThresholdReached", None),
+            BroadcastEvent::ChangesRequested => ("ChangesRequested", None),
+        };
+        self.insert("event.name", name);
+        if let Some((prefix, p)) = progress {
+            self.insert(format!("{prefix}.current"), p.current);
+            self.insert(format!("{prefix}.total"), p.total);
+        }
+        self
+    }
+
+    /// Adds (or replaces) a value in the context.
+    pub fn with<K: Into<String>, V: Display>(mut self, key: K, value: V) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    pub fn insert<K: Into<String>, V: Display>(&mut self, key: K, value: V) {
+        self.values.insert(key.into(), value.to_string());
+    }
+
+    fn insert_args(&mut self, prefix: &str, command: &SlashCommand) {
+        self.insert(format!("{prefix}.args"), command.args.join(" "));
+        for (i, arg) in command.args.iter().enumerate() {
+            self.insert(format!("{prefix}.arg{}", i + 1), arg);
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.values.get(key).map(String::as_str)
+    }
+
+    /// Replaces every `{{placeholder}}` in the template with its value in this context.
+    pub fn render(&self, template: &str) -> String {
+        let re = Regex::new(r"\{\{\s*([\w.]+)\s*\}\}").expect("Template placeholder regex is invalid");
+        re.replace_all(template, |caps: &Captures| {
+            let key = &caps[1];
+            self.get(key).map(String::from).unwrap_or_else(|| {
+                debug!("🐙📝 No value for template placeholder '{key}'. It will be left blank.");
+                String::new()
+            })
+        })
+        .into_owned()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::{wrappers::RepoId, GithubEvent};
+
+    use super::TemplateContext;
+    use crate::events::{BroadcastEvent, Progress};
+
+    #[test]
+    fn render_placeholders() {
+        let ctx = TemplateContext::new().with("a.b", "x").with("n", 3);
+        assert_eq!(ctx.render("{{a.b}}-{{ n }}-{{missing}}"), "x-3-");
+        assert_eq!(ctx.render("no placeholders {here}"), "no placeholders {here}");
+    }
+
+    #[test]
+    fn pull_request_context() {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        let ctx = TemplateContext::from_event("pull_request", &event);
+        let pr = event.pull_request().unwrap();
+        assert_eq!(ctx.get("pr.number"), Some(pr.number().to_string().as_str()));
+        assert_eq!(ctx.get("issue.number"), ctx.get("pr.number"));
+        assert_eq!(ctx.get("sender.login"), Some(pr.info.sender.login.as_str()));
+        assert_eq!(ctx.get("pr.title"), Some(pr.pull_request.title.as_str()));
+        assert_eq!(ctx.get("event.name"), Some("pull_request"));
+    }
+
+    #[test]
+    fn issue_context() {
+        let data = include_str!("../../../github-api/src/test_data/issue_event.json");
+        let event = GithubEvent::try_from_webhook_info("issues", data).unwrap();
+        let ctx = TemplateContext::from_event("issues", &event);
+        assert_eq!(
+            ctx.render("@{{sender.login}} assigned {{repo.full_name}}#{{issue.number}}{{pr.number}}"),
+            "@sdbondi assigned tari-project/tari#4630"
+        );
+        assert_eq!(ctx.get("issue.author"), Some("hansieodendaal"));
+    }
+
+    #[test]
+    fn slash_command_context() {
+        let data = include_str!("../../test-data/issue_comment_command.json");
+        let event = GithubEvent::try_from_webhook_info("issue_comment", data).unwrap();
+        let ctx = TemplateContext::from_event("issue_comment", &event);
+        assert_eq!(ctx.render("/{{command.name}} {{command.args}}"), "/label P-merge");
+        assert_eq!(ctx.get("command.arg1"), Some("P-merge"));
+        assert_eq!(ctx.get("command.arg2"), None);
+        assert_eq!(ctx.render("{{commands.assign.args}}"), "@CjS77 @sdbondi");
+        assert_eq!(ctx.get("commands.assign.arg2"), Some("@sdbondi"));
+    }
+
+    #[test]
+    fn issue_from_api() {
+        let data = include_str!("../../../github-api/src/test_data/issue.json");
+        let issue = serde_json::from_str(data).unwrap();
+        let repo = RepoId::new("tari-project", "tari-crypto");
+        let ctx = TemplateContext::from_issue(&repo, &issue);
+        assert_eq!(
+            ctx.render("{{repo.full_name}}#{{issue.number}} by @{{issue.author}}{{pr.number}}"),
+            "tari-project/tari-crypto#72 by @CjS77"
+        );
+    }
+
+    #[test]
+    fn broadcast_progress() {
+        let event = BroadcastEvent::AcksNeeded(Box::new(Progress::new(1, 3)));
+        let ctx = TemplateContext::new().with_broadcast_event(&event);
+        assert_eq!(ctx.render("{{acks.current}}/{{acks.total}} ACKs"), "1/3 ACKs");
+        assert_eq!(ctx.get("event.name"), Some("AcksNeeded"));
+        assert_eq!(ctx.get("reviews.total"), None);
+    }
+}
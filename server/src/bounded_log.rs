@@ -0,0 +1,54 @@
+//! A thread-safe log that holds a fixed number of entries, discarding the oldest ones first. It backs the global
+//! dry-run report and the log of recent rule executions.
+use std::{collections::VecDeque, sync::Mutex};
+
+pub struct BoundedLog<T> {
+    capacity: usize,
+    entries: Mutex<VecDeque<T>>,
+}
+
+impl<T: Clone> BoundedLog<T> {
+    /// Creates an empty log. The log always keeps at least one entry, even if `capacity` is zero.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Adds an entry to the log, discarding the oldest entries if the log is full.
+    pub fn push(&self, entry: T) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back(entry);
+    }
+
+    /// Returns a copy of the entries in the log, oldest first.
+    pub fn entries(&self) -> Vec<T> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.iter().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BoundedLog;
+
+    #[test]
+    fn oldest_entries_are_discarded() {
+        let log = BoundedLog::new(3);
+        (1..=5).for_each(|i| log.push(i));
+        assert_eq!(log.entries(), vec![3, 4, 5]);
+        let log = BoundedLog::new(0);
+        log.push("a");
+        log.push("b");
+        assert_eq!(log.capacity(), 1);
+        assert_eq!(log.entries(), vec!["b"]);
+    }
+}
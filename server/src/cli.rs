@@ -16,7 +16,20 @@ fn display_readme() {
 
 fn display_envs() {
     // Be explicit about which envars to print, so as to avoid accidentally exposing secrets
-    const DISPLAY_ENVS: [&str; 4] = ["GH_PILOT_HOST", "GH_PILOT_PORT", "GH_PILOT_RULESET_PATH", "RUST_LOG"];
+    const DISPLAY_ENVS: [&str; 12] = [
+        "GH_PILOT_HOST",
+        "GH_PILOT_PORT",
+        "GH_PILOT_RULESET_PATH",
+        "GH_PILOT_JOURNAL_PATH",
+        "GH_PILOT_DRY_RUN",
+        "GH_PILOT_DELIVERY_CACHE_SIZE",
+        "GH_PILOT_DELIVERY_TTL",
+        "GH_PILOT_APP_ID",
+        "GH_PILOT_APP_PRIVATE_KEY_PATH",
+        "GH_PILOT_API_URL",
+        "GH_PILOT_GRAPHQL_URL",
+        "RUST_LOG",
+    ];
 
     println!("Current environment values:");
     DISPLAY_ENVS.iter().for_each(|&name| {
@@ -1,7 +1,9 @@
-use std::env;
+use std::{env, time::Duration};
 
 use log::error;
 
+use crate::deliveries::{DEFAULT_DELIVERY_CACHE_SIZE, DEFAULT_DELIVERY_TTL};
+
 const DEFAULT_GH_PILOT_HOST: &str = "127.0.0.1";
 const DEFAULT_GH_PILOT_PORT: u16 = 8330;
 const DEFAULT_GH_PILOT_RULESET_PATH: &str = "rules.yaml";
@@ -10,6 +12,14 @@ pub struct ServerConfig {
     pub host: String,
     pub port: u16,
     pub rule_set_path: String,
+    /// Where to journal incoming webhook deliveries. Journaling is disabled if this is not set.
+    pub journal_path: Option<String>,
+    /// If true, nothing is changed on Github. See [`crate::dry_run`].
+    pub dry_run: bool,
+    /// The number of webhook delivery ids to remember, to detect redeliveries. See [`crate::deliveries`].
+    pub delivery_cache_size: usize,
+    /// How long to remember webhook delivery ids for.
+    pub delivery_ttl: Duration,
 }
 
 impl Default for ServerConfig {
@@ -18,6 +28,10 @@ impl Default for ServerConfig {
             host: DEFAULT_GH_PILOT_HOST.to_string(),
             port: DEFAULT_GH_PILOT_PORT,
             rule_set_path: DEFAULT_GH_PILOT_RULESET_PATH.to_string(),
+            journal_path: None,
+            dry_run: false,
+            delivery_cache_size: DEFAULT_DELIVERY_CACHE_SIZE,
+            delivery_ttl: DEFAULT_DELIVERY_TTL,
         }
     }
 }
@@ -50,22 +64,52 @@ impl ServerConfig {
         let rule_set_path = env::var("GH_PILOT_RULESET_PATH")
             .ok()
             .unwrap_or_else(|| DEFAULT_GH_PILOT_RULESET_PATH.into());
+        let journal_path = env::var("GH_PILOT_JOURNAL_PATH").ok().filter(|p| !p.is_empty());
+        let dry_run = env::var("GH_PILOT_DRY_RUN")
+            .map(|s| matches!(s.to_lowercase().as_str(), "1" | "true" | "yes" | "on"))
+            .unwrap_or(false);
+        let delivery_cache_size = parse_env("GH_PILOT_DELIVERY_CACHE_SIZE", DEFAULT_DELIVERY_CACHE_SIZE);
+        let delivery_ttl = parse_env("GH_PILOT_DELIVERY_TTL", DEFAULT_DELIVERY_TTL.as_secs());
         Self {
             host,
             port,
             rule_set_path,
+            journal_path,
+            dry_run,
+            delivery_cache_size,
+            delivery_ttl: Duration::from_secs(delivery_ttl),
         }
     }
 }
 
+fn parse_env<T: std::str::FromStr + std::fmt::Display>(var: &str, default: T) -> T
+where T::Err: std::fmt::Display {
+    match env::var(var) {
+        Ok(s) => s.parse::<T>().unwrap_or_else(|e| {
+            error!("{s} is not a valid value for {var}. {e} Using the default, {default}, instead.");
+            default
+        }),
+        Err(_) => default,
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use crate::config::{ServerConfig, DEFAULT_GH_PILOT_HOST, DEFAULT_GH_PILOT_PORT, DEFAULT_GH_PILOT_RULESET_PATH};
+    use std::time::Duration;
+
+    use crate::{
+        config::{ServerConfig, DEFAULT_GH_PILOT_HOST, DEFAULT_GH_PILOT_PORT, DEFAULT_GH_PILOT_RULESET_PATH},
+        deliveries::{DEFAULT_DELIVERY_CACHE_SIZE, DEFAULT_DELIVERY_TTL},
+    };
 
     fn clear_env() {
         std::env::remove_var("GH_PILOT_HOST");
         std::env::remove_var("GH_PILOT_PORT");
         std::env::remove_var("GH_PILOT_RULESET_PATH");
+        std::env::remove_var("GH_PILOT_JOURNAL_PATH");
+        std::env::remove_var("GH_PILOT_DRY_RUN");
+        std::env::remove_var("GH_PILOT_DELIVERY_CACHE_SIZE");
+        std::env::remove_var("GH_PILOT_DELIVERY_TTL");
     }
 
     fn default_config() {
@@ -114,6 +158,34 @@ mod test {
         assert_eq!(config.rule_set_path, "my_house_my_rules.yaml");
     }
 
+    fn journal_from_env() {
+        assert_eq!(ServerConfig::from_env_or_default().journal_path, None);
+        std::env::set_var("GH_PILOT_JOURNAL_PATH", "/var/log/ghp/journal.jsonl");
+        let config = ServerConfig::from_env_or_default();
+        assert_eq!(config.journal_path.as_deref(), Some("/var/log/ghp/journal.jsonl"));
+    }
+
+    fn dry_run_from_env() {
+        assert!(!ServerConfig::from_env_or_default().dry_run);
+        std::env::set_var("GH_PILOT_DRY_RUN", "True");
+        assert!(ServerConfig::from_env_or_default().dry_run);
+        std::env::set_var("GH_PILOT_DRY_RUN", "0");
+        assert!(!ServerConfig::from_env_or_default().dry_run);
+    }
+
+    fn delivery_cache_from_env() {
+        let config = ServerConfig::from_env_or_default();
+        assert_eq!(config.delivery_cache_size, DEFAULT_DELIVERY_CACHE_SIZE);
+        assert_eq!(config.delivery_ttl, DEFAULT_DELIVERY_TTL);
+        std::env::set_var("GH_PILOT_DELIVERY_CACHE_SIZE", "100");
+        std::env::set_var("GH_PILOT_DELIVERY_TTL", "3600");
+        let config = ServerConfig::from_env_or_default();
+        assert_eq!(config.delivery_cache_size, 100);
+        assert_eq!(config.delivery_ttl, Duration::from_secs(3600));
+        std::env::set_var("GH_PILOT_DELIVERY_TTL", "a while");
+        assert_eq!(ServerConfig::from_env_or_default().delivery_ttl, DEFAULT_DELIVERY_TTL);
+    }
+
     // Calls the individual test functions in sequence. If we don't do this, tests running the parallel threads
     // result in flaky tests since the environment is global.
     #[test]
@@ -127,5 +199,11 @@ mod test {
         invalid_port_from_env();
         clear_env();
         rule_set_from_env();
+        clear_env();
+        journal_from_env();
+        clear_env();
+        dry_run_from_env();
+        clear_env();
+        delivery_cache_from_env();
     }
 }
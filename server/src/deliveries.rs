@@ -0,0 +1,194 @@
+//! Deduplication of webhook deliveries.
+//!
+//! Github sends every webhook delivery with a unique `X-GitHub-Delivery` id, and redeliveries (manual ones from the
+//! app or repo settings, or ones made via the API) reuse the id of the original delivery. Processing a redelivery
+//! would run the rules again, which could post the same comment twice or try to merge a PR that was already merged,
+//! so [`DeliveryCache`] remembers the ids it has seen and lets the webhook handler skip duplicates.
+//!
+//! The cache is bounded in size, and forgets ids after a while. Github only allows deliveries from the last few days
+//! to be redelivered, so there's no need to keep them for longer than that. If the event journal is enabled, the cache
+//! is seeded from it at startup, so duplicates are caught across restarts too.
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::Duration,
+};
+
+use github_pilot_api::models::DateTime;
+use log::*;
+
+use crate::journal::Journal;
+
+/// The default number of delivery ids to remember.
+pub const DEFAULT_DELIVERY_CACHE_SIZE: usize = 10_000;
+/// The default time to remember a delivery id for. Github allows redeliveries of webhooks from the past 3 days.
+pub const DEFAULT_DELIVERY_TTL: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+#[derive(Default)]
+struct Deliveries {
+    seen: HashMap<String, DateTime>,
+    /// Delivery ids in the order they were first seen, so that the oldest can be evicted first
+    order: VecDeque<(String, DateTime)>,
+}
+
+pub struct DeliveryCache {
+    capacity: usize,
+    ttl: Duration,
+    deliveries: Mutex<Deliveries>,
+}
+
+impl Default for DeliveryCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_DELIVERY_CACHE_SIZE, DEFAULT_DELIVERY_TTL)
+    }
+}
+
+impl DeliveryCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            deliveries: Mutex::new(Deliveries::default()),
+        }
+    }
+
+    /// The number of delivery ids being remembered.
+    pub fn len(&self) -> usize {
+        self.deliveries.lock().unwrap_or_else(|e| e.into_inner()).seen.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Records the delivery, and returns true if this is the first time it has been seen (within the TTL).
+    pub fn first_delivery(&self, delivery_id: &str) -> bool {
+        self.record_at(delivery_id, DateTime::now())
+    }
+
+    /// Forgets a delivery, so that a redelivery of it will be processed. This is used when the delivery could not be
+    /// processed after all.
+    pub fn forget(&self, delivery_id: &str) {
+        let mut deliveries = self.deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        if deliveries.seen.remove(delivery_id).is_some() {
+            deliveries.order.retain(|(id, _)| id != delivery_id);
+        }
+    }
+
+    /// Adds the deliveries in the journal that are still within the TTL to the cache. Returns the number of
+    /// deliveries that were added.
+    pub fn seed_from_journal(&self, journal: &Journal) -> usize {
+        match journal.entries() {
+            Ok(entries) => entries
+                .into_iter()
+                .filter(|entry| self.record_at(&entry.delivery_id, entry.received_at.clone()))
+                .count(),
+            Err(e) => {
+                warn!("📓 Could not read previous deliveries from the journal. {e}");
+                0
+            },
+        }
+    }
+
+    fn record_at(&self, delivery_id: &str, at: DateTime) -> bool {
+        let now = DateTime::now();
+        let mut deliveries = self.deliveries.lock().unwrap_or_else(|e| e.into_inner());
+        self.evict(&mut deliveries, &now);
+        // Deliveries older than the TTL aren't worth remembering
+        if self.is_expired(&at, &now) {
+            return true;
+        }
+        if deliveries.seen.contains_key(delivery_id) {
+            return false;
+        }
+        deliveries.seen.insert(delivery_id.to_string(), at.clone());
+        deliveries.order.push_back((delivery_id.to_string(), at));
+        while deliveries.seen.len() > self.capacity && !deliveries.order.is_empty() {
+            self.evict_oldest(&mut deliveries);
+        }
+        true
+    }
+
+    fn is_expired(&self, at: &DateTime, now: &DateTime) -> bool {
+        let age = now.as_ref().signed_duration_since(*at.as_ref());
+        age.to_std().map(|age| age > self.ttl).unwrap_or(false)
+    }
+
+    /// Removes expired deliveries from the front of the queue.
+    fn evict(&self, deliveries: &mut Deliveries, now: &DateTime) {
+        while let Some((_, at)) = deliveries.order.front() {
+            if !self.is_expired(at, now) {
+                break;
+            }
+            self.evict_oldest(deliveries);
+        }
+    }
+
+    fn evict_oldest(&self, deliveries: &mut Deliveries) {
+        if let Some((id, _)) = deliveries.order.pop_front() {
+            deliveries.seen.remove(&id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use github_pilot_api::models::DateTime;
+
+    use super::DeliveryCache;
+    use crate::journal::{Journal, JournalEntry};
+
+    #[test]
+    fn duplicates() {
+        let cache = DeliveryCache::default();
+        assert!(cache.first_delivery("a"));
+        assert!(cache.first_delivery("b"));
+        assert!(!cache.first_delivery("a"));
+        assert_eq!(cache.len(), 2);
+        cache.forget("a");
+        assert_eq!(cache.deliveries.lock().unwrap().order.len(), 1);
+        assert!(cache.first_delivery("a"));
+        assert!(!cache.first_delivery("a"));
+        assert_eq!(cache.deliveries.lock().unwrap().order.len(), 2);
+    }
+
+    #[test]
+    fn bounded_size() {
+        let cache = DeliveryCache::new(2, Duration::from_secs(3600));
+        assert!(cache.first_delivery("a"));
+        assert!(cache.first_delivery("b"));
+        assert!(cache.first_delivery("c"));
+        assert_eq!(cache.len(), 2);
+        // "a" was evicted, so it is treated as a new delivery
+        assert!(cache.first_delivery("a"));
+        assert!(!cache.first_delivery("c"));
+    }
+
+    #[test]
+    fn expired_deliveries() {
+        let cache = DeliveryCache::new(10, Duration::from_secs(3600));
+        let long_ago = serde_json::from_str::<DateTime>("\"2022-09-14T12:00:00Z\"").unwrap();
+        assert!(cache.record_at("old", long_ago));
+        assert!(cache.is_empty());
+        assert!(cache.record_at("new", DateTime::now()));
+        assert!(!cache.first_delivery("new"));
+        assert!(cache.first_delivery("old"));
+    }
+
+    #[test]
+    fn seed_from_journal() {
+        let path = std::env::temp_dir().join(format!("ghp_deliveries_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let journal = Journal::open(&path).unwrap();
+        journal.append(&JournalEntry::new("a", "ping", "{}")).unwrap();
+        journal.append(&JournalEntry::new("b", "ping", "{}")).unwrap();
+        journal.append(&JournalEntry::new("a", "ping", "{}")).unwrap();
+        let cache = DeliveryCache::default();
+        assert_eq!(cache.seed_from_journal(&journal), 2);
+        assert!(!cache.first_delivery("b"));
+        assert!(cache.first_delivery("c"));
+        std::fs::remove_file(&path).unwrap();
+    }
+}
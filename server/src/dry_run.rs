@@ -0,0 +1,443 @@
+//! Dry-run mode lets Github Pilot process real webhook events without changing anything on Github.
+//!
+//! When dry-run mode is enabled (by setting `GH_PILOT_DRY_RUN=true`), predicates, heuristics and the read-only parts
+//! of actions run as usual, but every call that would modify Github (adding labels, posting comments, merging, etc.)
+//! is skipped. Instead, the intended effect is logged and added to an in-memory report, which can be inspected via
+//! the `/admin/dry-run` endpoint.
+//!
+//! Code that modifies Github doesn't need to check for dry-run mode itself. Executors wrap their providers in a
+//! [`DryRunProvider`] when it is enabled:
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use ghp_server::dry_run::{self, DryRunProvider};
+//! # use github_pilot_api::{provider_traits::IssueProvider, GithubProvider};
+//! let provider: Arc<dyn IssueProvider> = Arc::new(GithubProvider::default());
+//! let provider: Arc<dyn IssueProvider> = match dry_run::is_enabled() {
+//!     true => Arc::new(DryRunProvider::new(provider)),
+//!     false => provider,
+//! };
+//! ```
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use async_trait::async_trait;
+use github_pilot_api::{
+    error::GithubProviderError,
+    models::{
+        AuthorAssociation,
+        DateTime,
+        Event,
+        Issue,
+        IssueComment,
+        Label,
+        LockReason,
+        PullRequest,
+        PullRequestFile,
+        Reactions,
+        Repository,
+        State,
+        StateReason,
+    },
+    models_plus::{MergeParameters, MergeResult},
+    provider_traits::{IssueProvider, PullRequestProvider, RepoProvider},
+    wrappers::{IssueId, NewLabel},
+};
+use log::*;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::bounded_log::BoundedLog;
+
+/// The number of effects kept in the global report. Older effects are discarded first.
+pub const DRY_RUN_REPORT_CAPACITY: usize = 500;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static REPORT: Lazy<Arc<DryRunReport>> = Lazy::new(|| Arc::new(DryRunReport::new(DRY_RUN_REPORT_CAPACITY)));
+
+/// Turns dry-run mode on or off for the whole server.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::SeqCst)
+}
+
+/// Returns the effects recorded in the global report, oldest first.
+pub fn report() -> Vec<DryRunEffect> {
+    REPORT.effects()
+}
+
+/// A change that would have been made to Github, had dry-run mode been off.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DryRunEffect {
+    pub recorded_at: DateTime,
+    pub target: String,
+    pub effect: String,
+}
+
+/// A bounded log of dry-run effects.
+pub struct DryRunReport {
+    effects: BoundedLog<DryRunEffect>,
+}
+
+impl DryRunReport {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            effects: BoundedLog::new(capacity),
+        }
+    }
+
+    pub fn record<T: Display, S: Into<String>>(&self, target: T, effect: S) {
+        let effect = DryRunEffect {
+            recorded_at: DateTime::now(),
+            target: target.to_string(),
+            effect: effect.into(),
+        };
+        info!("🧪 [dry run] {}: {}", effect.target, effect.effect);
+        self.effects.push(effect);
+    }
+
+    pub fn effects(&self) -> Vec<DryRunEffect> {
+        self.effects.entries()
+    }
+}
+
+/// Wraps a provider so that every call that would change something on Github is recorded in a [`DryRunReport`]
+/// instead of being made. Calls that only read from Github are passed through. The skipped calls return what Github
+/// currently has where they can, e.g. `add_assignees` returns the issue as it is now.
+pub struct DryRunProvider<P: ?Sized> {
+    inner: Arc<P>,
+    report: Arc<DryRunReport>,
+}
+
+impl<P: ?Sized> DryRunProvider<P> {
+    /// Wraps the provider, recording effects in the global report.
+    pub fn new(inner: Arc<P>) -> Self {
+        Self::with_report(inner, Arc::clone(&REPORT))
+    }
+
+    pub fn with_report(inner: Arc<P>, report: Arc<DryRunReport>) -> Self {
+        Self { inner, report }
+    }
+
+    pub fn inner(&self) -> &Arc<P> {
+        &self.inner
+    }
+
+    pub fn report(&self) -> &Arc<DryRunReport> {
+        &self.report
+    }
+}
+
+#[async_trait]
+impl<P: IssueProvider + ?Sized> IssueProvider for DryRunProvider<P> {
+    async fn fetch_issue(&self, id: &IssueId) -> Result<Issue, GithubProviderError> {
+        self.inner.fetch_issue(id).await
+    }
+
+    async fn add_label(&self, id: &IssueId, label: &str) -> Result<Vec<Label>, GithubProviderError> {
+        self.report.record(id, format!("Add label `{label}`"));
+        self.inner.fetch_issue_labels(id).await
+    }
+
+    async fn remove_label(&self, id: &IssueId, label: &str, only_if_exists: bool) -> Result<bool, GithubProviderError> {
+        let exists = self.inner.label_exists(label, id).await?;
+        if exists || !only_if_exists {
+            self.report.record(id, format!("Remove label `{label}`"));
+        }
+        Ok(exists)
+    }
+
+    async fn label_exists(&self, label: &str, id: &IssueId) -> Result<bool, GithubProviderError> {
+        self.inner.label_exists(label, id).await
+    }
+
+    async fn fetch_issue_labels(&self, id: &IssueId) -> Result<Vec<Label>, GithubProviderError> {
+        self.inner.fetch_issue_labels(id).await
+    }
+
+    async fn fetch_issue_comments(&self, id: &IssueId) -> Result<Vec<IssueComment>, GithubProviderError> {
+        self.inner.fetch_issue_comments(id).await
+    }
+
+    async fn add_comment(&self, id: &IssueId, comment: &str) -> Result<IssueComment, GithubProviderError> {
+        self.report.record(id, format!("Add comment:\n{comment}"));
+        Ok(unsent_comment(id, comment))
+    }
+
+    async fn add_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        self.report.record(id, format!("Assign {}", assignees.join(", ")));
+        self.inner.fetch_issue(id).await
+    }
+
+    async fn remove_assignees(&self, id: &IssueId, assignees: &[&str]) -> Result<Issue, GithubProviderError> {
+        self.report.record(id, format!("Unassign {}", assignees.join(", ")));
+        self.inner.fetch_issue(id).await
+    }
+
+    async fn set_state(
+        &self,
+        id: &IssueId,
+        state: State,
+        reason: Option<StateReason>,
+    ) -> Result<Issue, GithubProviderError> {
+        let effect = match state {
+            State::Closed => "Close",
+            _ => "Reopen",
+        };
+        self.report.record(id, format!("{effect} (reason: {reason:?})"));
+        self.inner.fetch_issue(id).await
+    }
+
+    async fn lock(&self, id: &IssueId, reason: Option<LockReason>) -> Result<(), GithubProviderError> {
+        self.report
+            .record(id, format!("Lock conversation (reason: {reason:?})"));
+        Ok(())
+    }
+
+    async fn unlock(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        self.report.record(id, "Unlock conversation");
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl<P: PullRequestProvider + ?Sized> PullRequestProvider for DryRunProvider<P> {
+    async fn fetch_pull_request(&self, id: &IssueId) -> Result<PullRequest, GithubProviderError> {
+        self.inner.fetch_pull_request(id).await
+    }
+
+    async fn merge_pull_request(
+        &self,
+        id: &IssueId,
+        params: MergeParameters,
+    ) -> Result<MergeResult, GithubProviderError> {
+        self.report.record(id, format!("Merge ({:?})", params.merge_method));
+        Ok(MergeResult {
+            sha: String::new(),
+            merged: true,
+            message: "Not merged, because dry-run mode is enabled".into(),
+        })
+    }
+
+    async fn convert_to_draft(&self, id: &IssueId) -> Result<(), GithubProviderError> {
+        self.report.record(id, "Convert to draft");
+        Ok(())
+    }
+
+    async fn fetch_pull_request_files(&self, id: &IssueId) -> Result<Vec<PullRequestFile>, GithubProviderError> {
+        self.inner.fetch_pull_request_files(id).await
+    }
+
+    async fn request_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let effect = format!("Request reviews from {}", describe_reviewers(reviewers, team_reviewers));
+        self.report.record(id, effect);
+        self.inner.fetch_pull_request(id).await
+    }
+
+    async fn remove_requested_reviewers(
+        &self,
+        id: &IssueId,
+        reviewers: &[&str],
+        team_reviewers: &[&str],
+    ) -> Result<PullRequest, GithubProviderError> {
+        let effect = format!(
+            "Remove review requests for {}",
+            describe_reviewers(reviewers, team_reviewers)
+        );
+        self.report.record(id, effect);
+        self.inner.fetch_pull_request(id).await
+    }
+}
+
+#[async_trait]
+impl<P: RepoProvider + Send + ?Sized> RepoProvider for DryRunProvider<P> {
+    async fn fetch_repository(&self, owner: &str, repo: &str) -> Result<Repository, GithubProviderError> {
+        self.inner.fetch_repository(owner, repo).await
+    }
+
+    async fn fetch_labels(
+        &self,
+        owner: &str,
+        repo: &str,
+        page: Option<usize>,
+        per_page: Option<usize>,
+    ) -> Result<Vec<Label>, GithubProviderError> {
+        self.inner.fetch_labels(owner, repo, page, per_page).await
+    }
+
+    async fn delete_label(&self, owner: &str, repo: &str, label: &str) -> Result<bool, GithubProviderError> {
+        self.report
+            .record(format!("{owner}/{repo}"), format!("Delete label `{label}`"));
+        Ok(true)
+    }
+
+    async fn assign_labels(&self, owner: &str, repo: &str, labels: &[NewLabel]) -> Result<(), GithubProviderError> {
+        let names = labels.iter().map(|l| format!("`{}`", l.name)).collect::<Vec<String>>();
+        self.report
+            .record(format!("{owner}/{repo}"), format!("Create labels {}", names.join(", ")));
+        Ok(())
+    }
+
+    async fn edit_label(
+        &self,
+        owner: &str,
+        repo: &str,
+        label: &str,
+        new: &NewLabel,
+    ) -> Result<bool, GithubProviderError> {
+        self.report
+            .record(format!("{owner}/{repo}"), format!("Edit label `{label}`: {new:?}"));
+        Ok(true)
+    }
+
+    async fn fetch_events(&self, owner: &str, repo: &str, since: DateTime) -> Result<Vec<Event>, GithubProviderError> {
+        self.inner.fetch_events(owner, repo, since).await
+    }
+
+    async fn fetch_open_issues(&self, owner: &str, repo: &str) -> Result<Vec<Issue>, GithubProviderError> {
+        self.inner.fetch_open_issues(owner, repo).await
+    }
+
+    async fn fetch_file_contents(&self, owner: &str, repo: &str, path: &str) -> Result<String, GithubProviderError> {
+        self.inner.fetch_file_contents(owner, repo, path).await
+    }
+}
+
+fn describe_reviewers(reviewers: &[&str], teams: &[&str]) -> String {
+    reviewers
+        .iter()
+        .map(|r| format!("@{r}"))
+        .chain(teams.iter().map(|t| format!("team {t}")))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+/// The comment that `add_comment` returns in dry-run mode. It was never posted, so it has no id or URLs.
+fn unsent_comment(id: &IssueId, body: &str) -> IssueComment {
+    let now = DateTime::now().to_string();
+    IssueComment {
+        id: 0,
+        node_id: String::new(),
+        url: "".into(),
+        body: Some(body.into()),
+        body_text: None,
+        body_html: None,
+        html_url: "".into(),
+        user: None,
+        created_at: now.clone(),
+        updated_at: now,
+        issue_url: Some(
+            format!(
+                "https://api.github.com/repos/{}/{}/issues/{}",
+                id.owner, id.repo, id.number
+            )
+            .into(),
+        ),
+        author_association: AuthorAssociation::None,
+        reactions: Reactions {
+            url: "".into(),
+            total_count: 0,
+            plus1: 0,
+            minus1: 0,
+            laugh: 0,
+            hooray: 0,
+            confused: 0,
+            heart: 0,
+            rocket: 0,
+            eyes: 0,
+        },
+        pull_request_url: None,
+        pull_request_review_id: None,
+        diff_hunk: None,
+        path: None,
+        position: None,
+        original_position: None,
+        commit_id: None,
+        original_commit_id: None,
+        start_line: None,
+        original_start_line: None,
+        start_side: None,
+        line: None,
+        original_line: None,
+        side: None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use github_pilot_api::{
+        models::{State, StateReason},
+        provider_traits::{IssueProvider, PullRequestProvider},
+        testing::MockGithub,
+        wrappers::IssueId,
+    };
+
+    use super::{DryRunProvider, DryRunReport};
+
+    #[test]
+    fn report_is_bounded() {
+        let report = DryRunReport::new(2);
+        report.record("a/b#1", "Add label `one`");
+        report.record("a/b#2", "Add label `two`");
+        report.record("a/b#3", String::from("Merge"));
+        let effects = report.effects();
+        assert_eq!(effects.len(), 2);
+        assert_eq!(effects[0].target, "a/b#2");
+        assert_eq!(effects[1].effect, "Merge");
+    }
+
+    #[tokio::test]
+    async fn mutations_are_recorded_instead_of_made() {
+        let id = IssueId::new("tari-project", "tari", 42);
+        let github = Arc::new(
+            MockGithub::new()
+                .with_pull_request(&id, "Add a feature")
+                .with_label(&id, "T-new"),
+        );
+        let report = Arc::new(DryRunReport::new(10));
+        let provider = DryRunProvider::with_report(github.clone(), report.clone());
+
+        let labels = provider.add_label(&id, "T-bug").await.unwrap();
+        assert_eq!(labels.len(), 1);
+        assert!(provider.remove_label(&id, "T-new", true).await.unwrap());
+        assert!(!provider.remove_label(&id, "T-missing", true).await.unwrap());
+        provider.add_comment(&id, "Thanks!").await.unwrap();
+        provider.add_assignees(&id, &["alice", "bob"]).await.unwrap();
+        provider.request_reviewers(&id, &["carol"], &["core"]).await.unwrap();
+        let issue = provider
+            .set_state(&id, State::Closed, Some(StateReason::NotPlanned))
+            .await
+            .unwrap();
+        assert_eq!(issue.state, State::Open);
+        provider.merge_pull_request(&id, Default::default()).await.unwrap();
+
+        assert!(github.mutations().is_empty());
+        assert_eq!(github.labels(&id), vec!["T-new"]);
+        let effects = report.effects().into_iter().map(|e| e.effect).collect::<Vec<String>>();
+        assert_eq!(effects, vec![
+            "Add label `T-bug`",
+            "Remove label `T-new`",
+            "Add comment:\nThanks!",
+            "Assign alice, bob",
+            "Request reviews from @carol, team core",
+            "Close (reason: Some(NotPlanned))",
+            "Merge (Merge)",
+        ]);
+        assert!(report.effects().iter().all(|e| e.target == "tari-project/tari#42"));
+    }
+}
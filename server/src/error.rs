@@ -6,6 +6,8 @@ use actix_web::{
 };
 use thiserror::Error;
 
+use crate::journal::JournalError;
+
 #[derive(Debug, Error)]
 pub enum ServerError {
     #[error("Invalid signature")]
@@ -30,6 +32,14 @@ pub enum ServerError {
     RuleConfigurationError(String),
     #[error("An I/O error happened in the server. {0}")]
     IOError(#[from] std::io::Error),
+    #[error("Journal error. {0}")]
+    JournalError(#[from] JournalError),
+    #[error("The admin API is disabled. Set GH_PILOT_ADMIN_TOKEN to enable it")]
+    AdminApiDisabled,
+    #[error("Missing or invalid admin token")]
+    Unauthorized,
+    #[error("There is no rule named \"{0}\"")]
+    RuleNotFound(String),
     #[error("UnspecifiedError. {0}")]
     Unspecified(String),
 }
@@ -47,6 +57,10 @@ impl ResponseError for ServerError {
             Self::InvalidSignatureHeader(_) => StatusCode::BAD_REQUEST,
             Self::InvalidRequestBody(_) => StatusCode::BAD_REQUEST,
             Self::InvalidEventHeader(_) => StatusCode::BAD_REQUEST,
+            Self::JournalError(JournalError::InvalidQuery(_)) => StatusCode::BAD_REQUEST,
+            Self::AdminApiDisabled => StatusCode::NOT_FOUND,
+            Self::Unauthorized => StatusCode::UNAUTHORIZED,
+            Self::RuleNotFound(_) => StatusCode::NOT_FOUND,
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -7,6 +7,7 @@ use crate::events::Progress;
 
 /// An enumeration of all the internal messaging events that can occur in Github Pilot. These have the same
 /// enumeration as [`super::Event`], but include additional information useful for testing against `EventConstraints`.
+#[derive(Clone, Debug)]
 pub enum BroadcastEvent {
     ReviewsNeeded(Box<Progress>),
     ReviewsThresholdReached,
@@ -1,12 +1,43 @@
-use crate::events::BroadcastEvent;
+use std::any::Any as StdAny;
+
+use serde::{Deserialize, Serialize};
+
+use crate::events::{BroadcastEvent, ProgressConstraint};
 
 pub trait EventConstraint: Send + Sync {
     fn matches(&self, event: &BroadcastEvent) -> bool;
 }
 
-#[derive(Default)]
+/// The set of [`EventConstraint`] implementations that can be used in subscriptions. Like
+/// [`crate::predicates::Predicate`] for rules, this lets subscriptions be defined in, and loaded from, the rules file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Constraint {
+    Progress(ProgressConstraint),
+}
+
+impl Constraint {
+    pub fn as_event_constraint(&self) -> &dyn EventConstraint {
+        match self {
+            Constraint::Progress(p) => p,
+        }
+    }
+}
+
+impl<T: EventConstraint + 'static> From<T> for Constraint {
+    fn from(constraint: T) -> Self {
+        if let Some(progress) = (&constraint as &dyn StdAny).downcast_ref::<ProgressConstraint>() {
+            Self::Progress(progress.clone())
+        } else {
+            unreachable!("Unregistered event constraint type")
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
 pub struct EventConstraints {
-    constraints: Vec<Box<dyn EventConstraint>>,
+    constraints: Vec<Constraint>,
 }
 
 impl EventConstraints {
@@ -17,12 +48,16 @@ impl EventConstraints {
 
     /// Add a new constraint to the set
     pub fn add(&mut self, constraint: impl EventConstraint + 'static) {
-        self.constraints.push(Box::new(constraint));
+        self.constraints.push(Constraint::from(constraint));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.constraints.is_empty()
     }
 
     /// Check whether the set of constraints is satisfied by the given event. `matches` returns `true` if the
     /// constraints are satisfied, indicating that the response to the event should be triggered.
     pub fn matches(&self, event: &BroadcastEvent) -> bool {
-        self.constraints.iter().all(|c| c.matches(event))
+        self.constraints.iter().all(|c| c.as_event_constraint().matches(event))
     }
 }
@@ -1,6 +1,9 @@
+use serde::{Deserialize, Serialize};
+
 /// An enumeration of all the internal broadcast events that can occur in Github Pilot. Clients can subscribe and
 /// react to these events by defining an [`EventRule`].
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum Event {
     ReviewsNeeded,
     ReviewsThresholdReached,
@@ -8,5 +11,6 @@ pub enum Event {
     AcksThresholdReached,
     ChangesRequested,
     #[default]
+    #[serde(skip)]
     Default,
 }
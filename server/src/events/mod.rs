@@ -5,7 +5,7 @@ mod progress_constraint;
 mod subscription;
 
 pub use broadcast_event::*;
-pub use constraints::{EventConstraint, EventConstraints};
+pub use constraints::{Constraint, EventConstraint, EventConstraints};
 pub use event::Event;
 pub use progress_constraint::{Progress, ProgressConstraint};
 pub use subscription::{Subscription, SubscriptionBuilder};
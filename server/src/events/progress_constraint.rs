@@ -1,7 +1,10 @@
 use std::{cmp::Ordering, fmt::Display};
 
+use serde::{Deserialize, Serialize};
+
 use crate::events::{BroadcastEvent, EventConstraint};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct Progress {
     pub total: usize,
     pub current: usize,
@@ -19,7 +22,17 @@ impl Display for Progress {
     }
 }
 
-#[derive(Default)]
+/// Limits `AcksNeeded` and `ReviewsNeeded` subscriptions to events where the progress towards the threshold falls
+/// within certain bounds. In the rules file, the bounds use the same names as the builder methods, e.g.
+///
+/// ```yaml
+/// and:
+///   - progress:
+///       max_progress: 99
+///       total_at_least: 2
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "ProgressBounds", into = "ProgressBounds")]
 pub struct ProgressConstraint {
     percent_threshold: Option<(usize, Ordering)>,
     count_threshold: Option<(usize, Ordering)>,
@@ -91,6 +104,73 @@ impl ProgressConstraint {
     }
 }
 
+/// The serialized form of a [`ProgressConstraint`].
+#[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProgressBounds {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    min_progress: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_progress: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    at_least: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    at_most: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    total_at_least: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    total_at_most: Option<usize>,
+}
+
+fn to_threshold(
+    min: Option<usize>,
+    max: Option<usize>,
+    names: (&str, &str),
+) -> Result<Option<(usize, Ordering)>, String> {
+    match (min, max) {
+        (Some(_), Some(_)) => Err(format!("Only one of {} and {} can be set", names.0, names.1)),
+        (Some(v), None) => Ok(Some((v, Ordering::Less))),
+        (None, Some(v)) => Ok(Some((v, Ordering::Greater))),
+        (None, None) => Ok(None),
+    }
+}
+
+fn from_threshold(threshold: Option<(usize, Ordering)>) -> (Option<usize>, Option<usize>) {
+    match threshold {
+        Some((v, Ordering::Less)) => (Some(v), None),
+        Some((v, _)) => (None, Some(v)),
+        None => (None, None),
+    }
+}
+
+impl TryFrom<ProgressBounds> for ProgressConstraint {
+    type Error = String;
+
+    fn try_from(b: ProgressBounds) -> Result<Self, Self::Error> {
+        Ok(Self {
+            percent_threshold: to_threshold(b.min_progress, b.max_progress, ("min_progress", "max_progress"))?,
+            count_threshold: to_threshold(b.at_least, b.at_most, ("at_least", "at_most"))?,
+            total_threshold: to_threshold(b.total_at_least, b.total_at_most, ("total_at_least", "total_at_most"))?,
+        })
+    }
+}
+
+impl From<ProgressConstraint> for ProgressBounds {
+    fn from(c: ProgressConstraint) -> Self {
+        let (min_progress, max_progress) = from_threshold(c.percent_threshold);
+        let (at_least, at_most) = from_threshold(c.count_threshold);
+        let (total_at_least, total_at_most) = from_threshold(c.total_threshold);
+        Self {
+            min_progress,
+            max_progress,
+            at_least,
+            at_most,
+            total_at_least,
+            total_at_most,
+        }
+    }
+}
+
 impl EventConstraint for ProgressConstraint {
     fn matches(&self, event: &BroadcastEvent) -> bool {
         if let BroadcastEvent::ReviewsNeeded(p) | BroadcastEvent::AcksNeeded(p) = event {
@@ -178,4 +258,14 @@ mod test {
         assert!(!constraint.matches(&reviews(8, 40)), "Below 25%");
         assert!(constraint.matches(&reviews(9, 31)));
     }
+
+    #[test]
+    fn serde() {
+        let constraint = ProgressConstraint::new().max_progress(99).total_at_least(2);
+        let json = serde_json::to_string(&constraint).unwrap();
+        assert_eq!(json, r#"{"max_progress":99,"total_at_least":2}"#);
+        assert_eq!(serde_json::from_str::<ProgressConstraint>(&json).unwrap(), constraint);
+        assert!(serde_json::from_str::<ProgressConstraint>(r#"{"at_least":1,"at_most":2}"#).is_err());
+        assert!(serde_json::from_str::<ProgressConstraint>(r#"{"at_leest":1}"#).is_err());
+    }
 }
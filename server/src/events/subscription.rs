@@ -1,12 +1,31 @@
+//! Subscriptions let you react to the internal [`BroadcastEvent`]s that Github Pilot emits, such as `AcksNeeded`.
+//!
+//! Subscriptions are defined in code with a [`SubscriptionBuilder`], or in the rules file under the `on` key:
+//!
+//! ```yaml
+//! on:
+//!   - name: Ask for ACKs
+//!     event: acks_needed
+//!     and:
+//!       - progress:
+//!           max_progress: 99
+//!     then:
+//!       - github:
+//!           add_label: P-acks_required
+//! ```
 use std::sync::Arc;
 
+use serde::{Deserialize, Serialize};
+
 use crate::{
     actions::Actions,
     events::{constraints::EventConstraints, BroadcastEvent, Event, EventConstraint},
     rules::ActionVec,
 };
 
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Subscription {
+    #[serde(flatten)]
     sub: SubscriptionInner,
 }
 
@@ -14,11 +33,13 @@ pub struct SubscriptionBuilder {
     sub: SubscriptionInner,
 }
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
 struct SubscriptionInner {
     name: String,
     event: Event,
+    #[serde(rename = "and", default, skip_serializing_if = "EventConstraints::is_empty")]
     constraints: EventConstraints,
+    #[serde(rename = "then", default)]
     actions: Vec<Arc<Actions>>,
 }
 
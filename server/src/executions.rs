@@ -0,0 +1,91 @@
+//! A log of recent rule executions, which can be inspected via the `/admin/executions` endpoint.
+//!
+//! Whenever a rule matches a Github event, the results of its "execute" actions (and of its "then" actions, if they
+//! ran) are recorded here. Like the dry-run report, the log is global and bounded in size.
+use github_pilot_api::models::DateTime;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+
+use crate::{bounded_log::BoundedLog, pub_sub::ActionResult};
+
+/// The number of executions kept in the global log. Older executions are discarded first.
+pub const EXECUTION_LOG_CAPACITY: usize = 200;
+
+static LOG: Lazy<ExecutionLog> = Lazy::new(|| ExecutionLog::new(EXECUTION_LOG_CAPACITY));
+
+/// Records the outcome of a rule in the global log.
+pub fn record(rule: &str, event: &str, result: ActionResult, then_result: Option<ActionResult>) {
+    LOG.record(rule, event, result, then_result);
+}
+
+/// Returns the executions recorded in the global log, oldest first.
+pub fn recent() -> Vec<RuleExecution> {
+    LOG.executions()
+}
+
+/// The outcome of running a rule's actions in response to a Github event.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleExecution {
+    pub executed_at: DateTime,
+    pub rule: String,
+    pub event: String,
+    /// The overall result of the "execute" actions
+    pub result: ActionResult,
+    /// The overall result of the "then" actions. These only run if the "execute" actions succeeded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub then_result: Option<ActionResult>,
+}
+
+/// A bounded log of rule executions.
+pub struct ExecutionLog {
+    executions: BoundedLog<RuleExecution>,
+}
+
+impl ExecutionLog {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            executions: BoundedLog::new(capacity),
+        }
+    }
+
+    pub fn record(&self, rule: &str, event: &str, result: ActionResult, then_result: Option<ActionResult>) {
+        let execution = RuleExecution {
+            executed_at: DateTime::now(),
+            rule: rule.to_string(),
+            event: event.to_string(),
+            result,
+            then_result,
+        };
+        self.executions.push(execution);
+    }
+
+    pub fn executions(&self) -> Vec<RuleExecution> {
+        self.executions.entries()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ExecutionLog;
+    use crate::pub_sub::ActionResult;
+
+    #[test]
+    fn log_is_bounded() {
+        let log = ExecutionLog::new(2);
+        log.record(
+            "one",
+            "pull_request",
+            ActionResult::Success,
+            Some(ActionResult::Success),
+        );
+        log.record("two", "pull_request", ActionResult::ConditionsNotMet, None);
+        log.record("three", "issues", ActionResult::Failed, None);
+        let executions = log.executions();
+        assert_eq!(executions.len(), 2);
+        assert_eq!(executions[0].rule, "two");
+        assert_eq!(executions[1].result, ActionResult::Failed);
+        let json = serde_json::to_string(&executions[1]).unwrap();
+        assert!(json.contains(r#""result":"Failed""#));
+        assert!(!json.contains("then_result"));
+    }
+}
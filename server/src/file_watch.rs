@@ -11,7 +11,7 @@ use notify::{
 };
 use tokio::sync::mpsc::{channel, Sender};
 
-use crate::{load_rules::load_rules, pub_sub::PubSubActor};
+use crate::{load_rules::load_rule_set, pub_sub::PubSubActor, schedule::SchedulerActor};
 
 pub struct FileChangeHandler(Sender<Event>);
 
@@ -29,7 +29,11 @@ impl EventHandler for FileChangeHandler {
     }
 }
 
-pub fn async_watch(pubsub: Addr<PubSubActor>, path: &str) -> Option<RecommendedWatcher> {
+pub fn async_watch(
+    pubsub: Addr<PubSubActor>,
+    scheduler: Addr<SchedulerActor>,
+    path: &str,
+) -> Option<RecommendedWatcher> {
     let (tx, mut rx) = channel(1);
     let watcher = RecommendedWatcher::new(FileChangeHandler(tx), Config::default())
         .map_err(|e| {
@@ -46,12 +50,13 @@ pub fn async_watch(pubsub: Addr<PubSubActor>, path: &str) -> Option<RecommendedW
             trace!("Received Rules file change event: {:?}", event.kind);
             if matches!(event.kind, EventKind::Modify(ModifyKind::Data(DataChange::Content))) {
                 info!("Ruleset change detected. Resetting rules...");
-                match load_rules(pubsub.clone(), path_copy.as_str()).await {
-                    Ok(num_rules) => info!("📄 {num_rules} Rules loaded"),
-                    Err(e) => error!(
-                        "Could not update rules. GithubPilot Server will be running without rules until this is \
-                         resolved :(. {e}"
-                    ),
+                match load_rule_set(pubsub.clone(), scheduler.clone(), path_copy.as_str()).await {
+                    Ok(summary) => {
+                        info!("📄 {} Rules loaded", summary.rules);
+                        info!("📄 {} Subscriptions loaded", summary.subscriptions);
+                        info!("⏰ {} Schedules loaded", summary.schedules);
+                    },
+                    Err(e) => error!("Could not update the rule set. The current rules have been left in place. {e}"),
                 };
             }
         }
@@ -0,0 +1,261 @@
+//! An append-only, on-disk journal of every webhook delivery the server accepts.
+//!
+//! Each delivery is stored as a single line of JSON, holding the Github delivery ID, the event name, the time it was
+//! received and the raw payload. Payloads are stored before they are deserialized, so events that could not be
+//! processed at the time (because of a bug, a misconfigured rule, or the event type not being supported yet) can be
+//! replayed through the current rule set later on.
+//!
+//! The journal is enabled by setting `GH_PILOT_JOURNAL_PATH`.
+use std::{
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+use actix::Addr;
+use github_pilot_api::{models::DateTime, GithubEvent};
+use log::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::pub_sub::{GithubEventMessage, PubSubActor};
+
+#[derive(Debug, Error)]
+pub enum JournalError {
+    #[error("Could not access the journal. {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Could not serialize journal entry. {0}")]
+    SerializationError(#[from] serde_json::Error),
+    #[error("Replay query is invalid. {0}")]
+    InvalidQuery(String),
+    #[error("Could not deliver replayed event. {0}")]
+    MailboxError(#[from] actix::MailboxError),
+    #[error("The journal task did not complete. {0}")]
+    TaskFailed(#[from] tokio::task::JoinError),
+}
+
+/// A single webhook delivery.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub delivery_id: String,
+    pub event_name: String,
+    pub received_at: DateTime,
+    /// The raw JSON payload, exactly as it was received.
+    pub payload: String,
+}
+
+impl JournalEntry {
+    pub fn new<S1, S2, S3>(delivery_id: S1, event_name: S2, payload: S3) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+        S3: Into<String>,
+    {
+        Self {
+            delivery_id: delivery_id.into(),
+            event_name: event_name.into(),
+            received_at: DateTime::now(),
+            payload: payload.into(),
+        }
+    }
+}
+
+/// Selects the journal entries to replay. Either a single delivery, or every delivery received in a time range
+/// (inclusive). At least one of the fields must be set.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ReplayQuery {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub since: Option<DateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub until: Option<DateTime>,
+}
+
+impl ReplayQuery {
+    pub fn delivery<S: Into<String>>(id: S) -> Self {
+        Self {
+            delivery_id: Some(id.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn between(since: Option<DateTime>, until: Option<DateTime>) -> Self {
+        Self {
+            delivery_id: None,
+            since,
+            until,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.delivery_id.is_none() && self.since.is_none() && self.until.is_none()
+    }
+
+    pub fn matches(&self, entry: &JournalEntry) -> bool {
+        self.delivery_id
+            .as_ref()
+            .map(|id| *id == entry.delivery_id)
+            .unwrap_or(true) &&
+            self.since.as_ref().map(|t| entry.received_at >= *t).unwrap_or(true) &&
+            self.until.as_ref().map(|t| entry.received_at <= *t).unwrap_or(true)
+    }
+}
+
+/// The outcome of a replay request.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReplaySummary {
+    /// The number of journal entries that matched the query
+    pub matched: usize,
+    /// The number of events that were dispatched to the rule engine
+    pub replayed: usize,
+    /// The delivery IDs of the matching entries that could not be deserialized into a Github event
+    pub skipped: Vec<String>,
+}
+
+pub struct Journal {
+    path: PathBuf,
+    file: Mutex<File>,
+}
+
+impl Journal {
+    /// Opens the journal at the given path, creating it if it does not exist.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, JournalError> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
+
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), JournalError> {
+        let mut line = serde_json::to_string(entry)?;
+        line.push('\n');
+        let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.write_all(line.as_bytes())?;
+        file.flush()?;
+        Ok(())
+    }
+
+    /// Appends the entry on a blocking thread, so that async code (like the webhook handler) doesn't stall while the
+    /// journal is written to.
+    pub async fn record(self: Arc<Self>, entry: JournalEntry) -> Result<(), JournalError> {
+        tokio::task::spawn_blocking(move || self.append(&entry)).await?
+    }
+
+    /// Reads every entry in the journal, oldest first. Lines that cannot be read are logged and skipped.
+    pub fn entries(&self) -> Result<Vec<JournalEntry>, JournalError> {
+        let reader = BufReader::new(File::open(&self.path)?);
+        let entries = reader
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let entry = line
+                    .map_err(JournalError::from)
+                    .and_then(|l| serde_json::from_str::<JournalEntry>(&l).map_err(JournalError::from));
+                entry
+                    .map_err(|e| warn!("📓 Skipping unreadable journal entry on line {}. {e}", i + 1))
+                    .ok()
+            })
+            .collect();
+        Ok(entries)
+    }
+
+    pub fn select(&self, query: &ReplayQuery) -> Result<Vec<JournalEntry>, JournalError> {
+        if query.is_empty() {
+            return Err(JournalError::InvalidQuery(
+                "Provide a delivery id or a time range to replay".into(),
+            ));
+        }
+        let entries = self.entries()?.into_iter().filter(|e| query.matches(e)).collect();
+        Ok(entries)
+    }
+
+    /// Sends every entry matching the query through the rule engine again, in the order they were received. The
+    /// journal is read on a blocking thread.
+    pub async fn replay(
+        self: Arc<Self>,
+        pubsub: &Addr<PubSubActor>,
+        query: ReplayQuery,
+    ) -> Result<ReplaySummary, JournalError> {
+        let entries = tokio::task::spawn_blocking(move || self.select(&query)).await??;
+        let mut summary = ReplaySummary {
+            matched: entries.len(),
+            ..Default::default()
+        };
+        for entry in entries {
+            match GithubEvent::try_from_webhook_info(&entry.event_name, &entry.payload) {
+                Ok(event) => {
+                    info!("📓 Replaying delivery {} [{}]", entry.delivery_id, entry.event_name);
+                    pubsub.send(GithubEventMessage::new(&entry.event_name, event)).await?;
+                    summary.replayed += 1;
+                },
+                Err(e) => {
+                    warn!("📓 Could not replay delivery {}. {e}", entry.delivery_id);
+                    summary.skipped.push(entry.delivery_id);
+                },
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use github_pilot_api::models::DateTime;
+
+    use super::{Journal, JournalEntry, ReplayQuery};
+
+    fn temp_journal(name: &str) -> Journal {
+        let path = std::env::temp_dir().join(format!("ghp_journal_{name}_{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        Journal::open(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn append_and_read_back() {
+        let journal = Arc::new(temp_journal("append"));
+        let first = JournalEntry::new("a-1", "issues", r#"{"action": "opened"}"#);
+        let second = JournalEntry::new("a-2", "push", "{}");
+        journal.append(&first).unwrap();
+        Arc::clone(&journal).record(second.clone()).await.unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .and_then(|mut f| std::io::Write::write_all(&mut f, b"not json\n"))
+            .unwrap();
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries, vec![first, second]);
+        std::fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[test]
+    fn select_entries() {
+        let journal = temp_journal("select");
+        let mut old = JournalEntry::new("old", "issues", "{}");
+        old.received_at = serde_json::from_str("\"2022-08-01T00:00:00Z\"").unwrap();
+        let new = JournalEntry::new("new", "issues", "{}");
+        journal.append(&old).unwrap();
+        journal.append(&new).unwrap();
+
+        assert!(journal.select(&ReplayQuery::default()).is_err());
+        let found = journal.select(&ReplayQuery::delivery("old")).unwrap();
+        assert_eq!(found, vec![old.clone()]);
+        let since: DateTime = serde_json::from_str("\"2022-09-01T00:00:00Z\"").unwrap();
+        let found = journal
+            .select(&ReplayQuery::between(Some(since.clone()), None))
+            .unwrap();
+        assert_eq!(found, vec![new]);
+        let found = journal.select(&ReplayQuery::between(None, Some(since))).unwrap();
+        assert_eq!(found, vec![old]);
+        std::fs::remove_file(journal.path()).unwrap();
+    }
+}
@@ -1,17 +1,28 @@
 pub mod actions;
+pub mod bounded_log;
 pub mod cli;
 pub mod config;
+pub mod deliveries;
+pub mod dry_run;
 pub mod error;
 pub mod events;
+pub mod executions;
 pub mod file_watch;
 pub mod heuristics;
+pub mod journal;
+#[cfg(feature = "yaml")]
+pub mod lint;
 pub mod load_rules;
+pub mod metrics;
 pub mod notifications;
 pub mod predicates;
 pub mod pub_sub;
 pub mod routes;
 pub mod rule_set;
 pub mod rules;
+pub mod schedule;
+pub mod scope;
 pub mod serde;
 pub mod server;
+pub mod simulator;
 pub mod utilities;
@@ -0,0 +1,547 @@
+//! Validation and linting for rule set files.
+//!
+//! [`RuleSet::from_yaml`] only tells you whether a file can be deserialized. The linter goes further, and flags rule
+//! sets that load just fine, but almost certainly don't do what their author intended:
+//!
+//! * syntax and schema errors, with the line and column where they occur,
+//! * duplicate rule names within a file,
+//! * rules that can never be triggered, e.g. an `all` that combines `issue` and `pull_request` predicates,
+//! * rules without any actions,
+//! * ACK patterns and `body_matches` / `title_matches` predicates that are not valid regular expressions,
+//! * merge actions that would merge a PR without a single ACK,
+//! * labels used by rules, subscriptions or schedules that don't exist on the repository (see
+//!   [`LintReport::check_labels`]).
+//!
+//! A directory of rule files is linted one file at a time (see [`lint_dir`]), so a rule in a scoped file may share its
+//! name with a rule in the base file. That is how a shared rule is overridden for some repositories.
+//!
+//! ```
+//! # use ghp_server::lint::{lint_yaml, Severity};
+//! let report = lint_yaml("rules:\n  - name: greet\n    when:\n      - issue: opened\n");
+//! assert!(!report.has_errors());
+//! assert_eq!(report.issues()[0].severity, Severity::Warning);
+//! assert_eq!(report.issues()[0].location, Some((2, 5)));
+//! ```
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
+use github_pilot_api::{error::GithubProviderError, provider_traits::RepoProvider, wrappers::RepoId};
+use log::*;
+use regex::Regex;
+
+use crate::{
+    actions::{Actions, GithubActionParams},
+    predicates::{Issue, Predicate, PullRequest, PullRequestComment, StatusCheck},
+    rule_set::{rule_files, RuleSet},
+    schedule::ScheduledJob,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// The rule set is probably not doing what you expect.
+    Warning,
+    /// The rule set cannot be loaded, or is broken in a way that will cause problems at runtime.
+    Error,
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// The (1-based) line and column in the rules file that the issue refers to, if known.
+    pub location: Option<(usize, usize)>,
+    /// The name of the rule or schedule the issue refers to, if any.
+    pub subject: Option<String>,
+    pub message: String,
+}
+
+impl Display for LintIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.severity)?;
+        if let Some((line, col)) = self.location {
+            write!(f, " [{line}:{col}]")?;
+        }
+        if let Some(subject) = &self.subject {
+            write!(f, " \"{subject}\"")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The outcome of linting a rule set file.
+#[derive(Debug)]
+pub struct LintReport {
+    source: String,
+    rule_set: Option<RuleSet>,
+    issues: Vec<LintIssue>,
+}
+
+impl LintReport {
+    /// The rule set, if the file could be parsed.
+    pub fn rule_set(&self) -> Option<&RuleSet> {
+        self.rule_set.as_ref()
+    }
+
+    pub fn issues(&self) -> &[LintIssue] {
+        self.issues.as_slice()
+    }
+
+    pub fn has_errors(&self) -> bool {
+        self.issues.iter().any(|i| i.severity == Severity::Error)
+    }
+
+    /// Checks every label referenced in the rule set against the labels defined on the given repository, and adds a
+    /// warning for each one that does not exist. Github creates missing labels when they are added to an issue, so
+    /// this is usually a typo rather than a fatal error.
+    pub async fn check_labels<P: RepoProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+        repo: &RepoId,
+    ) -> Result<(), GithubProviderError> {
+        let rule_set = match &self.rule_set {
+            Some(rs) => rs,
+            None => return Ok(()),
+        };
+        let mut known = BTreeSet::new();
+        let per_page = 100;
+        for page in 1.. {
+            let labels = provider
+                .fetch_labels(repo.owner(), repo.repo(), Some(page), Some(per_page))
+                .await?;
+            let n = labels.len();
+            known.extend(labels.into_iter().map(|l| l.name));
+            if n < per_page {
+                break;
+            }
+        }
+        debug!("🔍 {repo} has {} labels", known.len());
+        let mut issues = Vec::new();
+        for (subject, label) in referenced_labels(rule_set) {
+            if !known.contains(&label) {
+                let message = format!("The label `{label}` does not exist on {repo}");
+                issues.push(self.issue(Severity::Warning, subject, message));
+            }
+        }
+        self.issues.extend(issues);
+        Ok(())
+    }
+
+    fn issue(&self, severity: Severity, subject: &str, message: String) -> LintIssue {
+        LintIssue {
+            severity,
+            location: locate_name(&self.source, subject, 0),
+            subject: Some(subject.to_string()),
+            message,
+        }
+    }
+}
+
+/// Parses and lints a YAML rule set.
+pub fn lint_yaml(source: &str) -> LintReport {
+    let mut report = LintReport {
+        source: source.to_string(),
+        rule_set: None,
+        issues: Vec::new(),
+    };
+    match serde_yaml::from_str::<RuleSet>(source) {
+        Ok(rule_set) => {
+            let rule_set = rule_set.with_default_scope();
+            report.issues = lint_rule_set(source, &rule_set);
+            report.rule_set = Some(rule_set);
+        },
+        Err(e) => report.issues.push(LintIssue {
+            severity: Severity::Error,
+            location: e.location().map(|l| (l.line(), l.column())),
+            subject: None,
+            message: e.to_string(),
+        }),
+    }
+    report
+}
+
+/// Lints each of the rule files in a directory, in the order they are loaded (see [`rule_files`]).
+pub fn lint_dir<P: AsRef<Path>>(path: P) -> Result<Vec<(PathBuf, LintReport)>, std::io::Error> {
+    rule_files(path)?
+        .into_iter()
+        .map(|file| {
+            let source = std::fs::read_to_string(&file)?;
+            Ok((file, lint_yaml(source.as_str())))
+        })
+        .collect()
+}
+
+fn lint_rule_set(source: &str, rule_set: &RuleSet) -> Vec<LintIssue> {
+    let mut issues = Vec::new();
+    let mut seen = HashMap::<&str, usize>::new();
+    for rule in rule_set.rules() {
+        let name = rule.name();
+        let occurrence = seen.entry(name).or_default();
+        let location = locate_name(source, name, *occurrence);
+        let mut push = |severity, message: String| {
+            issues.push(LintIssue {
+                severity,
+                location,
+                subject: Some(name.to_string()),
+                message,
+            })
+        };
+        if *occurrence > 0 {
+            push(
+                Severity::Error,
+                "Another rule in this file has the same name. Rule names must be unique within a file".into(),
+            );
+        }
+        *occurrence += 1;
+        if rule.predicates().is_empty() {
+            push(
+                Severity::Warning,
+                "The rule has no `when` predicates, so it will never be triggered".into(),
+            );
+        } else if rule.predicates().iter().all(|p| can_never_match(p)) {
+            push(
+                Severity::Warning,
+                "None of the rule's predicates can ever match. Look for empty `all` or `any` lists, or `all` lists \
+                 that combine predicates for different kinds of events"
+                    .into(),
+            );
+        }
+        if rule.actions().len() == 0 {
+            push(
+                Severity::Warning,
+                "The rule has no `execute` actions, so it does nothing".into(),
+            );
+        }
+        for pattern in rule.predicates().iter().flat_map(|p| patterns(p)) {
+            if let Err(e) = Regex::new(pattern) {
+                push(
+                    Severity::Error,
+                    format!("`{pattern}` is not a valid regular expression. {e}"),
+                );
+            }
+        }
+        for action in rule.actions().chain(rule.then_actions()) {
+            if let Actions::AutoMerge(params) = action.as_ref() {
+                for pattern in params.ack_patterns() {
+                    if let Err(e) = Regex::new(pattern) {
+                        push(
+                            Severity::Error,
+                            format!("The ACK pattern `{pattern}` is not valid. {e}"),
+                        );
+                    }
+                }
+                if params.perform_merge() && params.min_acks_required() == 0 {
+                    push(
+                        Severity::Error,
+                        "The merge action has `perform_merge: true` but `acks_required: 0`, so PRs will be merged \
+                         without any ACKs"
+                            .into(),
+                    );
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// The webhook events that a predicate could possibly match, or `None` if it could match any event.
+fn webhook_events(predicate: &Predicate) -> Option<BTreeSet<&'static str>> {
+    let events: &[&'static str] = match predicate {
+        Predicate::All(all) => {
+            return all
+                .predicates()
+                .iter()
+                .filter_map(webhook_events)
+                .reduce(|a, b| a.intersection(&b).copied().collect())
+                .or_else(|| all.predicates().is_empty().then(BTreeSet::new));
+        },
+        Predicate::Any(any) => {
+            return any
+                .predicates()
+                .iter()
+                .map(webhook_events)
+                .try_fold(BTreeSet::new(), |acc, events| {
+                    Some(acc.union(&events?).copied().collect())
+                });
+        },
+        Predicate::Not(_) => return None,
+        Predicate::Issue(_) => &["issues"],
+        Predicate::PullRequest(_) => &["pull_request", "pull_request_review"],
+        Predicate::PullRequestComment(_) => &["issue_comment", "pull_request_review_comment"],
+        Predicate::Push(_) => &["push"],
+        Predicate::StatusCheck(StatusCheck::CheckRunSuccess(_) | StatusCheck::CheckRunFailure(_)) => &["check_run"],
+        Predicate::StatusCheck(StatusCheck::CommitStatus { .. }) => &["status"],
+        Predicate::StatusCheck(_) => &["check_suite"],
+    };
+    Some(events.iter().copied().collect())
+}
+
+fn can_never_match(predicate: &Predicate) -> bool {
+    webhook_events(predicate)
+        .map(|events| events.is_empty())
+        .unwrap_or(false)
+}
+
+/// All the regular expressions used in a predicate, including nested ones.
+fn patterns(predicate: &Predicate) -> Vec<&str> {
+    match predicate {
+        Predicate::All(all) => all.predicates().iter().flat_map(patterns).collect(),
+        Predicate::Any(any) => any.predicates().iter().flat_map(patterns).collect(),
+        Predicate::Not(not) => patterns(not.predicate()),
+        Predicate::Issue(Issue::TitleMatches(p) | Issue::BodyMatches(p)) => vec![p.as_ref()],
+        Predicate::PullRequestComment(PullRequestComment::BodyMatches(p)) => vec![p.as_ref()],
+        _ => vec![],
+    }
+}
+
+/// All the labels referenced in a predicate, including nested ones.
+fn predicate_labels(predicate: &Predicate) -> Vec<&str> {
+    match predicate {
+        Predicate::All(all) => all.predicates().iter().flat_map(predicate_labels).collect(),
+        Predicate::Any(any) => any.predicates().iter().flat_map(predicate_labels).collect(),
+        Predicate::Not(not) => predicate_labels(not.predicate()),
+        Predicate::Issue(Issue::Labeled(Some(l)) | Issue::Unlabeled(Some(l))) => vec![l.as_ref()],
+        Predicate::PullRequest(PullRequest::Labeled(Some(l)) | PullRequest::Unlabeled(Some(l))) => vec![l.as_ref()],
+        _ => vec![],
+    }
+}
+
+/// The labels that an action adds, removes or looks for. Templated labels are only known once they are rendered for
+/// an event, so they are left out.
+fn action_labels(action: &Actions) -> Vec<&str> {
+    match action {
+        Actions::Github(params) => match params.as_ref() {
+            GithubActionParams::AddLabel(l) | GithubActionParams::RemoveLabel(l) if !l.contains("{{") => {
+                vec![l.as_str()]
+            },
+            _ => vec![],
+        },
+        Actions::AutoMerge(params) => vec![params.merge_label()],
+        _ => vec![],
+    }
+}
+
+/// Every label referenced by the rules, subscriptions and schedules in the rule set, along with the name of the rule,
+/// subscription or schedule that refers to it.
+fn referenced_labels(rule_set: &RuleSet) -> Vec<(&str, String)> {
+    let mut labels = Vec::new();
+    for rule in rule_set.rules() {
+        let rule_labels: BTreeSet<&str> = rule
+            .predicates()
+            .iter()
+            .flat_map(|p| predicate_labels(p))
+            .chain(rule.actions().chain(rule.then_actions()).flat_map(|a| action_labels(a)))
+            .collect();
+        labels.extend(rule_labels.into_iter().map(|l| (rule.name(), l.to_string())));
+    }
+    for sub in rule_set.subscriptions() {
+        let sub_labels: BTreeSet<&str> = sub.actions().flat_map(|a| action_labels(a)).collect();
+        labels.extend(sub_labels.into_iter().map(|l| (sub.name(), l.to_string())));
+    }
+    for schedule in rule_set.schedules() {
+        let ScheduledJob::StaleSweep(sweep) = &schedule.job;
+        let schedule_labels: BTreeSet<&str> = std::iter::once(sweep.label.as_str())
+            .chain(sweep.exempt_labels.iter().map(String::as_str))
+            .collect();
+        labels.extend(
+            schedule_labels
+                .into_iter()
+                .map(|l| (schedule.name.as_str(), l.to_string())),
+        );
+    }
+    labels
+}
+
+/// Finds the line and column of the `name:` key of the `occurrence`th (0-based) rule or schedule with the given name.
+fn locate_name(source: &str, name: &str, occurrence: usize) -> Option<(usize, usize)> {
+    source
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let start = line.find("name:")?;
+            let value = line[start + 5..].trim().trim_matches(|c| c == '"' || c == '\'');
+            (value == name).then_some((i + 1, start + 1))
+        })
+        .nth(occurrence)
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::testing::MockGithub;
+
+    use super::{lint_dir, lint_yaml, LintIssue, Severity};
+
+    fn messages(yaml: &str) -> Vec<LintIssue> {
+        lint_yaml(yaml).issues().to_vec()
+    }
+
+    #[test]
+    fn syntax_errors_have_a_location() {
+        let report = lint_yaml("rules:\n  - name: bad\n    when:\n      - pull_request: not_a_predicate\n");
+        assert!(report.has_errors());
+        assert!(report.rule_set().is_none());
+        // Rules are flattened when deserialized, so the error points at the start of the offending rule
+        assert_eq!(report.issues()[0].location.map(|(line, _)| line), Some(2));
+
+        let report = lint_yaml("rules:\n  - name: bad\n   when: []\n");
+        assert!(report.has_errors());
+        assert_eq!(report.issues()[0].location.map(|(line, _)| line), Some(3));
+    }
+
+    #[test]
+    fn duplicate_names() {
+        let yaml = r#"
+rules:
+  - name: label
+    when:
+      - pull_request: opened
+    execute:
+      - github:
+          add_label: T-new
+  - name: "label"
+    when:
+      - pull_request: reopened
+    execute:
+      - github:
+          add_label: T-new
+"#;
+        let issues = messages(yaml);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].location, Some((9, 5)));
+    }
+
+    #[test]
+    fn unreachable_rules() {
+        let yaml = r#"
+rules:
+  - name: never
+    when:
+      - all:
+          - issue: opened
+          - pull_request: opened
+      - any: []
+    execute:
+      - github: check_conflicts
+  - name: sometimes
+    when:
+      - all:
+          - pull_request: opened
+          - not:
+              issue: opened
+    execute:
+      - github: check_conflicts
+  - name: ci failed
+    when:
+      - all:
+          - status_check:
+              commit_status:
+                context: "ci/*"
+                state: failure
+          - not:
+              status_check: check_suite_success
+    execute:
+      - github: check_conflicts
+  - name: wrong event
+    when:
+      - all:
+          - status_check: check_suite_failure
+          - status_check:
+              check_run_failure: clippy
+    execute:
+      - github: check_conflicts
+"#;
+        let issues = messages(yaml);
+        assert_eq!(issues.len(), 2);
+        assert_eq!(issues[0].location, Some((3, 5)));
+        assert!(issues[0].message.starts_with("None of the rule's predicates"));
+        assert_eq!(issues[1].location, Some((30, 5)));
+    }
+
+    #[test]
+    fn invalid_regexes_and_merge_params() {
+        let yaml = r#"
+rules:
+  - name: merge
+    when:
+      - pull_request_comment:
+          body_matches: "(unclosed"
+    execute:
+      - merge:
+          acks_required: 0
+          ack_patterns: ["^ACK$", "[z-a]"]
+          perform_merge: true
+"#;
+        let issues = messages(yaml);
+        assert_eq!(issues.len(), 3);
+        assert!(issues.iter().all(|i| i.severity == Severity::Error));
+        assert!(issues[0].message.contains("(unclosed"));
+        assert!(issues[1].message.contains("[z-a]"));
+        assert!(issues[2].message.contains("without any ACKs"));
+    }
+
+    #[tokio::test]
+    async fn missing_labels() {
+        let yaml = r#"
+rules:
+  - name: label new PRs
+    when:
+      - pull_request: opened
+    execute:
+      - github:
+          add_label: T-new
+      - github:
+          add_label: T-typo
+      - github:
+          add_label: "team-{{sender.login}}"
+"#;
+        let github = MockGithub::new().with_repo_label("tari-project", "tari", "T-new");
+        let mut report = lint_yaml(yaml);
+        report
+            .check_labels(&github, &"tari-project/tari".parse().unwrap())
+            .await
+            .unwrap();
+        let issues = report.issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Warning);
+        assert_eq!(
+            issues[0].message,
+            "The label `T-typo` does not exist on tari-project/tari"
+        );
+    }
+
+    #[test]
+    fn overrides_across_files() {
+        let rule =
+            "  - name: label\n    when:\n      - pull_request: opened\n    execute:\n      - github:\n          \
+             add_label: T-new\n";
+        let dir = std::env::temp_dir().join(format!("ghp_lint_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("base.yaml"), format!("rules:\n{rule}")).unwrap();
+        std::fs::write(
+            dir.join("tari.yaml"),
+            format!("repos:\n  include: [tari-project/tari]\nrules:\n{rule}{rule}"),
+        )
+        .unwrap();
+        let reports = lint_dir(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].0.ends_with("base.yaml"));
+        assert!(reports[0].1.issues().is_empty());
+        // Overriding the base rule is fine, but the scoped file defines it twice
+        let issues = reports[1].1.issues();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert_eq!(issues[0].location, Some((10, 5)));
+    }
+}
@@ -1,43 +1,54 @@
-use actix::{Addr, MailboxError};
+use actix::Addr;
+use serde::Serialize;
 
 use crate::{
-    actions::Actions,
     error::ServerError,
-    events::{Event, ProgressConstraint, SubscriptionBuilder},
     pub_sub::{PubSubActor, ReplaceRulesMessage, ReplaceSubscriptionsMessage},
     rule_set::RuleSet,
+    schedule::{ReplaceSchedulesMessage, SchedulerActor},
 };
 
-pub async fn load_rules(pubsub: Addr<PubSubActor>, rules_path: &str) -> Result<usize, ServerError> {
-    let rules = RuleSet::from_yaml(rules_path).map_err(|e| ServerError::RuleConfigurationError(e.to_string()))?;
+/// Loads the rule set at `rules_path`, and replaces the rules, subscriptions and schedules in the actors with the ones
+/// it defines. The rule set is only read once, so the actors always agree on which version of it they are running. If
+/// it can't be loaded, nothing is replaced.
+pub async fn load_rule_set(
+    pubsub: Addr<PubSubActor>,
+    scheduler: Addr<SchedulerActor>,
+    rules_path: &str,
+) -> Result<ReloadSummary, ServerError> {
+    let rule_set = RuleSet::load(rules_path).map_err(|e| ServerError::RuleConfigurationError(e.to_string()))?;
+    let (new_rules, new_subscriptions, new_schedules) = rule_set.into_parts();
 
-    let msg = ReplaceRulesMessage {
-        new_rules: rules.to_rules(),
-    };
+    let rules = pubsub.send(ReplaceRulesMessage { new_rules }).await?;
+    let subscriptions = pubsub.send(ReplaceSubscriptionsMessage { new_subscriptions }).await?;
+    let schedules = scheduler.send(ReplaceSchedulesMessage { new_schedules }).await?;
+    Ok(ReloadSummary {
+        rules,
+        subscriptions,
+        schedules,
+    })
+}
+
+/// The number of rules, subscriptions and schedules that were loaded by [`load_rule_set`].
+#[derive(Debug, Clone, Serialize)]
+pub struct ReloadSummary {
+    pub rules: usize,
+    pub subscriptions: usize,
+    pub schedules: usize,
+}
 
-    let result = pubsub.send(msg).await?;
-    Ok(result)
+/// Where the rule set is loaded from, and the actors it is loaded into. This lets the admin API reload the rule set.
+#[derive(Clone)]
+pub struct RuleSetSource {
+    pub path: String,
+    pub pubsub: Addr<PubSubActor>,
+    pub scheduler: Addr<SchedulerActor>,
 }
 
-pub async fn load_subscriptions(pubsub: Addr<PubSubActor>) -> Result<usize, MailboxError> {
-    let subscriptions = vec![
-        SubscriptionBuilder::on("Ask for ACKs", Event::AcksNeeded)
-            .and(ProgressConstraint::new().max_progress(99))
-            .then(Actions::github().add_label("P-acks_required").build())
-            .submit(),
-        SubscriptionBuilder::on("Ask for reviews", Event::ReviewsNeeded)
-            .and(ProgressConstraint::new().max_progress(99))
-            .then(Actions::github().add_label("P-reviews_required").build())
-            .submit(),
-        SubscriptionBuilder::on("Acks achieved", Event::AcksThresholdReached)
-            .then(Actions::github().remove_label("P-acks_required").build())
-            .submit(),
-        SubscriptionBuilder::on("Acks achieved", Event::ReviewsThresholdReached)
-            .then(Actions::github().remove_label("P-reviews_required").build())
-            .submit(),
-    ];
-    let msg = ReplaceSubscriptionsMessage {
-        new_subscriptions: subscriptions,
-    };
-    pubsub.send(msg).await
+impl RuleSetSource {
+    /// Reloads the rules, subscriptions and schedules from the rule set. If the rule set can't be loaded, the current
+    /// rules are left in place.
+    pub async fn reload(&self) -> Result<ReloadSummary, ServerError> {
+        load_rule_set(self.pubsub.clone(), self.scheduler.clone(), &self.path).await
+    }
 }
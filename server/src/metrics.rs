@@ -0,0 +1,348 @@
+//! Metrics that describe what the server has been up to, exported in the Prometheus text format on `/metrics`.
+//!
+//! Metrics are global, so that they can be updated from anywhere without threading state through the actors.
+//!
+//! The server only exports a handful of metrics of four simple kinds, so they are implemented here rather than with the
+//! `prometheus` crate. That keeps its dependencies (and their minimum Rust versions) out of the build, and lets every
+//! metric be a plain `static` instead of being registered at startup. If we ever need more than counters, gauges and
+//! fixed-bucket histograms, or a second export format, switch to the crate rather than growing this module.
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use github_pilot_api::api::RequestStats;
+use once_cell::sync::Lazy;
+
+use crate::pub_sub::ActionResult;
+
+/// A value that only goes up.
+pub struct Counter {
+    name: &'static str,
+    help: &'static str,
+    value: AtomicU64,
+}
+
+impl Counter {
+    pub const fn new(name: &'static str, help: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            value: AtomicU64::new(0),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    pub fn help(&self) -> &'static str {
+        self.help
+    }
+
+    pub fn inc(&self) {
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        let _ = writeln!(out, "{} {}", self.name, self.get());
+    }
+}
+
+/// A family of counters, one for each value of a single label.
+pub struct LabelledCounter {
+    name: &'static str,
+    help: &'static str,
+    label: &'static str,
+    values: Lazy<Mutex<BTreeMap<String, u64>>>,
+}
+
+impl LabelledCounter {
+    pub const fn new(name: &'static str, help: &'static str, label: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            label,
+            values: Lazy::new(|| Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    pub fn inc(&self, label_value: &str) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        *values.entry(label_value.to_string()).or_default() += 1;
+    }
+
+    pub fn get(&self, label_value: &str) -> u64 {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(label_value).copied().unwrap_or_default()
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "counter");
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        for (value, count) in values.iter() {
+            let _ = writeln!(out, "{}{{{}=\"{}\"}} {count}", self.name, self.label, escape(value));
+        }
+    }
+}
+
+/// A family of gauges (values that can go up and down), one for each value of a single label. Label values that have
+/// never been set are not reported, and neither is the family if none of them have.
+pub struct LabelledGauge {
+    name: &'static str,
+    help: &'static str,
+    label: &'static str,
+    values: Lazy<Mutex<BTreeMap<String, i64>>>,
+}
+
+impl LabelledGauge {
+    pub const fn new(name: &'static str, help: &'static str, label: &'static str) -> Self {
+        Self {
+            name,
+            help,
+            label,
+            values: Lazy::new(|| Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    pub fn set(&self, label_value: &str, value: i64) {
+        let mut values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.insert(label_value.to_string(), value);
+    }
+
+    pub fn get(&self, label_value: &str) -> Option<i64> {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        values.get(label_value).copied()
+    }
+
+    fn render(&self, out: &mut String) {
+        let values = self.values.lock().unwrap_or_else(|e| e.into_inner());
+        if values.is_empty() {
+            return;
+        }
+        header(out, self.name, self.help, "gauge");
+        for (label_value, value) in values.iter() {
+            let _ = writeln!(
+                out,
+                "{}{{{}=\"{}\"}} {value}",
+                self.name,
+                self.label,
+                escape(label_value)
+            );
+        }
+    }
+}
+
+#[derive(Default)]
+struct Observations {
+    /// The number of observations that fell into each bucket (not cumulative)
+    buckets: Vec<u64>,
+    sum: f64,
+    count: u64,
+}
+
+/// Counts observations (such as request durations, in seconds) in a fixed set of buckets.
+pub struct Histogram {
+    name: &'static str,
+    help: &'static str,
+    /// The upper bounds of the buckets, in increasing order. The `+Inf` bucket is implied.
+    bounds: &'static [f64],
+    observations: Mutex<Observations>,
+}
+
+impl Histogram {
+    pub const fn new(name: &'static str, help: &'static str, bounds: &'static [f64]) -> Self {
+        Self {
+            name,
+            help,
+            bounds,
+            observations: Mutex::new(Observations {
+                buckets: Vec::new(),
+                sum: 0.0,
+                count: 0,
+            }),
+        }
+    }
+
+    pub fn observe(&self, value: f64) {
+        let mut observations = self.observations.lock().unwrap_or_else(|e| e.into_inner());
+        if observations.buckets.is_empty() {
+            observations.buckets = vec![0; self.bounds.len()];
+        }
+        if let Some(i) = self.bounds.iter().position(|bound| value <= *bound) {
+            observations.buckets[i] += 1;
+        }
+        observations.sum += value;
+        observations.count += 1;
+    }
+
+    pub fn count(&self) -> u64 {
+        self.observations.lock().unwrap_or_else(|e| e.into_inner()).count
+    }
+
+    fn render(&self, out: &mut String) {
+        header(out, self.name, self.help, "histogram");
+        let observations = self.observations.lock().unwrap_or_else(|e| e.into_inner());
+        let mut cumulative = 0;
+        for (i, bound) in self.bounds.iter().enumerate() {
+            cumulative += observations.buckets.get(i).copied().unwrap_or_default();
+            let _ = writeln!(out, "{}_bucket{{le=\"{bound}\"}} {cumulative}", self.name);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", self.name, observations.count);
+        let _ = writeln!(out, "{}_sum {}", self.name, observations.sum);
+        let _ = writeln!(out, "{}_count {}", self.name, observations.count);
+    }
+}
+
+pub static WEBHOOKS_RECEIVED: LabelledCounter = LabelledCounter::new(
+    "ghp_webhooks_received_total",
+    "Webhook deliveries received with a valid signature, by event type",
+    "event",
+);
+pub static DUPLICATE_DELIVERIES: Counter = Counter::new(
+    "ghp_duplicate_deliveries_total",
+    "Webhook deliveries that were skipped because they had already been processed",
+);
+pub static SIGNATURE_FAILURES: Counter = Counter::new(
+    "ghp_webhook_signature_failures_total",
+    "Webhook deliveries that were rejected because the signature was missing or invalid",
+);
+pub static DESERIALIZATION_FAILURES: LabelledCounter = LabelledCounter::new(
+    "ghp_webhook_deserialization_failures_total",
+    "Webhook deliveries whose payload could not be deserialized, by event type",
+    "event",
+);
+pub static RULE_MATCHES: LabelledCounter = LabelledCounter::new(
+    "ghp_rule_matches_total",
+    "Github events that matched a rule, by rule name",
+    "rule",
+);
+pub static ACTION_RESULTS: LabelledCounter =
+    LabelledCounter::new("ghp_action_results_total", "Actions that were run, by result", "result");
+pub static GITHUB_REQUEST_DURATION: Histogram = Histogram::new(
+    "ghp_github_request_duration_seconds",
+    "The time taken for requests to the Github API to complete",
+    &[0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0],
+);
+pub static GITHUB_REQUEST_ERRORS: Counter = Counter::new(
+    "ghp_github_request_errors_total",
+    "Requests to the Github API that failed with an error response or no response at all",
+);
+pub static GITHUB_RATE_LIMIT_REMAINING: LabelledGauge = LabelledGauge::new(
+    "ghp_github_rate_limit_remaining",
+    "The number of requests left in the current Github API rate limit window, by identity",
+    "identity",
+);
+
+pub fn record_action_result(result: &ActionResult) {
+    ACTION_RESULTS.inc(&result.to_string());
+}
+
+/// Records a request to the Github API. This is registered with [`github_pilot_api::api::set_request_observer`] when
+/// the server starts.
+pub fn record_github_request(stats: &RequestStats<'_>) {
+    GITHUB_REQUEST_DURATION.observe(stats.duration.as_secs_f64());
+    if !stats
+        .status
+        .map(|s| s.is_success() || s.is_redirection())
+        .unwrap_or(false)
+    {
+        GITHUB_REQUEST_ERRORS.inc();
+    }
+    if let Some(quota) = stats.rate_limit {
+        GITHUB_RATE_LIMIT_REMAINING.set(stats.identity, i64::from(quota.remaining));
+    }
+}
+
+/// Renders all the metrics in the Prometheus text exposition format.
+pub fn render() -> String {
+    let mut out = String::new();
+    WEBHOOKS_RECEIVED.render(&mut out);
+    DUPLICATE_DELIVERIES.render(&mut out);
+    SIGNATURE_FAILURES.render(&mut out);
+    DESERIALIZATION_FAILURES.render(&mut out);
+    RULE_MATCHES.render(&mut out);
+    ACTION_RESULTS.render(&mut out);
+    GITHUB_REQUEST_DURATION.render(&mut out);
+    GITHUB_REQUEST_ERRORS.render(&mut out);
+    GITHUB_RATE_LIMIT_REMAINING.render(&mut out);
+    out
+}
+
+fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+fn escape(label_value: &str) -> String {
+    label_value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Histogram, LabelledCounter, LabelledGauge};
+
+    #[test]
+    fn labelled_counters() {
+        let counter = LabelledCounter::new("test_total", "A test", "rule");
+        counter.inc("b");
+        counter.inc("a \"quoted\"");
+        counter.inc("b");
+        assert_eq!(counter.get("b"), 2);
+        assert_eq!(counter.get("c"), 0);
+        let mut out = String::new();
+        counter.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP test_total A test\n# TYPE test_total counter\ntest_total{rule=\"a \\\"quoted\\\"\"} \
+             1\ntest_total{rule=\"b\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn histograms() {
+        let histogram = Histogram::new("test_seconds", "A test", &[0.1, 1.0]);
+        histogram.observe(0.0625);
+        histogram.observe(0.5);
+        histogram.observe(0.75);
+        histogram.observe(3.0);
+        assert_eq!(histogram.count(), 4);
+        let mut out = String::new();
+        histogram.render(&mut out);
+        let expected = "# HELP test_seconds A test\n# TYPE test_seconds histogram\ntest_seconds_bucket{le=\"0.1\"} \
+                        1\ntest_seconds_bucket{le=\"1\"} 3\ntest_seconds_bucket{le=\"+Inf\"} 4\ntest_seconds_sum \
+                        4.3125\ntest_seconds_count 4\n";
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn unset_gauges_are_not_reported() {
+        let gauge = LabelledGauge::new("test_gauge", "A test", "identity");
+        let mut out = String::new();
+        gauge.render(&mut out);
+        assert!(out.is_empty());
+        gauge.set("installation 42", 4999);
+        gauge.set("app 7", 5000);
+        gauge.set("installation 42", 4998);
+        assert_eq!(gauge.get("installation 42"), Some(4998));
+        assert_eq!(gauge.get("installation 43"), None);
+        gauge.render(&mut out);
+        assert_eq!(
+            out,
+            "# HELP test_gauge A test\n# TYPE test_gauge gauge\ntest_gauge{identity=\"app 7\"} \
+             5000\ntest_gauge{identity=\"installation 42\"} 4998\n"
+        );
+    }
+}
@@ -0,0 +1,125 @@
+//! Boolean combinators for [`Predicate`]s.
+//!
+//! A rule's `when` clause triggers if _any_ of its predicates match. These combinators let you build more expressive
+//! conditions by nesting predicates, e.g. "labeled with P-merge AND NOT converted to draft".
+//!
+//! ```yaml
+//! when:
+//!   - all:
+//!       - pull_request:
+//!           labeled: P-merge
+//!       - not:
+//!           pull_request: converted_to_draft
+//! ```
+use log::trace;
+use serde::{Deserialize, Serialize};
+
+use crate::{predicates::Predicate, pub_sub::GithubEventMessage, rules::RulePredicate};
+
+/// Matches if _every_ one of the nested predicates match. An empty list never matches.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct All(Vec<Predicate>);
+
+/// Matches if _at least one_ of the nested predicates match. An empty list never matches.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Any(Vec<Predicate>);
+
+/// Matches if the nested predicate does _not_ match.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Not(Box<Predicate>);
+
+impl All {
+    pub fn of<I: IntoIterator<Item = Predicate>>(predicates: I) -> Self {
+        Self(predicates.into_iter().collect())
+    }
+
+    pub fn predicates(&self) -> &[Predicate] {
+        self.0.as_slice()
+    }
+}
+
+impl Any {
+    pub fn of<I: IntoIterator<Item = Predicate>>(predicates: I) -> Self {
+        Self(predicates.into_iter().collect())
+    }
+
+    pub fn predicates(&self) -> &[Predicate] {
+        self.0.as_slice()
+    }
+}
+
+impl Not {
+    pub fn new<P: Into<Predicate>>(predicate: P) -> Self {
+        Self(Box::new(predicate.into()))
+    }
+
+    pub fn predicate(&self) -> &Predicate {
+        self.0.as_ref()
+    }
+}
+
+impl RulePredicate for All {
+    fn matches(&self, event: &GithubEventMessage) -> bool {
+        trace!("❓testing all of {} predicates", self.0.len());
+        !self.0.is_empty() && self.0.iter().all(|p| p.as_rule_predicate().matches(event))
+    }
+}
+
+impl RulePredicate for Any {
+    fn matches(&self, event: &GithubEventMessage) -> bool {
+        trace!("❓testing any of {} predicates", self.0.len());
+        self.0.iter().any(|p| p.as_rule_predicate().matches(event))
+    }
+}
+
+impl RulePredicate for Not {
+    fn matches(&self, event: &GithubEventMessage) -> bool {
+        trace!("❓testing not {:?}", self.0);
+        !self.0.as_rule_predicate().matches(event)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::GithubEvent;
+
+    use super::*;
+    use crate::predicates::{PullRequest, StatusCheck};
+
+    fn pr_opened_event() -> GithubEventMessage {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        GithubEventMessage::new("test", event)
+    }
+
+    #[test]
+    fn all_predicates_must_match() {
+        let msg = pr_opened_event();
+        let pred = All::of([
+            PullRequest::opened().into(),
+            Not::new(PullRequest::closed_with_unmerged_commits()).into(),
+        ]);
+        assert!(pred.matches(&msg));
+        let pred = All::of([PullRequest::opened().into(), PullRequest::edited().into()]);
+        assert!(!pred.matches(&msg));
+        assert!(!All::of([]).matches(&msg));
+    }
+
+    #[test]
+    fn any_predicate_can_match() {
+        let msg = pr_opened_event();
+        let pred = Any::of([StatusCheck::suite_success().into(), PullRequest::opened().into()]);
+        assert!(pred.matches(&msg));
+        let pred = Any::of([StatusCheck::suite_success().into(), PullRequest::edited().into()]);
+        assert!(!pred.matches(&msg));
+        assert!(!Any::of([]).matches(&msg));
+    }
+
+    #[test]
+    fn not_inverts_predicate() {
+        let msg = pr_opened_event();
+        assert!(!Not::new(PullRequest::opened()).matches(&msg));
+        assert!(Not::new(PullRequest::edited()).matches(&msg));
+        assert!(Not::new(Not::new(PullRequest::opened())).matches(&msg));
+    }
+}
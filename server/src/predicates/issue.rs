@@ -0,0 +1,191 @@
+use github_pilot_api::{
+    models::{IssuesEvent, IssuesEventAction},
+    newtype,
+    GithubEvent,
+};
+use log::{trace, warn};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    predicates::pull_request::{LabelName, UserName},
+    pub_sub::GithubEventMessage,
+    rules::RulePredicate,
+};
+
+newtype!(MilestoneName, String, str);
+newtype!(Pattern, String, str);
+
+impl Pattern {
+    /// Returns true if the text matches this regular expression. Invalid expressions never match.
+    pub fn is_match(&self, text: &str) -> bool {
+        match Regex::new(self.as_ref()) {
+            Ok(re) => re.is_match(text),
+            Err(e) => {
+                warn!("❓'{}' is not a valid regular expression. {e}", self.as_ref());
+                false
+            },
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Issue {
+    Opened,
+    Closed,
+    Reopened,
+    Assigned(Option<UserName>),
+    Labeled(Option<LabelName>),
+    Unlabeled(Option<LabelName>),
+    Milestoned(Option<MilestoneName>),
+    /// Matches any issue event where the issue title matches the given regular expression
+    TitleMatches(Pattern),
+    /// Matches any issue event where the issue body matches the given regular expression
+    BodyMatches(Pattern),
+}
+
+impl Issue {
+    pub fn opened() -> Self {
+        Self::Opened
+    }
+
+    pub fn closed() -> Self {
+        Self::Closed
+    }
+
+    pub fn reopened() -> Self {
+        Self::Reopened
+    }
+
+    pub fn assigned() -> Self {
+        Self::Assigned(None)
+    }
+
+    pub fn assigned_to<S: Into<UserName>>(user_name: S) -> Self {
+        Self::Assigned(Some(user_name.into()))
+    }
+
+    pub fn labeled() -> Self {
+        Self::Labeled(None)
+    }
+
+    pub fn labeled_with<S: Into<LabelName>>(label: S) -> Self {
+        Self::Labeled(Some(label.into()))
+    }
+
+    pub fn unlabeled() -> Self {
+        Self::Unlabeled(None)
+    }
+
+    pub fn unlabeled_with<S: Into<LabelName>>(label: S) -> Self {
+        Self::Unlabeled(Some(label.into()))
+    }
+
+    pub fn milestoned() -> Self {
+        Self::Milestoned(None)
+    }
+
+    pub fn milestoned_with<S: Into<MilestoneName>>(milestone: S) -> Self {
+        Self::Milestoned(Some(milestone.into()))
+    }
+
+    pub fn title_matches<S: Into<Pattern>>(pattern: S) -> Self {
+        Self::TitleMatches(pattern.into())
+    }
+
+    pub fn body_matches<S: Into<Pattern>>(pattern: S) -> Self {
+        Self::BodyMatches(pattern.into())
+    }
+}
+
+impl RulePredicate for Issue {
+    fn matches(&self, event: &GithubEventMessage) -> bool {
+        if let GithubEvent::Issues(IssuesEvent { action, issue, .. }) = event.event() {
+            let action_name = action.to_string();
+            trace!("❓testing {self:?} against event {}/{action_name}", event.name());
+            match (&self, action) {
+                (Issue::Opened, IssuesEventAction::Opened) => true,
+                (Issue::Closed, IssuesEventAction::Closed) => true,
+                (Issue::Reopened, IssuesEventAction::Reopened) => true,
+                (Issue::Assigned(None), IssuesEventAction::Assigned { .. }) => true,
+                (Issue::Assigned(Some(user)), IssuesEventAction::Assigned { assignee }) => {
+                    matches!(assignee, Some(a) if a.login == user.as_ref())
+                },
+                (Issue::Labeled(None), IssuesEventAction::Labeled { .. }) => true,
+                (Issue::Labeled(Some(label_wanted)), IssuesEventAction::Labeled { label }) => {
+                    matches!(label, Some(l) if l.name == label_wanted.as_ref())
+                },
+                (Issue::Unlabeled(None), IssuesEventAction::Unlabeled { .. }) => true,
+                (Issue::Unlabeled(Some(label_wanted)), IssuesEventAction::Unlabeled { label }) => {
+                    matches!(label, Some(l) if l.name == label_wanted.as_ref())
+                },
+                (Issue::Milestoned(None), IssuesEventAction::Milestoned) => true,
+                (Issue::Milestoned(Some(milestone)), IssuesEventAction::Milestoned) => {
+                    matches!(&issue.milestone, Some(m) if m.title == milestone.as_ref())
+                },
+                (Issue::TitleMatches(pattern), _) => pattern.is_match(issue.title.as_str()),
+                (Issue::BodyMatches(pattern), _) => matches!(&issue.body, Some(b) if pattern.is_match(b)),
+                // Anything else does not match
+                _ => false,
+            }
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::GithubEvent;
+
+    use super::{Issue, LabelName, Pattern, UserName};
+    use crate::{pub_sub::GithubEventMessage, rules::RulePredicate};
+
+    fn issue_assigned_event() -> GithubEventMessage {
+        let data = include_str!("../../../github-api/src/test_data/issue_event.json");
+        let event = GithubEvent::try_from_webhook_info("issues", data).unwrap();
+        GithubEventMessage::new("test", event)
+    }
+
+    #[test]
+    fn issue_predicate_constructors() {
+        assert_eq!(Issue::assigned(), Issue::Assigned(None));
+        assert_eq!(Issue::assigned_to("Bob"), Issue::Assigned(Some(UserName::from("Bob"))));
+        assert_eq!(Issue::labeled_with("bug"), Issue::Labeled(Some(LabelName::from("bug"))));
+        assert_eq!(Issue::unlabeled(), Issue::Unlabeled(None));
+        assert_eq!(Issue::milestoned(), Issue::Milestoned(None));
+        assert_eq!(Issue::title_matches("^foo"), Issue::TitleMatches(Pattern::from("^foo")));
+    }
+
+    #[test]
+    fn issue_assigned_matches() {
+        let msg = issue_assigned_event();
+        assert!(Issue::assigned().matches(&msg));
+        assert!(Issue::assigned_to("sdbondi").matches(&msg));
+        assert!(!Issue::assigned_to("hansieodendaal").matches(&msg));
+        assert!(!Issue::opened().matches(&msg));
+        assert!(!Issue::labeled().matches(&msg));
+        assert!(!Issue::closed().matches(&msg));
+    }
+
+    #[test]
+    fn issue_title_and_body_matches() {
+        let msg = issue_assigned_event();
+        assert!(Issue::title_matches(r"^\[base node\]").matches(&msg));
+        assert!(!Issue::title_matches(r"^\[wallet\]").matches(&msg));
+        assert!(Issue::body_matches("(?i)existing sync session").matches(&msg));
+        assert!(!Issue::body_matches("panicked").matches(&msg));
+        // Invalid regexes never match
+        assert!(!Issue::title_matches("[base node").matches(&msg));
+    }
+
+    #[test]
+    fn pr_events_do_not_match() {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(!Issue::opened().matches(&msg));
+        assert!(!Issue::title_matches(".*").matches(&msg));
+    }
+}
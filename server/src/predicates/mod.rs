@@ -1,29 +1,60 @@
+mod combinators;
+mod issue;
 mod pull_request;
 mod pull_request_comment;
+mod push;
 mod status_checks;
 
-use std::any::Any;
+use std::any::Any as StdAny;
 
+pub use combinators::{All, Any, Not};
+pub use issue::{Issue, MilestoneName, Pattern};
 pub use pull_request::PullRequest;
-pub use pull_request_comment::PullRequestComment;
+pub use pull_request_comment::{CommandName, PullRequestComment};
+pub use push::{GlobPattern, Push};
 use serde::{Deserialize, Serialize};
-pub use status_checks::StatusCheck;
+pub use status_checks::{AppSlug, CheckName, StatusCheck};
 
 use crate::rules::RulePredicate;
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Predicate {
+    All(All),
+    Any(Any),
+    Not(Not),
+    Issue(Issue),
     PullRequest(PullRequest),
     PullRequestComment(PullRequestComment),
+    Push(Push),
     StatusCheck(StatusCheck),
 }
 
 impl Predicate {
+    /// A predicate that matches if all of the given predicates match.
+    pub fn all<I: IntoIterator<Item = Predicate>>(predicates: I) -> Self {
+        Self::All(All::of(predicates))
+    }
+
+    /// A predicate that matches if at least one of the given predicates match.
+    pub fn any<I: IntoIterator<Item = Predicate>>(predicates: I) -> Self {
+        Self::Any(Any::of(predicates))
+    }
+
+    /// A predicate that matches if the given predicate does not.
+    pub fn not<P: Into<Predicate>>(predicate: P) -> Self {
+        Self::Not(Not::new(predicate))
+    }
+
     pub fn as_rule_predicate(&self) -> &dyn RulePredicate {
         match self {
+            Predicate::All(all) => all,
+            Predicate::Any(any) => any,
+            Predicate::Not(not) => not,
+            Predicate::Issue(issue) => issue,
             Predicate::PullRequest(pr) => pr,
             Predicate::PullRequestComment(prc) => prc,
+            Predicate::Push(push) => push,
             Predicate::StatusCheck(sc) => sc,
         }
     }
@@ -31,11 +62,21 @@ impl Predicate {
 
 impl<T: RulePredicate + 'static> From<T> for Predicate {
     fn from(predicate: T) -> Self {
-        if let Some(pr) = (&predicate as &dyn Any).downcast_ref::<PullRequest>() {
+        if let Some(all) = (&predicate as &dyn StdAny).downcast_ref::<All>() {
+            Self::All(all.clone())
+        } else if let Some(any) = (&predicate as &dyn StdAny).downcast_ref::<Any>() {
+            Self::Any(any.clone())
+        } else if let Some(not) = (&predicate as &dyn StdAny).downcast_ref::<Not>() {
+            Self::Not(not.clone())
+        } else if let Some(issue) = (&predicate as &dyn StdAny).downcast_ref::<Issue>() {
+            Self::Issue(issue.clone())
+        } else if let Some(pr) = (&predicate as &dyn StdAny).downcast_ref::<PullRequest>() {
             Self::PullRequest(pr.clone())
-        } else if let Some(prc) = (&predicate as &dyn Any).downcast_ref::<PullRequestComment>() {
+        } else if let Some(prc) = (&predicate as &dyn StdAny).downcast_ref::<PullRequestComment>() {
             Self::PullRequestComment(prc.clone())
-        } else if let Some(sc) = (&predicate as &dyn Any).downcast_ref::<StatusCheck>() {
+        } else if let Some(push) = (&predicate as &dyn StdAny).downcast_ref::<Push>() {
+            Self::Push(push.clone())
+        } else if let Some(sc) = (&predicate as &dyn StdAny).downcast_ref::<StatusCheck>() {
             Self::StatusCheck(sc.clone())
         } else {
             unreachable!("Unregistered predicate type - {predicate:?}")
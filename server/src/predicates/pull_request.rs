@@ -8,6 +8,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     heuristics::pull_requests::{PullRequestComplexity, PullRequestHeuristics, PullRequestSize},
+    predicates::push::GlobPattern,
     pub_sub::GithubEventMessage,
     rules::RulePredicate,
 };
@@ -38,6 +39,10 @@ pub enum PullRequest {
     SizeGreaterThan(PullRequestSize),
     MoreComplexThan(PullRequestComplexity),
     PoorJustification,
+    /// Matches any pull request event where the PR's base branch matches the glob pattern
+    TargetsBranch(GlobPattern),
+    /// Matches any pull request event where the PR comes from a fork of the base repository
+    FromFork,
 }
 
 impl PullRequest {
@@ -145,6 +150,14 @@ impl PullRequest {
     pub fn approved() -> Self {
         Self::Approved
     }
+
+    pub fn targets_branch<S: Into<GlobPattern>>(pattern: S) -> Self {
+        Self::TargetsBranch(pattern.into())
+    }
+
+    pub fn from_fork() -> Self {
+        Self::FromFork
+    }
 }
 
 impl RulePredicate for PullRequest {
@@ -213,6 +226,13 @@ impl RulePredicate for PullRequest {
                 (PullRequest::PoorJustification, PullRequestAction::Opened | PullRequestAction::Edited { .. }) => {
                     !heuristic.has_sufficient_context()
                 },
+                (PullRequest::TargetsBranch(pattern), _) => pattern.is_match(pull_request.base.r#ref.as_str()),
+                // If the head repo is missing, the fork has been deleted
+                (PullRequest::FromFork, _) => match (&pull_request.head.repo, &pull_request.base.repo) {
+                    (Some(head), Some(base)) => head.full_name != base.full_name,
+                    (None, _) => true,
+                    (Some(_), None) => false,
+                },
                 // Anything else does not match
                 _ => false,
             }
@@ -275,6 +295,17 @@ mod test {
         assert_eq!(pred, PullRequest::Unlabeled(None));
     }
 
+    #[test]
+    fn pr_target_and_fork() {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(PullRequest::targets_branch("main").matches(&msg));
+        assert!(PullRequest::targets_branch("ma*").matches(&msg));
+        assert!(!PullRequest::targets_branch("development").matches(&msg));
+        assert!(PullRequest::from_fork().matches(&msg));
+    }
+
     #[test]
     fn sanity_checks() {
         let pred = PullRequest::closed_with_unmerged_commits();
@@ -1,15 +1,29 @@
 use github_pilot_api::{
-    models::{IssueCommentEvent, PullRequestReviewCommentEvent},
+    models::{IssueCommentAction, IssueCommentEvent, PullRequestReviewCommentEvent},
+    models_plus::SlashCommand,
+    newtype,
     GithubEvent,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{predicates::pull_request::UserName, pub_sub::GithubEventMessage, rules::RulePredicate};
+use crate::{
+    predicates::{issue::Pattern, pull_request::UserName},
+    pub_sub::GithubEventMessage,
+    rules::RulePredicate,
+};
+
+newtype!(CommandName, String, str);
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum PullRequestComment {
     Added(Option<UserName>),
+    /// Matches comments whose body matches the given regular expression.
+    BodyMatches(Pattern),
+    /// Matches comments containing the given slash command, e.g. `label` will match a comment with a line like
+    /// `/label T-foo`. Actions can use the command's arguments via the `command.*` template values (see
+    /// [`crate::actions::TemplateContext`]), e.g. `add_label: "{{command.arg1}}"`.
+    Command(CommandName),
 }
 
 impl PullRequestComment {
@@ -20,23 +34,48 @@ impl PullRequestComment {
     pub fn added() -> Self {
         PullRequestComment::Added(None)
     }
+
+    pub fn body_matches<S: Into<Pattern>>(pattern: S) -> Self {
+        PullRequestComment::BodyMatches(pattern.into())
+    }
+
+    pub fn command<S: Into<CommandName>>(command: S) -> Self {
+        PullRequestComment::Command(command.into())
+    }
+
+    fn matches_body(&self, body: &str) -> bool {
+        match &self {
+            PullRequestComment::Added(_) => true,
+            PullRequestComment::BodyMatches(pattern) => pattern.is_match(body),
+            PullRequestComment::Command(name) => SlashCommand::parse_all(body).iter().any(|c| c.is(name.as_ref())),
+        }
+    }
 }
 
 impl RulePredicate for PullRequestComment {
     fn matches(&self, event: &GithubEventMessage) -> bool {
         use github_pilot_api::models::PullRequestReviewCommentAction::*;
         use PullRequestComment::*;
-        if let GithubEvent::PullRequestReviewComment(PullRequestReviewCommentEvent { action, info, .. }) = event.event()
+        if let GithubEvent::PullRequestReviewComment(PullRequestReviewCommentEvent {
+            action, info, comment, ..
+        }) = event.event()
         {
             match (action, &self) {
                 (Created, Added(Some(user))) => user.as_ref() == info.sender.login.as_str(),
                 (Created, Added(None)) => true,
+                (Created, _) => self.matches_body(comment.body.as_str()),
                 _ => false,
             }
-        } else if let GithubEvent::IssueComment(IssueCommentEvent { info, comment, .. }) = event.event() {
+        } else if let GithubEvent::IssueComment(IssueCommentEvent {
+            action, info, comment, ..
+        }) = event.event()
+        {
+            let body = comment.body.as_deref().unwrap_or_default();
             match (comment.is_on_pull_request(), &self) {
                 (true, Added(Some(user))) => user.as_ref() == info.sender.login.as_str(),
                 (true, Added(None)) => true,
+                // Only act on the contents of new comments, so that editing a comment doesn't run the command again
+                (true, _) => matches!(action, IssueCommentAction::Created) && self.matches_body(body),
                 _ => false,
             }
         } else {
@@ -74,4 +113,50 @@ mod test {
         assert!(!PullRequestComment::from("Bob").matches(&msg));
         assert!(PullRequestComment::from("sdbondi").matches(&msg));
     }
+    #[test]
+    fn slash_command_matches() {
+        let data = include_str!("../../test-data/issue_comment_command.json");
+        let event = GithubEvent::try_from_webhook_info("issue_comment", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(PullRequestComment::command("label").matches(&msg));
+        assert!(PullRequestComment::command("assign").matches(&msg));
+        assert!(!PullRequestComment::command("merge").matches(&msg));
+        let commands = msg.event().slash_commands();
+        assert_eq!(commands.len(), 2);
+        assert_eq!(commands[0].args, vec!["P-merge"]);
+        assert_eq!(commands[1].handles(), vec!["CjS77", "sdbondi"]);
+
+        // Editing the comment doesn't run the command again
+        let edited = data.replacen(
+            "\"action\": \"created\",",
+            "\"action\": \"edited\", \"changes\": { \"body\": { \"from\": \"Thanks\" } },",
+            1,
+        );
+        assert_ne!(edited, data);
+        let event = GithubEvent::try_from_webhook_info("issue_comment", &edited).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(!PullRequestComment::command("label").matches(&msg));
+        assert!(PullRequestComment::added().matches(&msg));
+    }
+
+    #[test]
+    fn comment_body_matches() {
+        // This is an edit, which changes the comment to "utACK"
+        let data = include_str!("../../test-data/issue_comment_event1.json");
+        let event = GithubEvent::try_from_webhook_info("issue_comment", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(!PullRequestComment::body_matches("^(ut|t)?ACK$").matches(&msg));
+
+        let created = data.replacen("\"action\": \"edited\"", "\"action\": \"created\"", 1);
+        let event = GithubEvent::try_from_webhook_info("issue_comment", &created).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(PullRequestComment::body_matches("^(ut|t)?ACK$").matches(&msg));
+        assert!(!PullRequestComment::body_matches("(?i)nack").matches(&msg));
+        assert!(!PullRequestComment::command("merge").matches(&msg));
+
+        let data = include_str!("../../test-data/pr_review_comment_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request_review_comment", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(PullRequestComment::body_matches("digest type").matches(&msg));
+    }
 }
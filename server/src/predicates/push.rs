@@ -0,0 +1,176 @@
+use github_pilot_api::{models::PushEvent, newtype, GithubEvent};
+use glob::{MatchOptions, Pattern};
+use log::{trace, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::{predicates::pull_request::UserName, pub_sub::GithubEventMessage, rules::RulePredicate};
+
+newtype!(GlobPattern, String, str);
+
+impl GlobPattern {
+    /// Returns true if the text matches this glob pattern. As with paths, `*` does not match `/`, so use `**` to match
+    /// any number of directories. Invalid patterns never match.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.matches_with(text, true)
+    }
+
+    /// Like [`GlobPattern::is_match`], but ignores case.
+    pub fn is_match_ignore_case(&self, text: &str) -> bool {
+        self.matches_with(text, false)
+    }
+
+    fn matches_with(&self, text: &str, case_sensitive: bool) -> bool {
+        let options = MatchOptions {
+            case_sensitive,
+            require_literal_separator: true,
+            ..MatchOptions::default()
+        };
+        match Pattern::new(self.as_ref()) {
+            Ok(p) => p.matches_with(text, options),
+            Err(e) => {
+                warn!("❓'{}' is not a valid glob pattern. {e}", self.as_ref());
+                false
+            },
+        }
+    }
+}
+
+/// Predicates that match against `push` events.
+///
+/// Each variant tests a single property of the push. Use the [`crate::predicates::All`] combinator to require
+/// several of them, e.g. a push to `development` that touches `base_layer/core/**`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Push {
+    /// Matches if the full git ref (e.g. `refs/tags/v1.0.0`) matches the glob pattern
+    Ref(GlobPattern),
+    /// Matches if the push is to a branch whose name matches the glob pattern
+    Branch(GlobPattern),
+    /// Matches force pushes
+    Forced,
+    /// Matches if the push contains at least this many commits
+    CommitsAtLeast(usize),
+    /// Matches if the push was made by the given user
+    Pusher(UserName),
+    /// Matches if any file added, modified or removed in the push matches the glob pattern
+    Touches(GlobPattern),
+}
+
+impl Push {
+    pub fn to_ref<S: Into<GlobPattern>>(pattern: S) -> Self {
+        Self::Ref(pattern.into())
+    }
+
+    pub fn to_branch<S: Into<GlobPattern>>(pattern: S) -> Self {
+        Self::Branch(pattern.into())
+    }
+
+    pub fn forced() -> Self {
+        Self::Forced
+    }
+
+    pub fn commits_at_least(count: usize) -> Self {
+        Self::CommitsAtLeast(count)
+    }
+
+    pub fn by<S: Into<UserName>>(user_name: S) -> Self {
+        Self::Pusher(user_name.into())
+    }
+
+    pub fn touches<S: Into<GlobPattern>>(pattern: S) -> Self {
+        Self::Touches(pattern.into())
+    }
+}
+
+impl RulePredicate for Push {
+    fn matches(&self, event: &GithubEventMessage) -> bool {
+        if let GithubEvent::Push(push) = event.event() {
+            trace!("❓testing {self:?} against push to {}", push.git_ref);
+            match &self {
+                Push::Ref(pattern) => pattern.is_match(push.git_ref.as_str()),
+                Push::Branch(pattern) => {
+                    matches!(push.git_ref.strip_prefix("refs/heads/"), Some(branch) if pattern.is_match(branch))
+                },
+                Push::Forced => push.forced,
+                Push::CommitsAtLeast(count) => push.commits.len() >= *count,
+                Push::Pusher(user) => push.pusher.name == user.as_ref() || push.info.sender.login == user.as_ref(),
+                Push::Touches(pattern) => changed_files(push).any(|f| pattern.is_match(f)),
+            }
+        } else {
+            false
+        }
+    }
+}
+
+fn changed_files(push: &PushEvent) -> impl Iterator<Item = &str> {
+    push.commits
+        .iter()
+        .flat_map(|c| c.added.iter().chain(c.modified.iter()).chain(c.removed.iter()))
+        .map(String::as_str)
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::GithubEvent;
+
+    use super::{GlobPattern, Push};
+    use crate::{pub_sub::GithubEventMessage, rules::RulePredicate};
+
+    fn push_event(data: &str) -> GithubEventMessage {
+        let event = GithubEvent::try_from_webhook_info("push", data).unwrap();
+        GithubEventMessage::new("test", event)
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let pattern = GlobPattern::from("base_layer/core/**");
+        assert!(pattern.is_match("base_layer/core/src/lib.rs"));
+        assert!(!pattern.is_match("base_layer/wallet/src/lib.rs"));
+        assert!(!GlobPattern::from("src/*.rs").is_match("src/a/b/c.rs"));
+        assert!(GlobPattern::from("src/**/*.rs").is_match("src/a/b/c.rs"));
+        assert!(GlobPattern::from("src/**/*.rs").is_match("src/lib.rs"));
+        assert!(!GlobPattern::from("[main").is_match("[main"));
+    }
+
+    #[test]
+    fn push_to_branch() {
+        let msg = push_event(include_str!("../../../github-api/src/test_data/push_event.json"));
+        assert!(Push::to_branch("main").matches(&msg));
+        assert!(Push::to_branch("ma*").matches(&msg));
+        assert!(Push::to_ref("refs/heads/*").matches(&msg));
+        assert!(!Push::to_branch("development").matches(&msg));
+        assert!(!Push::to_ref("refs/tags/*").matches(&msg));
+        assert!(!Push::forced().matches(&msg));
+    }
+
+    #[test]
+    fn push_commits_and_pusher() {
+        let msg = push_event(include_str!("../../../github-api/src/test_data/push_event2.json"));
+        assert!(Push::commits_at_least(3).matches(&msg));
+        assert!(!Push::commits_at_least(4).matches(&msg));
+        assert!(Push::by("CjS77").matches(&msg));
+        assert!(!Push::by("sdbondi").matches(&msg));
+    }
+
+    #[test]
+    fn push_touches_files() {
+        let msg = push_event(include_str!("../../../github-api/src/test_data/push_event2.json"));
+        // added
+        assert!(Push::touches("gh-pilot/src/models/*.rs").matches(&msg));
+        assert!(!Push::touches("gh-pilot/*.rs").matches(&msg));
+        // modified
+        assert!(Push::touches("server/src/pub_sub/**").matches(&msg));
+        // removed
+        assert!(Push::touches("cli/src/context.rs").matches(&msg));
+        assert!(!Push::touches("base_layer/core/**").matches(&msg));
+    }
+
+    #[test]
+    fn other_events_do_not_match() {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        let msg = GithubEventMessage::new("test", event);
+        assert!(!Push::to_branch("*").matches(&msg));
+        assert!(!Push::touches("**").matches(&msg));
+    }
+}
@@ -1,15 +1,35 @@
 use github_pilot_api::{
-    models::{CheckSuiteConclusion, CheckSuiteStatus},
+    models::{CheckSuiteConclusion, CheckSuiteStatus, StatusEventState},
+    newtype,
     GithubEvent,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{pub_sub::GithubEventMessage, rules::RulePredicate};
+use crate::{predicates::push::GlobPattern, pub_sub::GithubEventMessage, rules::RulePredicate};
+
+newtype!(CheckName, String, str);
+newtype!(AppSlug, String, str);
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum StatusCheck {
     CheckSuiteSuccess,
+    CheckSuiteFailure,
+    CheckSuiteCancelled,
+    CheckSuiteTimedOut,
+    /// A check run with the given name (e.g. `clippy`) completed successfully.
+    CheckRunSuccess(CheckName),
+    /// A check run with the given name completed with a failure or timed out.
+    CheckRunFailure(CheckName),
+    /// A check suite run by the given app (e.g. `github-actions`) completed successfully.
+    AppSuccess(AppSlug),
+    /// A check suite run by the given app completed with a failure or timed out.
+    AppFailure(AppSlug),
+    /// A commit status whose context matches the glob pattern was set to the given state.
+    CommitStatus {
+        context: GlobPattern,
+        state: StatusEventState,
+    },
 }
 
 impl StatusCheck {
@@ -17,14 +37,95 @@ impl StatusCheck {
     pub fn suite_success() -> Self {
         Self::CheckSuiteSuccess
     }
+
+    /// Triggers when a Status Check Suite completes with a failure.
+    pub fn suite_failure() -> Self {
+        Self::CheckSuiteFailure
+    }
+
+    /// Triggers when a Status Check Suite is cancelled.
+    pub fn suite_cancelled() -> Self {
+        Self::CheckSuiteCancelled
+    }
+
+    /// Triggers when a Status Check Suite times out.
+    pub fn suite_timed_out() -> Self {
+        Self::CheckSuiteTimedOut
+    }
+
+    pub fn check_run_success<S: Into<CheckName>>(name: S) -> Self {
+        Self::CheckRunSuccess(name.into())
+    }
+
+    pub fn check_run_failure<S: Into<CheckName>>(name: S) -> Self {
+        Self::CheckRunFailure(name.into())
+    }
+
+    pub fn app_success<S: Into<AppSlug>>(slug: S) -> Self {
+        Self::AppSuccess(slug.into())
+    }
+
+    pub fn app_failure<S: Into<AppSlug>>(slug: S) -> Self {
+        Self::AppFailure(slug.into())
+    }
+
+    pub fn commit_status<S: Into<GlobPattern>>(context: S, state: StatusEventState) -> Self {
+        Self::CommitStatus {
+            context: context.into(),
+            state,
+        }
+    }
+}
+
+fn is_completed(status: Option<CheckSuiteStatus>) -> bool {
+    matches!(status, Some(CheckSuiteStatus::Completed))
+}
+
+fn is_failure(conclusion: Option<CheckSuiteConclusion>) -> bool {
+    matches!(
+        conclusion,
+        Some(CheckSuiteConclusion::Failure | CheckSuiteConclusion::TimedOut)
+    )
 }
 
 impl RulePredicate for StatusCheck {
     fn matches(&self, event: &GithubEventMessage) -> bool {
+        use CheckSuiteConclusion::*;
         match (event.event(), &self) {
             (GithubEvent::CheckSuiteEvent(ev), StatusCheck::CheckSuiteSuccess) => {
-                matches!(ev.check_suite.status, Some(CheckSuiteStatus::Completed)) &&
-                    matches!(ev.check_suite.conclusion, Some(CheckSuiteConclusion::Success))
+                is_completed(ev.check_suite.status) && matches!(ev.check_suite.conclusion, Some(Success))
+            },
+            (GithubEvent::CheckSuiteEvent(ev), StatusCheck::CheckSuiteFailure) => {
+                is_completed(ev.check_suite.status) && matches!(ev.check_suite.conclusion, Some(Failure))
+            },
+            (GithubEvent::CheckSuiteEvent(ev), StatusCheck::CheckSuiteCancelled) => {
+                is_completed(ev.check_suite.status) && matches!(ev.check_suite.conclusion, Some(Cancelled))
+            },
+            (GithubEvent::CheckSuiteEvent(ev), StatusCheck::CheckSuiteTimedOut) => {
+                is_completed(ev.check_suite.status) && matches!(ev.check_suite.conclusion, Some(TimedOut))
+            },
+            (GithubEvent::CheckSuiteEvent(ev), StatusCheck::AppSuccess(slug)) => {
+                ev.check_suite.app.slug.as_deref() == Some(slug.as_ref()) &&
+                    is_completed(ev.check_suite.status) &&
+                    matches!(ev.check_suite.conclusion, Some(Success))
+            },
+            (GithubEvent::CheckSuiteEvent(ev), StatusCheck::AppFailure(slug)) => {
+                ev.check_suite.app.slug.as_deref() == Some(slug.as_ref()) &&
+                    is_completed(ev.check_suite.status) &&
+                    is_failure(ev.check_suite.conclusion)
+            },
+            (GithubEvent::CheckRun(ev), StatusCheck::CheckRunSuccess(name)) => {
+                ev.check_run.name == name.as_ref() &&
+                    is_completed(ev.check_run.status) &&
+                    matches!(ev.check_run.conclusion, Some(Success))
+            },
+            (GithubEvent::CheckRun(ev), StatusCheck::CheckRunFailure(name)) => {
+                ev.check_run.name == name.as_ref() &&
+                    is_completed(ev.check_run.status) &&
+                    is_failure(ev.check_run.conclusion)
+            },
+            (GithubEvent::Status(ev), StatusCheck::CommitStatus { context, state }) => {
+                ev.state == *state && context.is_match(ev.context.as_str())
             },
             _ => false,
         }
@@ -33,9 +134,16 @@ impl RulePredicate for StatusCheck {
 
 #[cfg(test)]
 mod test {
+    use github_pilot_api::models::StatusEventState;
+
     use super::*;
     use crate::predicates::status_checks::StatusCheck;
 
+    fn message(event_type: &str, data: &str) -> GithubEventMessage {
+        let event = GithubEvent::try_from_webhook_info(event_type, data).unwrap();
+        GithubEventMessage::new(event_type, event)
+    }
+
     #[test]
     fn check_suite_success() {
         let data = include_str!("../../test-data/check_suite_event1.json");
@@ -43,5 +151,42 @@ mod test {
         let msg = GithubEventMessage::new("check-suite", event);
         let predicate = StatusCheck::suite_success();
         assert!(predicate.matches(&msg));
+        assert!(!StatusCheck::suite_failure().matches(&msg));
+        assert!(!StatusCheck::suite_cancelled().matches(&msg));
+        assert!(!StatusCheck::suite_timed_out().matches(&msg));
+    }
+
+    #[test]
+    fn app_checks() {
+        let msg = message("check_suite", include_str!("../../test-data/check_suite_event1.json"));
+        assert!(StatusCheck::app_success("github-actions").matches(&msg));
+        assert!(!StatusCheck::app_success("circleci-checks").matches(&msg));
+        assert!(!StatusCheck::app_failure("github-actions").matches(&msg));
+    }
+
+    #[test]
+    fn named_check_runs() {
+        let msg = message(
+            "check_run",
+            include_str!("../../../github-api/src/test_data/check_run_event.json"),
+        );
+        assert!(StatusCheck::check_run_failure("clippy").matches(&msg));
+        assert!(!StatusCheck::check_run_success("clippy").matches(&msg));
+        assert!(!StatusCheck::check_run_failure("tests").matches(&msg));
+        assert!(!StatusCheck::suite_failure().matches(&msg));
+    }
+
+    #[test]
+    fn commit_statuses() {
+        let msg = message(
+            "status",
+            include_str!("../../../github-api/src/test_data/status_event.json"),
+        );
+        assert!(
+            StatusCheck::commit_status("ci/circleci: run-integration-tests", StatusEventState::Failure).matches(&msg)
+        );
+        assert!(StatusCheck::commit_status("ci/circleci: *", StatusEventState::Failure).matches(&msg));
+        assert!(!StatusCheck::commit_status("ci/circleci: *", StatusEventState::Success).matches(&msg));
+        assert!(!StatusCheck::commit_status("ci/jenkins", StatusEventState::Failure).matches(&msg));
     }
 }
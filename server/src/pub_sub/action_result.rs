@@ -1,5 +1,8 @@
 use std::{error::Error, fmt::Display};
 
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ActionResult {
     Success,
     ConditionsNotMet,
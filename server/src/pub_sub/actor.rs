@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashSet, sync::Arc};
 
 use actix::{Actor, Addr, AsyncContext, Context, Handler, ResponseFuture, Running, SystemService};
 use github_pilot_api::GithubEvent;
@@ -18,13 +18,19 @@ use crate::{
         MergeActionParams,
         MergeExecutor,
     },
-    events::{BroadcastEventMessage, Subscription},
+    events::{BroadcastEvent, BroadcastEventMessage, Subscription},
+    executions,
+    metrics,
     pub_sub::{
         messages::ReplaceSubscriptionsMessage,
         ActionResult,
+        EnableRuleMessage,
         GithubEventMessage,
+        ListRulesMessage,
+        ListSubscriptionsMessage,
         PubSubError,
         ReplaceRulesMessage,
+        RuleStatus,
     },
     rules::{ActionVec, Rule},
     utilities::timestamp,
@@ -33,6 +39,8 @@ use crate::{
 pub struct PubSubActor {
     rules: Arc<RwLock<Vec<Rule>>>,
     subscriptions: Arc<RwLock<Vec<Subscription>>>,
+    /// The names of rules that have been disabled via the admin API. These survive rule set reloads.
+    disabled_rules: Arc<RwLock<HashSet<String>>>,
     address: Option<Addr<PubSubActor>>,
 }
 
@@ -47,6 +55,7 @@ impl PubSubActor {
         Self {
             rules: Arc::new(RwLock::new(Vec::new())),
             subscriptions: Arc::new(RwLock::new(Vec::new())),
+            disabled_rules: Arc::new(RwLock::new(HashSet::new())),
             address: None,
         }
     }
@@ -55,12 +64,13 @@ impl PubSubActor {
         action: Arc<Actions>,
         event_name: String,
         event: Option<GithubEvent>,
+        trigger: Option<BroadcastEvent>,
         addr: Option<Addr<Self>>,
     ) -> Result<ActionResult, PubSubError> {
         match action.as_ref() {
             Actions::AutoMerge(p) => Self::dispatch_merge_action(*p.clone(), event_name, event, addr).await,
             Actions::Closure(c) => Self::dispatch_closure_action(*c.clone(), event_name, event).await,
-            Actions::Github(a) => Self::dispatch_github_action(*a.clone(), event_name, event).await,
+            Actions::Github(a) => Self::dispatch_github_action(*a.clone(), event_name, event, trigger).await,
             Actions::NullAction => {
                 info!("📰 NullAction was dispatched. Doing nothing");
                 Ok(ActionResult::Success)
@@ -112,11 +122,12 @@ impl PubSubActor {
         params: GithubActionParams,
         ev_name: String,
         ev: Option<GithubEvent>,
+        trigger: Option<BroadcastEvent>,
     ) -> Result<ActionResult, PubSubError> {
         let name = format!("GithubAction-{}", timestamp());
         match ev {
             Some(ev) => {
-                let msg = GithubActionMessage::new(name, ev_name, ev, params);
+                let msg = GithubActionMessage::new(name, ev_name, ev, params).with_trigger(trigger);
                 let executor = GithubActionExecutor::from_registry();
                 executor
                     .send(msg)
@@ -144,7 +155,7 @@ impl PubSubActor {
         addr: Option<Addr<Self>>,
     ) -> ActionResult {
         let name = format!("{}-{}.execute.{}", rule.name(), event_name, timestamp());
-        Self::run_actions(name, event_name, event, rule.actions(), addr).await
+        Self::run_actions(name, event_name, event, None, rule.actions(), addr).await
     }
 
     /// Runs all the "then" actions attached to this rule. If any action returns `Failed`, `ConditionsNotMet` or
@@ -159,23 +170,30 @@ impl PubSubActor {
         addr: Option<Addr<Self>>,
     ) -> ActionResult {
         let name = format!("{}-{}.then.{}", rule.name(), event_name, timestamp());
-        Self::run_actions(name, event_name, event, rule.then_actions(), addr).await
+        Self::run_actions(name, event_name, event, None, rule.then_actions(), addr).await
     }
 
     /// Runs all the actions attached to this rule. If any action returns `Failed`, `ConditionsNotMet` or
     /// `Indeterminate`, the overall Result is same and the remaining actions are not run.
     ///
     /// Only if all actions return `Success`, will the overall result be `Success`.
+    ///
+    /// `trigger` is the broadcast event that triggered the actions, if they belong to a subscription.
     async fn run_actions(
         task: String,
         event_name: String,
         event: Option<GithubEvent>,
+        trigger: Option<BroadcastEvent>,
         actions: ActionVec<'_>,
         addr: Option<Addr<Self>>,
     ) -> ActionResult {
         for action in actions.cloned() {
             trace!("📰 Dispatching task \"{task}\" on \"{event_name}\"");
-            match Self::dispatch_message(action, event_name.clone(), event.clone(), addr.clone()).await {
+            let dispatch =
+                Self::dispatch_message(action, event_name.clone(), event.clone(), trigger.clone(), addr.clone());
+            let result = dispatch.await;
+            metrics::record_action_result(result.as_ref().unwrap_or(&ActionResult::Failed));
+            match result {
                 Ok(ActionResult::Success) => {
                     debug!("📰 Task \"{task}\" on \"{event_name}\" completed successfully")
                 },
@@ -217,24 +235,34 @@ impl PubSubActor {
     async fn evaluate_rules_against_message(
         msg: GithubEventMessage,
         rules: Arc<RwLock<Vec<Rule>>>,
+        disabled_rules: Arc<RwLock<HashSet<String>>>,
         addr: Option<Addr<Self>>,
     ) {
         trace!("📰 PubSub received github event message: {}", msg.name());
         let rules = rules.read().await;
+        let disabled_rules = disabled_rules.read().await;
         let mut rules_matched = 0usize;
         let (event_name, event) = msg.clone().to_parts();
         for rule in rules.iter() {
+            if disabled_rules.contains(rule.name()) {
+                trace!("📰 Rule \"{}\" is disabled. Skipping it.", rule.name());
+                continue;
+            }
             // Check if any of the predicates match
             let rule_triggered = rule.matches(&msg);
             // If so, dispatch a tasks to run the actions
             if rule_triggered.is_some() {
                 rules_matched += 1;
                 info!("📰 Rule \"{}\" triggered for \"{}\".", rule.name(), msg.name());
+                metrics::RULE_MATCHES.inc(rule.name());
                 let result =
                     Self::run_execute_actions(event_name.clone(), Some(event.clone()), rule, addr.clone()).await;
-                if matches!(result, ActionResult::Success) {
-                    let _ = Self::run_then_actions(event_name.clone(), Some(event.clone()), rule, addr.clone()).await;
-                }
+                let then_result = if matches!(result, ActionResult::Success) {
+                    Some(Self::run_then_actions(event_name.clone(), Some(event.clone()), rule, addr.clone()).await)
+                } else {
+                    None
+                };
+                executions::record(rule.name(), &event_name, result, then_result);
                 // It may be tempting to broadcast the result here, but don't do it. You will invariably cause an
                 // infinite loop and saturate the mailboxes of all the actors.
             }
@@ -261,6 +289,7 @@ impl PubSubActor {
                     task_name.clone(),
                     event_name.clone(),
                     msg.github_event.clone(),
+                    Some(msg.event.clone()),
                     actions,
                     addr.clone(),
                 )
@@ -296,9 +325,10 @@ impl Handler<GithubEventMessage> for PubSubActor {
 
     fn handle(&mut self, msg: GithubEventMessage, _ctx: &mut Self::Context) -> Self::Result {
         let copy_of_rules = self.rules.clone();
+        let disabled_rules = self.disabled_rules.clone();
         let addr = self.address.clone();
         let fut = async move {
-            Self::evaluate_rules_against_message(msg, copy_of_rules, addr).await;
+            Self::evaluate_rules_against_message(msg, copy_of_rules, disabled_rules, addr).await;
         };
         Box::pin(fut)
     }
@@ -309,10 +339,14 @@ impl Handler<ReplaceRulesMessage> for PubSubActor {
 
     fn handle(&mut self, msg: ReplaceRulesMessage, _ctx: &mut Self::Context) -> Self::Result {
         let rules = Arc::clone(&self.rules);
+        let disabled_rules = Arc::clone(&self.disabled_rules);
         let fut = async move {
             let mut my_rules = rules.write().await;
             my_rules.clear();
             msg.new_rules.into_iter().for_each(|r| my_rules.push(r));
+            // Forget about disabled rules that are no longer loaded
+            let mut disabled_rules = disabled_rules.write().await;
+            disabled_rules.retain(|name| my_rules.iter().any(|r| r.name() == name));
             my_rules.len()
         };
         Box::pin(fut)
@@ -334,6 +368,74 @@ impl Handler<ReplaceSubscriptionsMessage> for PubSubActor {
     }
 }
 
+impl Handler<ListRulesMessage> for PubSubActor {
+    type Result = ResponseFuture<Vec<RuleStatus>>;
+
+    fn handle(&mut self, _msg: ListRulesMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let rules = Arc::clone(&self.rules);
+        let disabled_rules = Arc::clone(&self.disabled_rules);
+        let fut = async move {
+            let rules = rules.read().await;
+            let disabled_rules = disabled_rules.read().await;
+            rules
+                .iter()
+                .map(|rule| RuleStatus {
+                    enabled: !disabled_rules.contains(rule.name()),
+                    rule: rule.clone(),
+                })
+                .collect()
+        };
+        Box::pin(fut)
+    }
+}
+
+impl Handler<ListSubscriptionsMessage> for PubSubActor {
+    type Result = ResponseFuture<Vec<Subscription>>;
+
+    fn handle(&mut self, _msg: ListSubscriptionsMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let subs = Arc::clone(&self.subscriptions);
+        let fut = async move { subs.read().await.clone() };
+        Box::pin(fut)
+    }
+}
+
+impl Handler<EnableRuleMessage> for PubSubActor {
+    type Result = ResponseFuture<Vec<RuleStatus>>;
+
+    fn handle(&mut self, msg: EnableRuleMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let rules = Arc::clone(&self.rules);
+        let disabled_rules = Arc::clone(&self.disabled_rules);
+        let fut = async move {
+            let rules = rules.read().await;
+            let statuses = rules
+                .iter()
+                .filter(|r| r.name() == msg.name)
+                .map(|rule| RuleStatus {
+                    enabled: msg.enabled,
+                    rule: rule.clone(),
+                })
+                .collect::<Vec<_>>();
+            if statuses.is_empty() {
+                return statuses;
+            }
+            let mut disabled_rules = disabled_rules.write().await;
+            if msg.enabled {
+                disabled_rules.remove(&msg.name);
+            } else {
+                disabled_rules.insert(msg.name.clone());
+            }
+            info!(
+                "📰 {} rule(s) named \"{}\" have been {}",
+                statuses.len(),
+                msg.name,
+                if msg.enabled { "enabled" } else { "disabled" }
+            );
+            statuses
+        };
+        Box::pin(fut)
+    }
+}
+
 impl Handler<BroadcastEventMessage> for PubSubActor {
     type Result = ResponseFuture<()>;
 
@@ -346,3 +448,233 @@ impl Handler<BroadcastEventMessage> for PubSubActor {
         Box::pin(fut)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use actix::{Actor, SystemRegistry};
+    use github_pilot_api::{
+        graphql::{review_counts::pull_request_review_counts_ql::PullRequestReviewState, run_status},
+        models_plus::MergeMethod,
+        testing::{MockGithub, Mutation},
+        wrappers::IssueId,
+        GithubEvent,
+    };
+
+    use crate::{
+        actions::{Actions, GithubActionExecutor, MergeExecutor},
+        executions,
+        predicates::{PullRequest, PullRequestComment},
+        pub_sub::{
+            ActionResult,
+            EnableRuleMessage,
+            GithubEventMessage,
+            ListRulesMessage,
+            PubSubActor,
+            ReplaceRulesMessage,
+        },
+        rules::{Rule, RuleBuilder},
+    };
+
+    fn pr_id() -> IssueId {
+        IssueId::new("tari-project", "tari-dan", 2)
+    }
+
+    /// Delivers the `pr_event.json` webhook to a PubSub actor running the given rules, and waits for the rules'
+    /// actions to complete.
+    async fn deliver_pr_opened(rules: Vec<Rule>) {
+        let pub_sub = PubSubActor::new().start();
+        pub_sub.send(ReplaceRulesMessage { new_rules: rules }).await.unwrap();
+        deliver_pr_opened_to(&pub_sub).await;
+    }
+
+    async fn deliver_pr_opened_to(pub_sub: &actix::Addr<PubSubActor>) {
+        let data = include_str!("../../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        pub_sub
+            .send(GithubEventMessage::new("pull_request", event))
+            .await
+            .unwrap();
+    }
+
+    #[actix::test]
+    async fn github_actions_from_webhook() {
+        let github = Arc::new(MockGithub::new().with_pull_request(&pr_id(), "Mempool propagation"));
+        SystemRegistry::set(GithubActionExecutor::new(github.clone()).start());
+        let rule = RuleBuilder::new("welcome")
+            .when(PullRequest::opened())
+            .execute(Actions::github().add_label("T-new").build())
+            .then(
+                Actions::github()
+                    .add_comment("Thanks for #{{pr.number}}, @{{sender.login}}!")
+                    .build(),
+            )
+            .submit();
+        deliver_pr_opened(vec![rule]).await;
+        assert_eq!(github.mutations(), vec![
+            Mutation::AddLabel {
+                id: pr_id(),
+                label: "T-new".into()
+            },
+            Mutation::AddComment {
+                id: pr_id(),
+                body: "Thanks for #2, @mrnaveira!".into()
+            },
+        ]);
+        assert_eq!(github.labels(&pr_id()), vec!["T-new"]);
+    }
+
+    #[actix::test]
+    async fn label_from_slash_command() {
+        let id = IssueId::new("tari-project", "tari-launchpad", 180);
+        let github = Arc::new(MockGithub::new().with_pull_request(&id, "Fix the build"));
+        SystemRegistry::set(GithubActionExecutor::new(github.clone()).start());
+        let rule = RuleBuilder::new("label-command")
+            .when(PullRequestComment::command("label"))
+            .execute(Actions::github().add_label("{{command.arg1}}").build())
+            .submit();
+        let pub_sub = PubSubActor::new().start();
+        pub_sub
+            .send(ReplaceRulesMessage { new_rules: vec![rule] })
+            .await
+            .unwrap();
+        let data = include_str!("../../test-data/issue_comment_command.json");
+        let event = GithubEvent::try_from_webhook_info("issue_comment", data).unwrap();
+        pub_sub
+            .send(GithubEventMessage::new("issue_comment", event))
+            .await
+            .unwrap();
+        assert_eq!(github.labels(&id), vec!["P-merge"]);
+    }
+
+    #[actix::test]
+    async fn code_owner_reviews_from_webhook() {
+        let codeowners = "* @alice @tari-project/devs @other-org/reviewers\n";
+        let github = MockGithub::new()
+            .with_pull_request(&pr_id(), "Mempool propagation")
+            .with_pr_file(&pr_id(), "src/lib.rs")
+            .with_file("tari-project", "tari-dan", ".github/CODEOWNERS", codeowners);
+        let github = Arc::new(github);
+        SystemRegistry::set(GithubActionExecutor::new(github.clone()).start());
+        let rule = RuleBuilder::new("code-owners")
+            .when(PullRequest::opened())
+            .execute(Actions::github().request_code_owner_reviews().build())
+            .submit();
+        deliver_pr_opened(vec![rule]).await;
+        // Teams from other organisations can't be asked for reviews
+        assert_eq!(github.mutations(), vec![Mutation::RequestReviewers {
+            id: pr_id(),
+            reviewers: vec!["alice".into()],
+            teams: vec!["devs".into()],
+        }]);
+    }
+
+    #[actix::test]
+    async fn assigning_nobody_is_skipped() {
+        let github = Arc::new(MockGithub::new().with_pull_request(&pr_id(), "Mempool propagation"));
+        SystemRegistry::set(GithubActionExecutor::new(github.clone()).start());
+        let rule = RuleBuilder::new("assign")
+            .when(PullRequest::opened())
+            .execute(Actions::github().assign(["{{no.such.user}}"]).build())
+            .submit();
+        deliver_pr_opened(vec![rule]).await;
+        assert!(github.mutations().is_empty());
+    }
+
+    #[actix::test]
+    async fn auto_merge_from_webhook() {
+        let id = pr_id();
+        let github = MockGithub::new()
+            .with_pull_request(&id, "Mempool propagation")
+            .with_label(&id, "P-merge")
+            .with_contributors("tari-project", "tari-dan", &["alice", "bob"])
+            .with_pr_comment(&id, "alice", "utACK")
+            .with_review(&id, "bob", PullRequestReviewState::APPROVED)
+            .with_overall_status(&id, run_status::check_run_status_ql::StatusState::SUCCESS);
+        let github = Arc::new(github);
+        SystemRegistry::set(MergeExecutor::from_provider(github.clone()).start());
+        let rule = RuleBuilder::new("merge")
+            .when(PullRequest::opened())
+            .execute(
+                Actions::auto_merge()
+                    .with_min_acks(1)
+                    .with_min_reviews(1)
+                    .with_merge_label("P-merge")
+                    .auto_merge()
+                    .build(),
+            )
+            .submit();
+        deliver_pr_opened(vec![rule]).await;
+        assert_eq!(github.mutations(), vec![Mutation::Merge {
+            id: id.clone(),
+            method: MergeMethod::Squash
+        }]);
+        assert_eq!(github.pull_request(&id).unwrap().merged, Some(true));
+    }
+
+    #[actix::test]
+    async fn disabled_rules_do_not_run() {
+        let github = Arc::new(MockGithub::new().with_pull_request(&pr_id(), "Mempool propagation"));
+        SystemRegistry::set(GithubActionExecutor::new(github.clone()).start());
+        let label = |name: &str, label: &str| {
+            RuleBuilder::new(name)
+                .when(PullRequest::opened())
+                .execute(Actions::github().add_label(label).build())
+                .submit()
+        };
+        let rules = vec![label("label-new", "T-new"), label("label-triage", "T-triage")];
+        let pub_sub = PubSubActor::new().start();
+        pub_sub.send(ReplaceRulesMessage { new_rules: rules }).await.unwrap();
+        let disable = |name: &str| EnableRuleMessage {
+            name: name.into(),
+            enabled: false,
+        };
+        let statuses = pub_sub.send(disable("label-new")).await.unwrap();
+        assert_eq!(statuses.len(), 1);
+        assert!(!statuses[0].enabled);
+        assert!(pub_sub.send(disable("no-such-rule")).await.unwrap().is_empty());
+        let statuses = pub_sub.send(ListRulesMessage).await.unwrap();
+        let enabled = statuses.iter().map(|s| (s.rule.name(), s.enabled)).collect::<Vec<_>>();
+        assert_eq!(enabled, vec![("label-new", false), ("label-triage", true)]);
+
+        deliver_pr_opened_to(&pub_sub).await;
+        assert_eq!(github.labels(&pr_id()), vec!["T-triage"]);
+        let recent = executions::recent();
+        assert!(recent
+            .iter()
+            .any(|e| e.rule == "label-triage" && e.result == ActionResult::Success));
+        assert!(!recent.iter().any(|e| e.rule == "label-new"));
+    }
+
+    #[actix::test]
+    async fn rules_sharing_a_name_are_disabled_together() {
+        let rule = |name: &str| RuleBuilder::new(name).when(PullRequest::opened()).submit();
+        let pub_sub = PubSubActor::new().start();
+        let rules = vec![rule("label"), rule("other"), rule("label")];
+        pub_sub.send(ReplaceRulesMessage { new_rules: rules }).await.unwrap();
+        let disable = EnableRuleMessage {
+            name: "label".into(),
+            enabled: false,
+        };
+        let statuses = pub_sub.send(disable).await.unwrap();
+        assert_eq!(statuses.len(), 2);
+        assert!(statuses.iter().all(|s| s.rule.name() == "label" && !s.enabled));
+
+        // Reloading without the rule forgets that it was disabled
+        pub_sub
+            .send(ReplaceRulesMessage {
+                new_rules: vec![rule("other")],
+            })
+            .await
+            .unwrap();
+        pub_sub
+            .send(ReplaceRulesMessage {
+                new_rules: vec![rule("label")],
+            })
+            .await
+            .unwrap();
+        let statuses = pub_sub.send(ListRulesMessage).await.unwrap();
+        assert!(statuses[0].enabled);
+    }
+}
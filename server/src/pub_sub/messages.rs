@@ -1,5 +1,6 @@
 use actix::Message;
 use github_pilot_api::GithubEvent;
+use serde::Serialize;
 
 use crate::{events::Subscription, rules::Rule};
 
@@ -57,3 +58,37 @@ pub struct ReplaceSubscriptionsMessage {
 impl Message for ReplaceSubscriptionsMessage {
     type Result = usize;
 }
+
+/// Lists the rules that are currently loaded.
+pub struct ListRulesMessage;
+
+impl Message for ListRulesMessage {
+    type Result = Vec<RuleStatus>;
+}
+
+/// Lists the subscriptions that are currently loaded.
+pub struct ListSubscriptionsMessage;
+
+impl Message for ListSubscriptionsMessage {
+    type Result = Vec<Subscription>;
+}
+
+/// Enables or disables the rules with the given name. Several rules share a name when a rule is overridden for some
+/// repositories (see [`RuleSet::merge`](crate::rule_set::RuleSet::merge)), and they are enabled or disabled together.
+/// Returns the new status of every rule with the name, which is empty if there is no such rule.
+pub struct EnableRuleMessage {
+    pub name: String,
+    pub enabled: bool,
+}
+
+impl Message for EnableRuleMessage {
+    type Result = Vec<RuleStatus>;
+}
+
+/// A loaded rule, and whether it is enabled.
+#[derive(Debug, Clone, Serialize)]
+pub struct RuleStatus {
+    pub enabled: bool,
+    #[serde(flatten)]
+    pub rule: Rule,
+}
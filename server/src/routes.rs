@@ -21,6 +21,8 @@
 //!         tokio::time::sleep(Duration::from_secs(5)).await; // <-- Ok. Worker thread will handle other requests here
 //!     }
 //! ```
+use std::sync::Arc;
+
 use actix::prelude::*;
 use actix_web::{get, http::header::HeaderMap, post, web, web::Data, HttpRequest, HttpResponse, Responder};
 use github_pilot_api::{error::GithubProviderError, GithubEvent};
@@ -28,23 +30,50 @@ use log::*;
 use zeroize::Zeroize;
 
 use crate::{
+    deliveries::DeliveryCache,
+    dry_run,
+    dry_run::DryRunEffect,
     error::ServerError,
-    pub_sub::{GithubEventMessage, PubSubActor},
-    utilities::{check_valid_signature, extract_signature, get_secret},
+    events::Subscription,
+    executions,
+    executions::RuleExecution,
+    journal::{Journal, JournalEntry, ReplayQuery, ReplaySummary},
+    load_rules::{ReloadSummary, RuleSetSource},
+    metrics,
+    pub_sub::{
+        EnableRuleMessage,
+        GithubEventMessage,
+        ListRulesMessage,
+        ListSubscriptionsMessage,
+        PubSubActor,
+        RuleStatus,
+    },
+    utilities::{check_admin_token, check_valid_signature, extract_signature, get_secret, timestamp},
 };
 
 type PubSubActorRef = Data<Addr<PubSubActor>>;
+type JournalRef = Option<Data<Journal>>;
 
 #[get("/health")]
 pub async fn health() -> impl Responder {
     HttpResponse::Ok().body("👍")
 }
 
+/// Reports the server's metrics in the Prometheus text format.
+#[get("/metrics")]
+pub async fn metrics_report() -> impl Responder {
+    HttpResponse::Ok()
+        .content_type("text/plain; version=0.0.4; charset=utf-8")
+        .body(metrics::render())
+}
+
 #[post("/webhook")]
 pub async fn github_webhook(
     req: HttpRequest,
     body: web::Bytes,
     pubsub: PubSubActorRef,
+    journal: JournalRef,
+    deliveries: Data<DeliveryCache>,
 ) -> Result<HttpResponse, ServerError> {
     let headers = req.headers();
     trace!("💻 Received webhook request: {}", req.uri());
@@ -52,6 +81,7 @@ pub async fn github_webhook(
     trace!("💻 Decoded payload body. {} bytes", payload.bytes().len());
     if let Err(e) = validate_signature(headers, payload) {
         error!("💻 The webhook signature is invalid. This event will not be processed. {e}");
+        metrics::SIGNATURE_FAILURES.inc();
         return Err(ServerError::InvalidSignature);
     }
     let event_name = headers
@@ -60,29 +90,161 @@ pub async fn github_webhook(
         .to_str()
         .map_err(|_| ServerError::InvalidEventHeader("x-github-event is not a valid string".into()))?;
     trace!("💻 Extracted event name: {}", event_name);
-    match GithubEvent::try_from_webhook_info(event_name, payload) {
+    metrics::WEBHOOKS_RECEIVED.inc(event_name);
+    let delivery_id = headers.get("x-github-delivery").and_then(|h| h.to_str().ok());
+    if let Some(id) = delivery_id {
+        if !deliveries.first_delivery(id) {
+            info!("💻 Delivery {id} [{event_name}] has already been processed. Ignoring the redelivery.");
+            metrics::DUPLICATE_DELIVERIES.inc();
+            return Ok(HttpResponse::Ok().finish());
+        }
+    }
+    let response = match GithubEvent::try_from_webhook_info(event_name, payload) {
         Ok(event) => {
             info!("💻 Github Event Received: [{event_name}], \"{}\"", event.summary());
-            dispatch_event_to_pubsub(pubsub, event_name, event)?;
-            Ok(HttpResponse::Ok().finish())
+            if let Err(e) = dispatch_event_to_pubsub(pubsub, event_name, event) {
+                // The event wasn't processed, so a redelivery should be. It isn't journaled either, since the journal
+                // seeds the delivery cache after a restart.
+                if let Some(id) = delivery_id {
+                    deliveries.forget(id);
+                }
+                return Err(e);
+            }
+            HttpResponse::Ok().finish()
         },
         Err(GithubProviderError::UnknownEvent(s)) => {
             info!("💻 /webhook handler could not handle an \"{s}\" event. Discarding it and moving on.");
-            Ok(HttpResponse::Ok().finish())
+            HttpResponse::Ok().finish()
         },
         Err(GithubProviderError::EventDeserializationError(s)) => {
+            metrics::DESERIALIZATION_FAILURES.inc(event_name);
             warn!(
                 "💻 /webhook handler could not deserialize a \"{event_name}\". Turn on TRACE level to get more \
                  details and maybe file a bug report?"
             );
             trace!("💻 {}", s);
             trace!("💻 JSON payload:\n{}", payload);
-            Ok(HttpResponse::Ok().finish())
+            HttpResponse::Ok().finish()
         },
         Err(e) => {
             warn!("💻 /webhook handler received an unexpected error: {e}. Dropping it like yesterday's news.");
-            Ok(HttpResponse::Ok().finish())
+            HttpResponse::Ok().finish()
         },
+    };
+    // Deliveries that could not be deserialized are journaled too, so that they can be replayed once they're supported
+    if let Some(journal) = journal {
+        record_delivery(journal.into_inner(), delivery_id, event_name, payload).await;
+    }
+    Ok(response)
+}
+
+/// Replays journaled webhook deliveries through the current rule set.
+#[post("/replay")]
+pub async fn replay_events(
+    req: HttpRequest,
+    query: web::Json<ReplayQuery>,
+    pubsub: PubSubActorRef,
+    journal: JournalRef,
+) -> Result<web::Json<ReplaySummary>, ServerError> {
+    check_admin_token(req.headers())?;
+    let journal = journal.ok_or_else(|| ServerError::Unspecified("The event journal is not enabled".into()))?;
+    let summary = journal.into_inner().replay(pubsub.as_ref(), query.into_inner()).await?;
+    info!(
+        "💻 Replayed {} of {} matching deliveries from the journal",
+        summary.replayed, summary.matched
+    );
+    Ok(web::Json(summary))
+}
+
+/// Lists the changes that would have been made to Github, had dry-run mode been off.
+#[get("/dry-run")]
+pub async fn dry_run_report(req: HttpRequest) -> Result<web::Json<Vec<DryRunEffect>>, ServerError> {
+    check_admin_token(req.headers())?;
+    Ok(web::Json(dry_run::report()))
+}
+
+/// Lists the loaded rules, and whether each one is enabled.
+#[get("/rules")]
+pub async fn list_rules(req: HttpRequest, pubsub: PubSubActorRef) -> Result<web::Json<Vec<RuleStatus>>, ServerError> {
+    check_admin_token(req.headers())?;
+    Ok(web::Json(pubsub.send(ListRulesMessage).await?))
+}
+
+/// Lists the loaded subscriptions.
+#[get("/subscriptions")]
+pub async fn list_subscriptions(
+    req: HttpRequest,
+    pubsub: PubSubActorRef,
+) -> Result<web::Json<Vec<Subscription>>, ServerError> {
+    check_admin_token(req.headers())?;
+    Ok(web::Json(pubsub.send(ListSubscriptionsMessage).await?))
+}
+
+/// Reloads the rule set from disk.
+#[post("/reload")]
+pub async fn reload_rules(
+    req: HttpRequest,
+    source: Data<RuleSetSource>,
+) -> Result<web::Json<ReloadSummary>, ServerError> {
+    check_admin_token(req.headers())?;
+    let summary = source.reload().await?;
+    info!(
+        "💻 Reloaded {} rules, {} subscriptions and {} schedules from {}",
+        summary.rules, summary.subscriptions, summary.schedules, source.path
+    );
+    Ok(web::Json(summary))
+}
+
+#[post("/rules/{name}/enable")]
+pub async fn enable_rule(
+    req: HttpRequest,
+    name: web::Path<String>,
+    pubsub: PubSubActorRef,
+) -> Result<web::Json<Vec<RuleStatus>>, ServerError> {
+    set_rule_enabled(req, name.into_inner(), true, pubsub).await
+}
+
+#[post("/rules/{name}/disable")]
+pub async fn disable_rule(
+    req: HttpRequest,
+    name: web::Path<String>,
+    pubsub: PubSubActorRef,
+) -> Result<web::Json<Vec<RuleStatus>>, ServerError> {
+    set_rule_enabled(req, name.into_inner(), false, pubsub).await
+}
+
+/// Lists the most recent rule executions, oldest first.
+#[get("/executions")]
+pub async fn recent_executions(req: HttpRequest) -> Result<web::Json<Vec<RuleExecution>>, ServerError> {
+    check_admin_token(req.headers())?;
+    Ok(web::Json(executions::recent()))
+}
+
+async fn set_rule_enabled(
+    req: HttpRequest,
+    name: String,
+    enabled: bool,
+    pubsub: PubSubActorRef,
+) -> Result<web::Json<Vec<RuleStatus>>, ServerError> {
+    check_admin_token(req.headers())?;
+    let msg = EnableRuleMessage {
+        name: name.clone(),
+        enabled,
+    };
+    let statuses = pubsub.send(msg).await?;
+    if statuses.is_empty() {
+        return Err(ServerError::RuleNotFound(name));
+    }
+    Ok(web::Json(statuses))
+}
+
+async fn record_delivery(journal: Arc<Journal>, delivery_id: Option<&str>, event_name: &str, payload: &str) {
+    let delivery_id = delivery_id
+        .map(String::from)
+        .unwrap_or_else(|| format!("local-{}", timestamp()));
+    let entry = JournalEntry::new(delivery_id.clone(), event_name, payload);
+    if let Err(e) = journal.record(entry).await {
+        error!("💻 Could not write delivery {delivery_id} to the journal. {e}");
     }
 }
 
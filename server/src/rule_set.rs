@@ -2,41 +2,156 @@
 //! from JSON, YAML, etc. so that the server can be configured without needing to recompile it every time.
 //!
 //! We maintain a vector of rules because order is important. Rules are run in the order that they are defined.
+//!
+//! A rule set may also define [`Subscription`]s to Github Pilot's internal events, under the `on` key, and
+//! [`Schedule`]s, under the `schedule` key. Schedules are jobs that run on a timer rather than in response to events.
+//!
+//! Rules can be limited to certain repositories or organisations with `repos` and `orgs` [`Scope`]s. These can be set
+//! on each rule, or at the top of the file, in which case they apply to every rule in the file that doesn't have a
+//! scope of its own.
+//!
+//! When a server looks after many repositories, the rules can be split over a directory of files (see
+//! [`RuleSet::from_dir`]). Typically, `base.yaml` holds the rules shared by every repository, and each of the other
+//! files has a top-level scope and holds the rules for one repository (or organisation). A rule in a scoped file that
+//! has the same name as a rule in the base file replaces it for the repositories that the overriding rule applies to.
 
-use std::{io::ErrorKind, path::Path};
+use std::{
+    io::ErrorKind,
+    path::{Path, PathBuf},
+};
 
+use log::*;
 use serde::{Deserialize, Serialize};
 
-use crate::rules::Rule;
+use crate::{events::Subscription, rules::Rule, schedule::Schedule, scope::Scope};
+
+/// The name (without extension) of the file holding the shared rules when loading rules from a directory.
+pub const BASE_RULES_FILE: &str = "base";
 
 #[derive(Debug, Default, Serialize, Deserialize, Eq, PartialEq)]
 pub struct RuleSet {
+    /// The default repository scope for the rules in this set.
+    #[serde(default, skip_serializing_if = "Scope::is_empty")]
+    repos: Scope,
+    /// The default organisation scope for the rules in this set.
+    #[serde(default, skip_serializing_if = "Scope::is_empty")]
+    orgs: Scope,
     rules: Vec<Rule>,
+    #[serde(rename = "on", default, skip_serializing_if = "Vec::is_empty")]
+    subscriptions: Vec<Subscription>,
+    #[serde(rename = "schedule", default, skip_serializing_if = "Vec::is_empty")]
+    schedules: Vec<Schedule>,
 }
 
 impl RuleSet {
     #[cfg(feature = "json")]
     pub fn from_json<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let json_file = std::fs::read_to_string(path)?;
-        let rs = serde_json::from_str(json_file.as_str())?;
-        Ok(rs)
+        let rs: Self = serde_json::from_str(json_file.as_str())?;
+        Ok(rs.with_default_scope())
     }
 
     #[cfg(feature = "yaml")]
     pub fn from_yaml<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
         let yaml_file = std::fs::read_to_string(path)?;
-        let rs =
+        let rs: Self =
             serde_yaml::from_str(yaml_file.as_str()).map_err(|e| std::io::Error::new(ErrorKind::InvalidData, e))?;
-        Ok(rs)
+        Ok(rs.with_default_scope())
+    }
+
+    /// Loads the rule set from `path`, which may be a YAML file, or a directory of them (see [`RuleSet::from_dir`]).
+    #[cfg(feature = "yaml")]
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        if path.as_ref().is_dir() {
+            Self::from_dir(path)
+        } else {
+            Self::from_yaml(path)
+        }
+    }
+
+    /// Loads every `.yaml` and `.yml` file in the directory (but not its subdirectories) into a single rule set.
+    /// The base file ([`BASE_RULES_FILE`]), if there is one, is loaded first, and the rest are [merged](RuleSet::merge)
+    /// into it in alphabetical order.
+    #[cfg(feature = "yaml")]
+    pub fn from_dir<P: AsRef<Path>>(path: P) -> Result<Self, std::io::Error> {
+        let mut rule_set = Self::default();
+        for file in rule_files(path)? {
+            debug!("📄 Loading rules from {}", file.display());
+            let rs = Self::from_yaml(&file).map_err(|e| {
+                std::io::Error::new(e.kind(), format!("Could not load rules from {}. {e}", file.display()))
+            })?;
+            rule_set.merge(rs);
+        }
+        Ok(rule_set)
+    }
+
+    /// Adds the rules, subscriptions and schedules in `other` to this rule set. Rules in this set that have the same
+    /// name as a rule in `other` no longer apply to the repositories and organisations in that rule's scope.
+    pub fn merge(&mut self, other: RuleSet) {
+        for rule in self.rules.iter_mut() {
+            let overrides = other
+                .rules
+                .iter()
+                .filter(|r| r.name() == rule.name())
+                .cloned()
+                .collect::<Vec<_>>();
+            for overriding in overrides.iter() {
+                rule.override_with(overriding);
+            }
+        }
+        self.rules.extend(other.rules);
+        self.subscriptions.extend(other.subscriptions);
+        self.schedules.extend(other.schedules);
+    }
+
+    /// Applies this rule set's scope to every rule that doesn't have a scope of its own.
+    pub(crate) fn with_default_scope(mut self) -> Self {
+        for rule in self.rules.iter_mut() {
+            rule.inherit_scope(&self.repos, &self.orgs);
+        }
+        self
     }
 
     pub fn add_rule(&mut self, rule: Rule) {
         self.rules.push(rule);
     }
 
+    pub fn add_subscription(&mut self, subscription: Subscription) {
+        self.subscriptions.push(subscription);
+    }
+
+    pub fn add_schedule(&mut self, schedule: Schedule) {
+        self.schedules.push(schedule);
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        self.rules.as_slice()
+    }
+
+    pub fn subscriptions(&self) -> &[Subscription] {
+        self.subscriptions.as_slice()
+    }
+
+    pub fn schedules(&self) -> &[Schedule] {
+        self.schedules.as_slice()
+    }
+
     pub fn to_rules(self) -> Vec<Rule> {
         self.rules
     }
+
+    pub fn to_subscriptions(self) -> Vec<Subscription> {
+        self.subscriptions
+    }
+
+    pub fn to_schedules(self) -> Vec<Schedule> {
+        self.schedules
+    }
+
+    /// Splits the rule set into its rules, subscriptions and schedules.
+    pub fn into_parts(self) -> (Vec<Rule>, Vec<Subscription>, Vec<Schedule>) {
+        (self.rules, self.subscriptions, self.schedules)
+    }
 }
 
 impl From<RuleSet> for Vec<Rule> {
@@ -47,6 +162,195 @@ impl From<RuleSet> for Vec<Rule> {
 
 impl From<Vec<Rule>> for RuleSet {
     fn from(rules: Vec<Rule>) -> Self {
-        Self { rules }
+        Self {
+            repos: Scope::default(),
+            orgs: Scope::default(),
+            rules,
+            subscriptions: Vec::new(),
+            schedules: Vec::new(),
+        }
+    }
+}
+
+/// Returns `true` if `path` is the base rules file ([`BASE_RULES_FILE`]) of a rules directory.
+pub fn is_base_file(path: &Path) -> bool {
+    path.file_stem().map(|s| s == BASE_RULES_FILE).unwrap_or(false)
+}
+
+/// Lists the `.yaml` and `.yml` files in the directory (but not its subdirectories) in the order that
+/// [`RuleSet::from_dir`] loads them: the base file first, followed by the rest in alphabetical order.
+pub fn rule_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>, std::io::Error> {
+    let mut files = std::fs::read_dir(path.as_ref())?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|p| p.is_file() && p.extension().map(|ext| ext == "yaml" || ext == "yml").unwrap_or(false))
+        .collect::<Vec<_>>();
+    files.sort();
+    let (base, others): (Vec<_>, Vec<_>) = files.into_iter().partition(|p| is_base_file(p));
+    Ok(base.into_iter().chain(others).collect())
+}
+
+#[cfg(all(test, feature = "yaml"))]
+mod test {
+    use github_pilot_api::GithubEvent;
+
+    use super::RuleSet;
+    use crate::pub_sub::GithubEventMessage;
+
+    const BASE: &str = r#"
+rules:
+  - name: greet
+    when:
+      - pull_request: opened
+  - name: label
+    when:
+      - pull_request: opened
+"#;
+
+    const DAN: &str = r#"
+repos:
+  include: [tari-project/tari-dan]
+rules:
+  - name: label
+    when:
+      - pull_request: opened
+  - name: other repo
+    repos:
+      include: [tari-project/tari]
+    when:
+      - pull_request: opened
+"#;
+
+    fn matching_rules(rule_set: &RuleSet, event: &GithubEventMessage) -> Vec<String> {
+        rule_set
+            .rules()
+            .iter()
+            .filter(|r| r.matches(event).is_some())
+            .map(|r| r.name().to_string())
+            .collect()
+    }
+
+    fn pr_event(owner: &str, repo: &str) -> GithubEventMessage {
+        let data = include_str!("../test-data/pr_event.json")
+            .replace("tari-project/tari-dan", &format!("{owner}/{repo}"))
+            .replace(r#""login": "tari-project""#, &format!(r#""login": "{owner}""#));
+        GithubEventMessage::new(
+            "pull_request",
+            GithubEvent::try_from_webhook_info("pull_request", &data).unwrap(),
+        )
+    }
+
+    fn rules_in_scope(rule_set: &RuleSet, event: &GithubEventMessage) -> Vec<usize> {
+        (0..rule_set.rules().len())
+            .filter(|&i| rule_set.rules()[i].in_scope(event))
+            .collect()
+    }
+
+    /// Merges the given overrides into the base rules
+    fn merged(overrides: &str) -> RuleSet {
+        let mut rule_set: RuleSet = serde_yaml::from_str(BASE).unwrap();
+        rule_set.merge(serde_yaml::from_str::<RuleSet>(overrides).unwrap().with_default_scope());
+        rule_set
+    }
+
+    #[test]
+    fn scoped_rules_and_overrides() {
+        let data = include_str!("../test-data/pr_event.json");
+        let dan_event = GithubEventMessage::new(
+            "pull_request",
+            GithubEvent::try_from_webhook_info("pull_request", data).unwrap(),
+        );
+        let other_event = GithubEventMessage::new(
+            "pull_request",
+            GithubEvent::try_from_webhook_info("pull_request", &data.replace("tari-project/tari-dan", "x/y")).unwrap(),
+        );
+
+        let mut rule_set: RuleSet = serde_yaml::from_str(BASE).unwrap();
+        let dan: RuleSet = serde_yaml::from_str::<RuleSet>(DAN).unwrap().with_default_scope();
+        rule_set.merge(dan);
+        assert_eq!(rule_set.rules().len(), 4);
+        // The base `label` rule is replaced by the tari-dan one, and `other repo` keeps its own scope
+        assert_eq!(matching_rules(&rule_set, &dan_event), vec!["greet", "label"]);
+        assert!(rule_set.rules()[3].repos().matches("tari-project/tari"));
+        assert_eq!(matching_rules(&rule_set, &other_event), vec!["greet", "label"]);
+        assert!(!rule_set.rules()[2].in_scope(&other_event));
+    }
+
+    #[test]
+    fn load_directory() {
+        let dir = std::env::temp_dir().join(format!("ghp_rules_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a_tari_dan.yml"), DAN).unwrap();
+        std::fs::write(dir.join("base.yaml"), BASE).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not rules").unwrap();
+        let rule_set = RuleSet::load(&dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        let names = rule_set.rules().iter().map(|r| r.name()).collect::<Vec<_>>();
+        assert_eq!(names, vec!["greet", "label", "label", "other repo"]);
+        let dan_event = pr_event("tari-project", "tari-dan");
+        assert_eq!(rules_in_scope(&rule_set, &dan_event), vec![0, 2]);
+    }
+
+    #[test]
+    fn override_with_excluded_repos() {
+        let overrides = r#"
+repos:
+  include: ["tari-project/*"]
+  exclude: [tari-project/tari-crypto]
+rules:
+  - name: label
+    when:
+      - pull_request: opened
+"#;
+        let rule_set = merged(overrides);
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("tari-project", "tari-dan")), vec![
+            0, 2
+        ]);
+        // The override doesn't apply to tari-crypto, so the base rule still does
+        assert_eq!(
+            rules_in_scope(&rule_set, &pr_event("tari-project", "tari-crypto")),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn override_with_repos_and_orgs() {
+        let overrides = r#"
+repos:
+  include: ["*/tari-dan"]
+orgs:
+  include: [tari-project]
+rules:
+  - name: label
+    when:
+      - pull_request: opened
+"#;
+        let rule_set = merged(overrides);
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("tari-project", "tari-dan")), vec![
+            0, 2
+        ]);
+        // Only repositories in both scopes are overridden
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("other-org", "tari-dan")), vec![
+            0, 1
+        ]);
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("tari-project", "tari")), vec![0, 1]);
+    }
+
+    #[test]
+    fn override_with_rule_scope() {
+        let overrides = r#"
+rules:
+  - name: label
+    repos:
+      include: [tari-project/tari-dan]
+    when:
+      - pull_request: opened
+"#;
+        let rule_set = merged(overrides);
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("tari-project", "tari-dan")), vec![
+            0, 2
+        ]);
+        assert_eq!(rules_in_scope(&rule_set, &pr_event("tari-project", "tari")), vec![0, 1]);
     }
 }
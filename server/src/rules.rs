@@ -25,7 +25,7 @@ use std::{fmt::Debug, slice::Iter, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 
-use crate::{actions::Actions, predicates::Predicate, pub_sub::GithubEventMessage, utilities::timestamp};
+use crate::{actions::Actions, predicates::Predicate, pub_sub::GithubEventMessage, scope::Scope, utilities::timestamp};
 
 pub type ActionVec<'a> = Iter<'a, Arc<Actions>>;
 
@@ -33,7 +33,7 @@ pub type ActionVec<'a> = Iter<'a, Arc<Actions>>;
 /// When the Github Pilot receives an event from the webhook, it will scan all its registered rules. For each rule
 /// that is triggered (because one/more of the predicates match the event), all notifications get sent out, and all
 /// actions get executed.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct Rule {
     #[serde(flatten)]
     inner_rule: RuleInner,
@@ -50,9 +50,49 @@ impl Rule {
         self.inner_rule.then_actions.iter()
     }
 
+    /// Return this Rule's predicates. The rule triggers if any of them match.
+    pub fn predicates(&self) -> &[Arc<Predicate>] {
+        self.inner_rule.predicates.as_slice()
+    }
+
+    /// The repositories (as `owner/repo`) that this rule applies to.
+    pub fn repos(&self) -> &Scope {
+        &self.inner_rule.scope.repos
+    }
+
+    /// The organisations (or repository owners) that this rule applies to.
+    pub fn orgs(&self) -> &Scope {
+        &self.inner_rule.scope.orgs
+    }
+
+    /// Determine whether the repository that the event came from is in this rule's scope. Rules without a scope
+    /// apply to every event. Scoped rules never apply to events that don't belong to a repository. A rule that has
+    /// been overridden doesn't apply to events that are in the overriding rule's scope.
+    pub fn in_scope(&self, event: &GithubEventMessage) -> bool {
+        let rule = &self.inner_rule;
+        rule.scope.includes(event) && !rule.overridden_in.iter().any(|scope| scope.includes(event))
+    }
+
+    /// Sets the rule's scope to the given one, unless it has already been scoped.
+    pub(crate) fn inherit_scope(&mut self, repos: &Scope, orgs: &Scope) {
+        let scope = &mut self.inner_rule.scope;
+        if scope.is_empty() {
+            scope.repos = repos.clone();
+            scope.orgs = orgs.clone();
+        }
+    }
+
+    /// Stops this rule from applying to events in the scope of `other`, which replaces it there.
+    pub(crate) fn override_with(&mut self, other: &Rule) {
+        self.inner_rule.overridden_in.push(other.inner_rule.scope.clone());
+    }
+
     /// Determine whether this rule's predicate match against the given github event, returning the first predicate
-    /// that matches, or None.
+    /// that matches, or None. Events from repositories outside the rule's scope never match.
     pub(crate) fn matches(&self, event: &GithubEventMessage) -> Option<Arc<Predicate>> {
+        if !self.in_scope(event) {
+            return None;
+        }
         self.inner_rule
             .predicates
             .iter()
@@ -66,9 +106,14 @@ impl Rule {
 }
 
 /// Common fields that get passed between the builder and the rule itself.
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub(crate) struct RuleInner {
     name: String,
+    #[serde(flatten)]
+    scope: RuleScope,
+    /// The scopes of rules with the same name that replace this one where they apply.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    overridden_in: Vec<RuleScope>,
     #[serde(rename = "when", default)]
     predicates: Vec<Arc<Predicate>>,
     #[serde(rename = "execute", default)]
@@ -81,6 +126,8 @@ impl Default for RuleInner {
     fn default() -> Self {
         Self {
             name: RuleInner::time_stamped_name(),
+            scope: RuleScope::default(),
+            overridden_in: Vec::new(),
             predicates: Vec::new(),
             actions: Vec::new(),
             then_actions: Vec::new(),
@@ -101,6 +148,33 @@ impl RuleInner {
     }
 }
 
+/// The repositories and organisations that a rule applies to.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub(crate) struct RuleScope {
+    #[serde(default, skip_serializing_if = "Scope::is_empty")]
+    repos: Scope,
+    #[serde(default, skip_serializing_if = "Scope::is_empty")]
+    orgs: Scope,
+}
+
+impl RuleScope {
+    fn is_empty(&self) -> bool {
+        self.repos.is_empty() && self.orgs.is_empty()
+    }
+
+    fn includes(&self, event: &GithubEventMessage) -> bool {
+        if self.is_empty() {
+            return true;
+        }
+        match event.event().common_fields() {
+            Some(info) => {
+                self.repos.matches(&info.repository.full_name) && self.orgs.matches(&info.repository.owner.login)
+            },
+            None => false,
+        }
+    }
+}
+
 /// Define behaviour for structs that want to be defined as Rule predicate conditions.
 /// A rule predicate defines a single method, [`matches`], that takes a Github event message and decides whether the
 /// predicate is satisfied or not. If so, it returns `true`, otherwise it must return false. This method must not panic
@@ -122,6 +196,18 @@ impl RuleBuilder {
         }
     }
 
+    /// Only apply the rule to events from repositories (given as `owner/repo`) in the scope.
+    pub fn for_repos(mut self, scope: Scope) -> Self {
+        self.inner_rule.scope.repos = scope;
+        self
+    }
+
+    /// Only apply the rule to events from repositories owned by organisations (or users) in the scope.
+    pub fn for_orgs(mut self, scope: Scope) -> Self {
+        self.inner_rule.scope.orgs = scope;
+        self
+    }
+
     /// Add an event predicate. You can add any number of event predicates. The rule will trigger if _any_ of the
     /// predicates match the event.
     pub fn when(mut self, pred: impl RulePredicate + 'static) -> Self {
@@ -129,6 +215,24 @@ impl RuleBuilder {
         self
     }
 
+    /// Add a compound predicate that only matches if _all_ of the given predicates match the event.
+    pub fn when_all<I: IntoIterator<Item = Predicate>>(mut self, preds: I) -> Self {
+        self.inner_rule.predicates.push(Arc::new(Predicate::all(preds)));
+        self
+    }
+
+    /// Add a compound predicate that matches if _any_ of the given predicates match the event.
+    pub fn when_any<I: IntoIterator<Item = Predicate>>(mut self, preds: I) -> Self {
+        self.inner_rule.predicates.push(Arc::new(Predicate::any(preds)));
+        self
+    }
+
+    /// Add a predicate that matches if the given predicate does _not_ match the event.
+    pub fn when_not(mut self, pred: impl RulePredicate + 'static) -> Self {
+        self.inner_rule.predicates.push(Arc::new(Predicate::not(pred)));
+        self
+    }
+
     /// Add an action to the set of actions to fire when the rule matches. You may add any number of actions.
     pub fn execute(mut self, action: Actions) -> Self {
         self.inner_rule.actions.push(Arc::new(action));
@@ -0,0 +1,151 @@
+use std::{fmt::Display, str::FromStr};
+
+use chrono::{TimeZone, Utc};
+use github_pilot_api::models::Timestamp;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::schedule::Interval;
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+#[error("'{expression}' is not a valid cron expression. {reason}")]
+pub struct CronParseError {
+    expression: String,
+    reason: String,
+}
+
+/// A cron expression, with the fields `sec min hour day-of-month month day-of-week [year]`, e.g. `0 0 9 * * Mon-Fri`.
+/// Times are in UTC. See the [`cron`] crate for the full syntax.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct CronExpression {
+    expression: String,
+    schedule: Box<cron::Schedule>,
+}
+
+impl CronExpression {
+    pub fn as_str(&self) -> &str {
+        self.expression.as_str()
+    }
+}
+
+impl PartialEq for CronExpression {
+    fn eq(&self, other: &Self) -> bool {
+        self.expression == other.expression
+    }
+}
+
+impl Eq for CronExpression {}
+
+impl FromStr for CronExpression {
+    type Err = CronParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = s.trim().to_string();
+        match cron::Schedule::from_str(&expression) {
+            Ok(schedule) => Ok(Self {
+                expression,
+                schedule: Box::new(schedule),
+            }),
+            Err(e) => Err(CronParseError {
+                expression,
+                reason: e.to_string(),
+            }),
+        }
+    }
+}
+
+impl Display for CronExpression {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl TryFrom<String> for CronExpression {
+    type Error = CronParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<CronExpression> for String {
+    fn from(expression: CronExpression) -> Self {
+        expression.expression
+    }
+}
+
+/// When a [`crate::schedule::Schedule`] runs: either on a cron schedule, or every `n` units of time.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cadence {
+    Cron(CronExpression),
+    /// Shorthand for a job that runs at a fixed interval. Runs are aligned to multiples of the interval since the Unix
+    /// epoch, so `every: 1d` runs at midnight UTC, and `every: 6h` runs at midnight, 06:00, 12:00 and 18:00 UTC.
+    Every(Interval),
+}
+
+impl Cadence {
+    /// The first time strictly after `now` that the job is due, or `None` if it will never run again.
+    pub fn next_after(&self, now: Timestamp) -> Option<Timestamp> {
+        match self {
+            Self::Cron(expression) => expression.schedule.after(&now).next(),
+            Self::Every(interval) => {
+                let period = i64::try_from(interval.as_secs()).ok()?;
+                let next = now.timestamp().div_euclid(period).checked_add(1)?.checked_mul(period)?;
+                Utc.timestamp_opt(next, 0).single()
+            },
+        }
+    }
+}
+
+impl Display for Cadence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cron(expression) => write!(f, "on `{expression}`"),
+            Self::Every(interval) => write!(f, "every {interval}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use chrono::{TimeZone, Utc};
+
+    use super::{Cadence, CronExpression};
+    use crate::schedule::Interval;
+
+    #[test]
+    fn intervals_are_aligned_to_the_epoch() {
+        let now = Utc.with_ymd_and_hms(2022, 10, 4, 13, 21, 5).unwrap();
+        let daily = Cadence::Every(Interval::from_days(1));
+        assert_eq!(
+            daily.next_after(now),
+            Utc.with_ymd_and_hms(2022, 10, 5, 0, 0, 0).single()
+        );
+        let six_hourly = Cadence::Every(Interval::from_hours(6));
+        assert_eq!(
+            six_hourly.next_after(now),
+            Utc.with_ymd_and_hms(2022, 10, 4, 18, 0, 0).single()
+        );
+        // Strictly after: a job that is due right now is next due one period later
+        let at_six = Utc.with_ymd_and_hms(2022, 10, 4, 18, 0, 0).unwrap();
+        assert_eq!(
+            six_hourly.next_after(at_six),
+            Utc.with_ymd_and_hms(2022, 10, 5, 0, 0, 0).single()
+        );
+    }
+
+    #[test]
+    fn cron_expressions() {
+        // 2022-10-08 is a Saturday
+        let now = Utc.with_ymd_and_hms(2022, 10, 8, 13, 0, 0).unwrap();
+        let weekdays: CronExpression = "0 30 9 * * Mon-Fri".parse().unwrap();
+        let cadence = Cadence::Cron(weekdays);
+        assert_eq!(
+            cadence.next_after(now),
+            Utc.with_ymd_and_hms(2022, 10, 10, 9, 30, 0).single()
+        );
+        assert!("every tuesday".parse::<CronExpression>().is_err());
+    }
+}
@@ -0,0 +1,109 @@
+use std::{fmt::Display, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+const UNITS: [(char, u64); 5] = [('w', 604_800), ('d', 86_400), ('h', 3_600), ('m', 60), ('s', 1)];
+
+#[derive(Clone, Debug, Error, PartialEq, Eq)]
+pub enum IntervalParseError {
+    #[error("'{0}' is not a valid interval. Use a number followed by one of s, m, h, d or w, e.g. 30d")]
+    InvalidFormat(String),
+    #[error("Intervals must be longer than zero")]
+    Zero,
+}
+
+/// A period of time, written in configuration files as a whole number followed by a unit, e.g. `90s`, `6h` or `30d`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Interval(u64);
+
+impl Interval {
+    pub fn from_secs(secs: u64) -> Self {
+        Self(secs)
+    }
+
+    pub fn from_hours(hours: u64) -> Self {
+        Self(hours * 3_600)
+    }
+
+    pub fn from_days(days: u64) -> Self {
+        Self(days * 86_400)
+    }
+
+    pub fn as_secs(&self) -> u64 {
+        self.0
+    }
+
+    pub fn as_duration(&self) -> Duration {
+        Duration::from_secs(self.0)
+    }
+}
+
+impl FromStr for Interval {
+    type Err = IntervalParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let invalid = || IntervalParseError::InvalidFormat(s.to_string());
+        let unit = s.chars().last().ok_or_else(invalid)?;
+        let (_, scale) = UNITS.iter().find(|(u, _)| *u == unit).ok_or_else(invalid)?;
+        let n = s[..s.len() - 1].trim().parse::<u64>().map_err(|_| invalid())?;
+        match n.checked_mul(*scale) {
+            Some(0) => Err(IntervalParseError::Zero),
+            Some(secs) => Ok(Self(secs)),
+            None => Err(invalid()),
+        }
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (unit, scale) = UNITS
+            .iter()
+            .find(|(_, scale)| self.0.checked_rem(*scale) == Some(0))
+            .unwrap_or(&('s', 1));
+        write!(f, "{}{unit}", self.0 / scale)
+    }
+}
+
+impl TryFrom<String> for Interval {
+    type Error = IntervalParseError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<Interval> for String {
+    fn from(interval: Interval) -> Self {
+        interval.to_string()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Interval, IntervalParseError};
+
+    #[test]
+    fn parse_intervals() {
+        assert_eq!("90s".parse::<Interval>().unwrap().as_secs(), 90);
+        assert_eq!("15m".parse::<Interval>().unwrap().as_secs(), 900);
+        assert_eq!(" 6h ".parse::<Interval>().unwrap(), Interval::from_hours(6));
+        assert_eq!("30d".parse::<Interval>().unwrap(), Interval::from_days(30));
+        assert_eq!("2w".parse::<Interval>().unwrap(), Interval::from_days(14));
+        assert_eq!("0d".parse::<Interval>(), Err(IntervalParseError::Zero));
+        assert!("d".parse::<Interval>().is_err());
+        assert!("10".parse::<Interval>().is_err());
+        assert!("10y".parse::<Interval>().is_err());
+        assert!("".parse::<Interval>().is_err());
+    }
+
+    #[test]
+    fn display_uses_largest_unit() {
+        assert_eq!(Interval::from_days(14).to_string(), "2w");
+        assert_eq!(Interval::from_days(3).to_string(), "3d");
+        assert_eq!(Interval::from_secs(5400).to_string(), "90m");
+        assert_eq!(Interval::from_secs(61).to_string(), "61s");
+    }
+}
@@ -0,0 +1,288 @@
+//! Scheduled jobs. Everything else in Github Pilot reacts to webhook events; scheduled jobs are the exception, and run
+//! periodically on a timer instead.
+//!
+//! Schedules are defined alongside the rules in the rule set, under the `schedule` key:
+//!
+//! ```yaml
+//! schedule:
+//!   - name: Stale sweeper
+//!     cron: "0 0 6 * * Mon-Fri"
+//!     repos:
+//!       - tari-project/tari
+//!     job:
+//!       stale_sweep:
+//!         stale_after: 30d
+//!         close_after: 7d
+//!         label: stale
+//!         exempt_labels: [pinned]
+//!         comment: "This has been quiet for a while, @{{issue.author}}. It will be closed if nothing happens."
+//! ```
+//!
+//! Each schedule gives either a `cron` expression (with a seconds field, in UTC), or, as a shorthand for jobs that run
+//! at a fixed interval, `every`. Intervals are written as a number followed by a unit: `s`, `m`, `h`, `d` or `w`.
+mod cadence;
+mod interval;
+mod scheduler;
+mod stale;
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+pub use cadence::{Cadence, CronExpression, CronParseError};
+use github_pilot_api::{
+    error::GithubProviderError,
+    provider_traits::{IssueProvider, RepoProvider},
+    wrappers::RepoId,
+    GithubProvider,
+};
+pub use interval::{Interval, IntervalParseError};
+use log::*;
+pub use scheduler::{ReplaceSchedulesMessage, SchedulerActor};
+use serde::{Deserialize, Serialize};
+pub use stale::{StaleSweep, StaleTarget, StaleVerdict, SweepSummary};
+
+use crate::dry_run::DryRunProvider;
+
+/// The parts of the Github API that scheduled jobs use.
+#[async_trait]
+pub trait ScheduleProvider: IssueProvider + RepoProvider + Send + Sync {
+    /// Returns a provider to run jobs against the given repository with. When running as a Github App, this uses the
+    /// credentials of the installation that covers the repository.
+    async fn for_repo(self: Arc<Self>, repo: &RepoId) -> Result<Arc<dyn ScheduleProvider>, GithubProviderError>;
+}
+
+#[async_trait]
+impl ScheduleProvider for GithubProvider {
+    async fn for_repo(self: Arc<Self>, repo: &RepoId) -> Result<Arc<dyn ScheduleProvider>, GithubProviderError> {
+        let provider = GithubProvider::for_repo(&self, repo.owner(), repo.repo()).await?;
+        Ok(Arc::new(provider))
+    }
+}
+
+#[async_trait]
+impl<P: ScheduleProvider + ?Sized> ScheduleProvider for DryRunProvider<P> {
+    async fn for_repo(self: Arc<Self>, repo: &RepoId) -> Result<Arc<dyn ScheduleProvider>, GithubProviderError> {
+        let scoped = Arc::clone(self.inner()).for_repo(repo).await?;
+        Ok(Arc::new(DryRunProvider::with_report(scoped, Arc::clone(self.report()))))
+    }
+}
+
+/// A job that is run periodically against every repository in a [`Schedule`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledJob {
+    StaleSweep(StaleSweep),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Schedule {
+    pub name: String,
+    #[serde(flatten)]
+    pub cadence: Cadence,
+    /// The repositories the job runs against, as `owner/repo`.
+    pub repos: Vec<String>,
+    pub job: ScheduledJob,
+}
+
+impl Schedule {
+    pub fn new<S: Into<String>>(name: S, cadence: Cadence, job: ScheduledJob) -> Self {
+        Self {
+            name: name.into(),
+            cadence,
+            repos: Vec::new(),
+            job,
+        }
+    }
+
+    pub fn for_repo<S: Into<String>>(mut self, repo: S) -> Self {
+        self.repos.push(repo.into());
+        self
+    }
+
+    /// Runs the job once against each of the schedule's repositories. Failures are logged and do not prevent the job
+    /// from running against the remaining repositories.
+    pub async fn run(&self, provider: Arc<dyn ScheduleProvider>) {
+        for repo in &self.repos {
+            let id = match repo.parse::<RepoId>() {
+                Ok(id) => id,
+                Err(e) => {
+                    warn!("⏰ Schedule '{}' has an invalid repository, '{repo}'. {e}", self.name);
+                    continue;
+                },
+            };
+            let provider = match Arc::clone(&provider).for_repo(&id).await {
+                Ok(provider) => provider,
+                Err(e) => {
+                    warn!(
+                        "⏰ Schedule '{}' could not get Github credentials for {id}. {e}",
+                        self.name
+                    );
+                    continue;
+                },
+            };
+            match &self.job {
+                ScheduledJob::StaleSweep(sweep) => match sweep.run(provider.as_ref(), &id).await {
+                    Ok(summary) => info!("⏰ Schedule '{}' swept {id}. {summary}", self.name),
+                    Err(e) => warn!("⏰ Schedule '{}' could not sweep {id}. {e}", self.name),
+                },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use async_trait::async_trait;
+    use github_pilot_api::{
+        error::GithubProviderError,
+        models::{State, StateReason},
+        testing::{MockGithub, Mutation},
+        wrappers::{IssueId, RepoId},
+    };
+
+    use super::{Cadence, Interval, Schedule, ScheduleProvider, ScheduledJob, StaleSweep};
+
+    #[async_trait]
+    impl ScheduleProvider for MockGithub {
+        async fn for_repo(self: Arc<Self>, _repo: &RepoId) -> Result<Arc<dyn ScheduleProvider>, GithubProviderError> {
+            Ok(self)
+        }
+    }
+
+    #[test]
+    fn deserialize_schedule() {
+        let yaml = r#"
+name: Stale sweeper
+every: 6h
+repos: [tari-project/tari, tari-project/tari-dan]
+job:
+  stale_sweep:
+    applies_to: pull_requests
+    stale_after: 30d
+    close_after: 1w
+    comment: Nudge
+"#;
+        let schedule: Schedule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(schedule.cadence, Cadence::Every(Interval::from_hours(6)));
+        assert_eq!(schedule.repos.len(), 2);
+        let ScheduledJob::StaleSweep(sweep) = &schedule.job;
+        assert_eq!(sweep.stale_after, Interval::from_days(30));
+        assert_eq!(sweep.close_after, Some(Interval::from_days(7)));
+        assert_eq!(sweep.label, "stale");
+        assert_eq!(sweep.comment.as_deref(), Some("Nudge"));
+    }
+
+    #[test]
+    fn deserialize_cron_schedule() {
+        let yaml = r#"
+name: Weekday sweeper
+cron: 0 0 6 * * Mon-Fri
+repos: [tari-project/tari]
+job:
+  stale_sweep:
+    stale_after: 30d
+"#;
+        let schedule: Schedule = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(schedule.cadence, Cadence::Cron("0 0 6 * * Mon-Fri".parse().unwrap()));
+        let yaml = yaml.replace("Mon-Fri", "Someday");
+        assert!(serde_yaml::from_str::<Schedule>(&yaml).is_err());
+    }
+
+    #[test]
+    fn serialize_schedule() {
+        let schedule = Schedule::new(
+            "Stale issues",
+            Cadence::Every(Interval::from_days(1)),
+            ScheduledJob::StaleSweep(StaleSweep::new(Interval::from_days(60))),
+        )
+        .for_repo("tari-project/tari");
+        let yaml = serde_yaml::to_string(&schedule).unwrap();
+        assert_eq!(
+            yaml,
+            "---\nname: Stale issues\nevery: 1d\nrepos:\n  - tari-project/tari\njob:\n  stale_sweep:\n    applies_to: \
+             all\n    stale_after: 60d\n    label: stale\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn run_sweeps_each_repo() {
+        let fresh = IssueId::new("tari-project", "tari", 1);
+        let stale = IssueId::new("tari-project", "tari", 2);
+        let pinned = IssueId::new("tari-project", "tari", 3);
+        let other_repo = IssueId::new("tari-project", "tari-dan", 4);
+        let github = Arc::new(
+            MockGithub::new()
+                .with_issue(&fresh, "Quiet issue")
+                .with_pull_request(&stale, "Stale PR")
+                .with_label(&stale, "stale")
+                .with_issue(&pinned, "Pinned issue")
+                .with_label(&pinned, "pinned")
+                .with_issue(&other_repo, "Quiet issue"),
+        );
+        let sweep = StaleSweep::new(Interval::from_days(30))
+            .close_after(Interval::from_days(7))
+            .exempt("pinned")
+            .with_comment("@{{issue.author}}, this has gone quiet")
+            .with_close_comment("Closing #{{issue.number}}");
+        let schedule = Schedule::new(
+            "Stale sweeper",
+            Cadence::Every(Interval::from_days(1)),
+            ScheduledJob::StaleSweep(sweep.clone()),
+        )
+        .for_repo("tari-project/tari")
+        .for_repo("not a repo")
+        .for_repo("tari-project/tari-dan");
+        schedule.run(github.clone()).await;
+
+        let author = github.issue(&fresh).unwrap().user.unwrap().login;
+        assert_eq!(github.mutations(), vec![
+            Mutation::AddLabel {
+                id: fresh.clone(),
+                label: "stale".into()
+            },
+            Mutation::AddComment {
+                id: fresh.clone(),
+                body: format!("@{author}, this has gone quiet")
+            },
+            Mutation::AddComment {
+                id: stale.clone(),
+                body: "Closing #2".into()
+            },
+            Mutation::SetState {
+                id: stale.clone(),
+                state: State::Closed,
+                reason: None
+            },
+            Mutation::AddLabel {
+                id: other_repo.clone(),
+                label: "stale".into()
+            },
+            Mutation::AddComment {
+                id: other_repo.clone(),
+                body: format!("@{author}, this has gone quiet")
+            },
+        ]);
+        assert_eq!(github.issue(&stale).unwrap().state, State::Closed);
+
+        // The next sweep closes the issue that was marked stale, since the mock doesn't update timestamps
+        github.clear_mutations();
+        let summary = sweep
+            .run(github.as_ref(), &"tari-project/tari".parse().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(summary.checked, 2);
+        assert_eq!(summary.marked, 0);
+        assert_eq!(summary.closed, 1);
+        assert_eq!(summary.failed, 0);
+        assert_eq!(
+            github.mutations().last(),
+            Some(&Mutation::SetState {
+                id: fresh,
+                state: State::Closed,
+                reason: Some(StateReason::NotPlanned)
+            })
+        );
+    }
+}
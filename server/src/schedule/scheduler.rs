@@ -0,0 +1,116 @@
+use std::{sync::Arc, time::SystemTime};
+
+use actix::{Actor, Context, Handler, Message};
+use github_pilot_api::{models::Timestamp, GithubProvider};
+use log::*;
+use tokio::sync::oneshot;
+
+use crate::{
+    dry_run,
+    dry_run::DryRunProvider,
+    schedule::{Schedule, ScheduleProvider},
+};
+
+/// Runs each [`Schedule`] on its own timer, whenever its [`crate::schedule::Cadence`] says the job is due. Due times
+/// come from the wall clock, so restarting the server or reloading the rules doesn't move them.
+pub struct SchedulerActor {
+    provider: Arc<dyn ScheduleProvider>,
+    jobs: Vec<ScheduledTask>,
+}
+
+/// A schedule that is running on its own task. Dropping it stops the task, but never in the middle of a run.
+struct ScheduledTask {
+    schedule: Schedule,
+    _stop: oneshot::Sender<()>,
+}
+
+impl Default for SchedulerActor {
+    fn default() -> Self {
+        Self::new(Arc::new(GithubProvider::default()))
+    }
+}
+
+impl SchedulerActor {
+    pub fn new(provider: Arc<dyn ScheduleProvider>) -> Self {
+        Self {
+            provider,
+            jobs: Vec::new(),
+        }
+    }
+
+    fn start_job(&self, schedule: Schedule) -> ScheduledTask {
+        let (stop_tx, mut stop_rx) = oneshot::channel::<()>();
+        let provider = Arc::clone(&self.provider);
+        let job = schedule.clone();
+        tokio::spawn(async move {
+            loop {
+                let now = Timestamp::from(SystemTime::now());
+                let next = match job.cadence.next_after(now) {
+                    Some(next) => next,
+                    None => {
+                        info!("⏰ Schedule '{}' will not run again", job.name);
+                        break;
+                    },
+                };
+                debug!("⏰ Schedule '{}' will next run at {next}", job.name);
+                let wait = (next - now).to_std().unwrap_or_default();
+                tokio::select! {
+                    _ = tokio::time::sleep(wait) => {},
+                    _ = &mut stop_rx => break,
+                }
+                debug!("⏰ Running scheduled job '{}'", job.name);
+                let provider: Arc<dyn ScheduleProvider> = match dry_run::is_enabled() {
+                    true => Arc::new(DryRunProvider::new(Arc::clone(&provider))),
+                    false => Arc::clone(&provider),
+                };
+                job.run(provider).await;
+            }
+        });
+        ScheduledTask {
+            schedule,
+            _stop: stop_tx,
+        }
+    }
+}
+
+impl Actor for SchedulerActor {
+    type Context = Context<Self>;
+
+    fn stopped(&mut self, _ctx: &mut Self::Context) {
+        self.jobs.clear();
+    }
+}
+
+/// Replaces the running schedules with a new set. Jobs whose schedule has not changed keep running undisturbed, and
+/// jobs that are removed finish any run that is in progress before they stop.
+pub struct ReplaceSchedulesMessage {
+    pub new_schedules: Vec<Schedule>,
+}
+
+impl Message for ReplaceSchedulesMessage {
+    type Result = usize;
+}
+
+impl Handler<ReplaceSchedulesMessage> for SchedulerActor {
+    type Result = usize;
+
+    fn handle(&mut self, msg: ReplaceSchedulesMessage, _ctx: &mut Self::Context) -> Self::Result {
+        let mut old_jobs = std::mem::take(&mut self.jobs);
+        for schedule in msg.new_schedules {
+            match old_jobs.iter().position(|job| job.schedule == schedule) {
+                Some(i) => self.jobs.push(old_jobs.swap_remove(i)),
+                None => {
+                    let job = self.start_job(schedule);
+                    self.jobs.push(job);
+                },
+            }
+        }
+        if !old_jobs.is_empty() {
+            debug!(
+                "⏰ Stopping {} scheduled jobs that are no longer in the rule set",
+                old_jobs.len()
+            );
+        }
+        self.jobs.len()
+    }
+}
@@ -0,0 +1,312 @@
+use std::fmt::Display;
+
+use github_pilot_api::{
+    error::GithubProviderError,
+    models::{Issue, State, StateReason, Timestamp},
+    provider_traits::{IssueProvider, RepoProvider},
+    wrappers::{IssueId, RepoId},
+};
+use log::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{actions::TemplateContext, schedule::Interval};
+
+fn default_stale_label() -> String {
+    "stale".into()
+}
+
+/// Which kinds of issue a [`StaleSweep`] looks at.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleTarget {
+    #[default]
+    All,
+    Issues,
+    PullRequests,
+}
+
+/// Marks open issues and pull requests that have had no activity for a while as stale, and optionally closes them if
+/// they remain inactive.
+///
+/// An issue is marked as stale by adding `label` (and posting `comment`, if given) once it has not been updated for
+/// `stale_after`. Marking an issue updates it, so `close_after` is measured from the time it was marked. Any other
+/// activity on the issue also resets the clock, but the label is not removed automatically; use a rule for that.
+///
+/// Comments are templates, and are rendered with the `issue.*`, `pr.*` and `repo.*` values described in
+/// [`crate::actions::TemplateContext`], along with `stale.inactive_days`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StaleSweep {
+    #[serde(default)]
+    pub applies_to: StaleTarget,
+    pub stale_after: Interval,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_after: Option<Interval>,
+    #[serde(default = "default_stale_label")]
+    pub label: String,
+    /// Issues with any of these labels are never marked as stale
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exempt_labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub close_comment: Option<String>,
+    /// Draft pull requests are ignored unless this is set
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub include_drafts: bool,
+}
+
+/// What a [`StaleSweep`] decided to do with an issue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StaleVerdict {
+    Ignore,
+    MarkStale,
+    Close,
+}
+
+/// The outcome of a single sweep of a repository.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SweepSummary {
+    pub checked: usize,
+    pub marked: usize,
+    pub closed: usize,
+    pub failed: usize,
+}
+
+impl Display for SweepSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} checked, {} marked stale, {} closed, {} failed",
+            self.checked, self.marked, self.closed, self.failed
+        )
+    }
+}
+
+impl StaleSweep {
+    pub fn new(stale_after: Interval) -> Self {
+        Self {
+            applies_to: StaleTarget::default(),
+            stale_after,
+            close_after: None,
+            label: default_stale_label(),
+            exempt_labels: Vec::new(),
+            comment: None,
+            close_comment: None,
+            include_drafts: false,
+        }
+    }
+
+    pub fn applies_to(mut self, target: StaleTarget) -> Self {
+        self.applies_to = target;
+        self
+    }
+
+    pub fn close_after(mut self, interval: Interval) -> Self {
+        self.close_after = Some(interval);
+        self
+    }
+
+    pub fn with_label<S: Into<String>>(mut self, label: S) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    pub fn exempt<S: Into<String>>(mut self, label: S) -> Self {
+        self.exempt_labels.push(label.into());
+        self
+    }
+
+    pub fn with_comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn with_close_comment<S: Into<String>>(mut self, comment: S) -> Self {
+        self.close_comment = Some(comment.into());
+        self
+    }
+
+    /// Decides what to do with the given issue at time `now`.
+    pub fn evaluate(&self, issue: &Issue, now: Timestamp) -> StaleVerdict {
+        let is_pr = issue.pull_request.is_some();
+        let in_scope = match self.applies_to {
+            StaleTarget::All => true,
+            StaleTarget::Issues => !is_pr,
+            StaleTarget::PullRequests => is_pr,
+        };
+        if !in_scope || (is_pr && issue.draft == Some(true) && !self.include_drafts) {
+            return StaleVerdict::Ignore;
+        }
+        if issue.labels.iter().any(|l| self.exempt_labels.contains(&l.name)) {
+            return StaleVerdict::Ignore;
+        }
+        let inactive_for = match inactive_secs(issue, now) {
+            Some(secs) => secs,
+            None => return StaleVerdict::Ignore,
+        };
+        if issue.labels.iter().any(|l| l.name == self.label) {
+            match self.close_after {
+                Some(limit) if inactive_for >= limit.as_secs() => StaleVerdict::Close,
+                _ => StaleVerdict::Ignore,
+            }
+        } else if inactive_for >= self.stale_after.as_secs() {
+            StaleVerdict::MarkStale
+        } else {
+            StaleVerdict::Ignore
+        }
+    }
+
+    /// Sweeps all the open issues in the repository, marking and closing them as required.
+    pub async fn run<P>(&self, provider: &P, repo: &RepoId) -> Result<SweepSummary, GithubProviderError>
+    where P: RepoProvider + IssueProvider + Sync + ?Sized {
+        let issues = provider.fetch_open_issues(repo.owner(), repo.repo()).await?;
+        let now = Timestamp::from(std::time::SystemTime::now());
+        let mut summary = SweepSummary {
+            checked: issues.len(),
+            ..Default::default()
+        };
+        for issue in issues {
+            let verdict = self.evaluate(&issue, now);
+            if verdict == StaleVerdict::Ignore {
+                continue;
+            }
+            let id = IssueId::new(repo.owner(), repo.repo(), issue.number);
+            let inactive_days = inactive_secs(&issue, now).unwrap_or_default() / 86_400;
+            let ctx = TemplateContext::from_issue(repo, &issue).with("stale.inactive_days", inactive_days);
+            let result = match verdict {
+                StaleVerdict::MarkStale => self.mark_stale(provider, &id, &ctx).await,
+                StaleVerdict::Close => self.close(provider, &id, issue.pull_request.is_some(), &ctx).await,
+                StaleVerdict::Ignore => Ok(()),
+            };
+            match (result, verdict) {
+                (Ok(()), StaleVerdict::MarkStale) => summary.marked += 1,
+                (Ok(()), _) => summary.closed += 1,
+                (Err(e), _) => {
+                    warn!("🧹 Could not process stale issue {id}. {e}");
+                    summary.failed += 1;
+                },
+            }
+        }
+        Ok(summary)
+    }
+
+    async fn mark_stale<P: IssueProvider + Sync + ?Sized>(
+        &self,
+        provider: &P,
+        id: &IssueId,
+        ctx: &TemplateContext,
+    ) -> Result<(), GithubProviderError> {
+        let comment = self.comment.as_ref().map(|c| ctx.render(c));
+        provider.add_label(id, &self.label).await?;
+        if let Some(comment) = &comment {
+            provider.add_comment(id, comment).await?;
+        }
+        info!("🧹 Marked {id} as stale");
+        Ok(())
+    }
+
+    async fn close<P: IssueProvider + Sync + ?Sized>(
+        &self,
+        provider: &P,
+        id: &IssueId,
+        is_pr: bool,
+        ctx: &TemplateContext,
+    ) -> Result<(), GithubProviderError> {
+        let comment = self.close_comment.as_ref().map(|c| ctx.render(c));
+        if let Some(comment) = &comment {
+            provider.add_comment(id, comment).await?;
+        }
+        let reason = if is_pr { None } else { Some(StateReason::NotPlanned) };
+        provider.set_state(id, State::Closed, reason).await?;
+        info!("🧹 Closed stale issue {id}");
+        Ok(())
+    }
+}
+
+/// The number of seconds since the issue was last updated, or `None` if the timestamp cannot be read.
+fn inactive_secs(issue: &Issue, now: Timestamp) -> Option<u64> {
+    match issue.updated_at.parse::<Timestamp>() {
+        Ok(updated) => Some((now - updated).to_std().map(|d| d.as_secs()).unwrap_or_default()),
+        Err(e) => {
+            warn!("🧹 Issue #{} has an invalid updated_at timestamp. {e}", issue.number);
+            None
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use github_pilot_api::models::{Issue, IssuePullRequest, Label, Timestamp};
+
+    use super::{StaleSweep, StaleTarget, StaleVerdict};
+    use crate::schedule::Interval;
+
+    // issue.json was last updated on 2022-01-05T13:23:32Z
+    fn issue() -> Issue {
+        let data = include_str!("../../../github-api/src/test_data/issue.json");
+        let mut issue: Issue = serde_json::from_str(data).unwrap();
+        issue.labels.clear();
+        issue
+    }
+
+    fn label(name: &str) -> Label {
+        Label {
+            id: 1,
+            node_id: "LA_1".into(),
+            url: "https://api.github.com/repos/tari-project/tari-crypto/labels/x".into(),
+            name: name.into(),
+            description: None,
+            color: "ffffff".into(),
+            default: false,
+        }
+    }
+
+    fn days_later(days: u64) -> Timestamp {
+        Timestamp::from(UNIX_EPOCH + Duration::from_secs(1_641_389_012 + days * 86_400))
+    }
+
+    #[test]
+    fn marks_inactive_issues() {
+        let sweep = StaleSweep::new(Interval::from_days(30));
+        let issue = issue();
+        assert_eq!(sweep.evaluate(&issue, days_later(29)), StaleVerdict::Ignore);
+        assert_eq!(sweep.evaluate(&issue, days_later(30)), StaleVerdict::MarkStale);
+    }
+
+    #[test]
+    fn closes_stale_issues() {
+        let mut issue = issue();
+        issue.labels.push(label("stale"));
+        let sweep = StaleSweep::new(Interval::from_days(30));
+        // Never closes without close_after
+        assert_eq!(sweep.evaluate(&issue, days_later(365)), StaleVerdict::Ignore);
+        let sweep = sweep.close_after(Interval::from_days(7));
+        assert_eq!(sweep.evaluate(&issue, days_later(6)), StaleVerdict::Ignore);
+        assert_eq!(sweep.evaluate(&issue, days_later(7)), StaleVerdict::Close);
+    }
+
+    #[test]
+    fn exempt_labels_and_targets() {
+        let mut issue = issue();
+        let sweep = StaleSweep::new(Interval::from_days(1)).exempt("pinned");
+        assert_eq!(sweep.evaluate(&issue, days_later(10)), StaleVerdict::MarkStale);
+        issue.labels.push(label("pinned"));
+        assert_eq!(sweep.evaluate(&issue, days_later(10)), StaleVerdict::Ignore);
+        issue.labels.clear();
+
+        let prs_only = StaleSweep::new(Interval::from_days(1)).applies_to(StaleTarget::PullRequests);
+        assert_eq!(prs_only.evaluate(&issue, days_later(10)), StaleVerdict::Ignore);
+        issue.pull_request = Some(Box::new(IssuePullRequest {
+            diff_url: None,
+            html_url: None,
+            patch_url: None,
+            url: None,
+            merged_at: None,
+        }));
+        assert_eq!(prs_only.evaluate(&issue, days_later(10)), StaleVerdict::MarkStale);
+        issue.draft = Some(true);
+        assert_eq!(prs_only.evaluate(&issue, days_later(10)), StaleVerdict::Ignore);
+    }
+}
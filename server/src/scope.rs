@@ -0,0 +1,87 @@
+//! Limits rules to a subset of the repositories and organisations that send webhooks to the server.
+//!
+//! A [`Scope`] is a list of glob patterns to include and a list to exclude. A name is in scope if it matches at least
+//! one of the `include` patterns (or there aren't any), and none of the `exclude` patterns. Matching is
+//! case-insensitive, since Github names are.
+//!
+//! ```yaml
+//! rules:
+//!   - name: Label large PRs
+//!     repos:
+//!       include: ["tari-project/tari*"]
+//!       exclude: ["tari-project/tari-crypto"]
+//!     orgs:
+//!       include: [tari-project]
+//!     when:
+//!       - pull_request:
+//!           size_greater_than: large
+//!     execute:
+//!       - github:
+//!           add_label: CR-too_long
+//! ```
+use serde::{Deserialize, Serialize};
+
+use crate::predicates::GlobPattern;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Scope {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    include: Vec<GlobPattern>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    exclude: Vec<GlobPattern>,
+}
+
+impl Scope {
+    /// A scope that includes everything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn include<S: Into<GlobPattern>>(mut self, pattern: S) -> Self {
+        self.include.push(pattern.into());
+        self
+    }
+
+    pub fn exclude<S: Into<GlobPattern>>(mut self, pattern: S) -> Self {
+        self.exclude.push(pattern.into());
+        self
+    }
+
+    pub fn includes(&self) -> &[GlobPattern] {
+        self.include.as_slice()
+    }
+
+    pub fn excludes(&self) -> &[GlobPattern] {
+        self.exclude.as_slice()
+    }
+
+    /// True if the scope has no patterns, and so includes everything.
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty()
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|p| p.is_match_ignore_case(name))) &&
+            !self.exclude.iter().any(|p| p.is_match_ignore_case(name))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Scope;
+
+    #[test]
+    fn include_and_exclude() {
+        assert!(Scope::new().matches("tari-project/tari"));
+        let scope = Scope::new()
+            .include("tari-project/*")
+            .exclude("tari-project/tari-crypto");
+        assert!(scope.matches("tari-project/tari"));
+        assert!(scope.matches("Tari-Project/Tari"));
+        assert!(!scope.matches("tari-project/tari-crypto"));
+        assert!(!scope.matches("other-org/tari"));
+        let scope = Scope::new().exclude("*/legacy-*");
+        assert!(scope.matches("tari-project/tari"));
+        assert!(!scope.matches("tari-project/legacy-wallet"));
+    }
+}
@@ -1,9 +1,11 @@
 #[cfg(test)]
 mod test {
+    use github_pilot_api::models::{LockReason, StateReason, StatusEventState};
+
     use crate::{
-        actions::Actions,
+        actions::{Actions, ReviewRequest, StateChange},
         heuristics::pull_requests::PullRequestSize,
-        predicates::{Predicate, PullRequest, PullRequestComment, StatusCheck},
+        predicates::{Issue, Predicate, PullRequest, PullRequestComment, Push, StatusCheck},
     };
 
     #[test]
@@ -22,6 +24,74 @@ mod test {
         assert_eq!(yaml, expected);
     }
 
+    #[test]
+    fn serialize_comment_action_to_yaml() {
+        let action = Actions::github()
+            .add_comment("{{acks.current}}/{{acks.total}} ACKs so far")
+            .build();
+        let yaml = serde_yaml::to_string(&action).unwrap();
+        let expected = "---\ngithub:\n  add_comment: \"{{acks.current}}/{{acks.total}} ACKs so far\"\n";
+        assert_eq!(yaml, expected);
+        let action2: Actions = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(action, action2);
+    }
+
+    #[test]
+    fn deserialize_review_request_action_from_yaml() {
+        let yaml = "github:\n  request_review:\n    reviewers: [sdbondi]\n    teams: [core-devs]\n";
+        let action: Actions = serde_yaml::from_str(yaml).unwrap();
+        let expected = Actions::github()
+            .request_review(ReviewRequest::new().reviewer("sdbondi").team("core-devs"))
+            .build();
+        assert_eq!(action, expected);
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(
+            json,
+            r#"{"github":{"request_review":{"reviewers":["sdbondi"],"teams":["core-devs"]}}}"#
+        );
+    }
+
+    #[test]
+    fn serialize_code_owners_actions_to_yaml() {
+        let action = Actions::github().request_code_owner_reviews().build();
+        let yaml = serde_yaml::to_string(&action).unwrap();
+        assert_eq!(yaml, "---\ngithub:\n  code_owners: request_review\n");
+        let action = Actions::github().mention_code_owners("cc {{code_owners}}").build();
+        let yaml = serde_yaml::to_string(&action).unwrap();
+        assert_eq!(
+            yaml,
+            "---\ngithub:\n  code_owners:\n    mention: \"cc {{code_owners}}\"\n"
+        );
+        let action2: Actions = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(action, action2);
+    }
+
+    #[test]
+    fn serialize_close_action_to_yaml() {
+        let change = StateChange::new()
+            .with_comment("PRs from forks may not target main")
+            .with_reason(StateReason::NotPlanned);
+        let action = Actions::github().close(change).build();
+        let yaml = serde_yaml::to_string(&action).unwrap();
+        let expected =
+            "---\ngithub:\n  close:\n    comment: PRs from forks may not target main\n    reason: not_planned\n";
+        assert_eq!(yaml, expected);
+        let action2: Actions = serde_yaml::from_str("github:\n  close: {}\n").unwrap();
+        assert_eq!(action2, Actions::github().close(StateChange::new()).build());
+    }
+
+    #[test]
+    fn serialize_lock_and_draft_actions_to_json() {
+        let action = Actions::github().lock(Some(LockReason::TooHeated)).build();
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"github":{"lock":"too heated"}}"#);
+        let action = Actions::github().convert_to_draft().build();
+        let json = serde_json::to_string(&action).unwrap();
+        assert_eq!(json, r#"{"github":"convert_to_draft"}"#);
+        let action: Actions = serde_json::from_str(r#"{"github":{"lock":null}}"#).unwrap();
+        assert_eq!(action, Actions::github().lock(None).build());
+    }
+
     #[test]
     fn serialize_closure_action_to_json() {
         let action = Actions::closure()
@@ -157,4 +227,92 @@ merge:
         let expected = "---\npull_request:\n  assigned: Sarah\n";
         assert_eq!(yaml, expected);
     }
+
+    #[test]
+    fn serialize_combinator_predicate_to_yaml() {
+        let pred = Predicate::all([
+            PullRequest::opened().into(),
+            Predicate::not(PullRequestComment::from("dependabot")),
+        ]);
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let expected =
+            "---\nall:\n  - pull_request: opened\n  - not:\n      pull_request_comment:\n        added: dependabot\n";
+        assert_eq!(yaml, expected);
+        let round_trip: Predicate = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+
+    #[test]
+    fn serialize_combinator_predicate_to_json() {
+        let pred = Predicate::any([StatusCheck::suite_success().into(), PullRequest::approved().into()]);
+        let json = serde_json::to_string(&pred).unwrap();
+        let expected = r#"{"any":[{"status_check":"check_suite_success"},{"pull_request":"approved"}]}"#;
+        assert_eq!(json, expected);
+        let round_trip: Predicate = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+
+    #[test]
+    fn serialize_issue_predicate_to_yaml() {
+        let pred = Predicate::Issue(Issue::labeled_with("bug"));
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let expected = "---\nissue:\n  labeled: bug\n";
+        assert_eq!(yaml, expected);
+        let pred = Predicate::Issue(Issue::title_matches("^\\[wallet\\]"));
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let round_trip: Predicate = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+
+    #[test]
+    fn serialize_issue_predicate_to_json() {
+        let pred = Predicate::Issue(Issue::opened());
+        let json = serde_json::to_string(&pred).unwrap();
+        let expected = r#"{"issue":"opened"}"#;
+        assert_eq!(json, expected);
+        let pred = Predicate::Issue(Issue::title_matches("^RFC"));
+        let json = serde_json::to_string(&pred).unwrap();
+        let expected = r#"{"issue":{"title_matches":"^RFC"}}"#;
+        assert_eq!(json, expected);
+    }
+
+    #[test]
+    fn serialize_push_predicate_to_yaml() {
+        let pred = Predicate::all([
+            Push::to_branch("development").into(),
+            Push::touches("base_layer/core/**").into(),
+        ]);
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let expected =
+            "---\nall:\n  - push:\n      branch: development\n  - push:\n      touches: base_layer/core/**\n";
+        assert_eq!(yaml, expected);
+        let round_trip: Predicate = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+    #[test]
+    fn serialize_comment_command_predicate_to_yaml() {
+        let pred = Predicate::PullRequestComment(PullRequestComment::command("merge"));
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let expected = "---\npull_request_comment:\n  command: merge\n";
+        assert_eq!(yaml, expected);
+        let round_trip: Predicate = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+    #[test]
+    fn serialize_commit_status_predicate_to_yaml() {
+        let pred = Predicate::StatusCheck(StatusCheck::commit_status("ci/circleci: *", StatusEventState::Failure));
+        let yaml = serde_yaml::to_string(&pred).unwrap();
+        let expected = "---\nstatus_check:\n  commit_status:\n    context: \"ci/circleci: *\"\n    state: failure\n";
+        assert_eq!(yaml, expected);
+        let round_trip: Predicate = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(round_trip, pred);
+    }
+
+    #[test]
+    fn serialize_check_run_predicate_to_json() {
+        let pred = Predicate::StatusCheck(StatusCheck::check_run_failure("clippy"));
+        let json = serde_json::to_string(&pred).unwrap();
+        let expected = r#"{"status_check":{"check_run_failure":"clippy"}}"#;
+        assert_eq!(json, expected);
+    }
 }
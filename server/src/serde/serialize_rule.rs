@@ -2,8 +2,9 @@
 mod test {
     use crate::{
         actions::Actions,
+        events::{Event, ProgressConstraint, SubscriptionBuilder},
         heuristics::pull_requests::{PullRequestComplexity, PullRequestSize},
-        predicates::{PullRequest, PullRequestComment, StatusCheck},
+        predicates::{Predicate, PullRequest, PullRequestComment, StatusCheck},
         rule_set::RuleSet,
         rules::{Rule, RuleBuilder},
     };
@@ -42,6 +43,15 @@ mod test {
                 .when(StatusCheck::suite_success())
                 .execute(Actions::auto_merge().with_min_acks(1).auto_merge().build())
                 .submit(),
+            RuleBuilder::new("Combinators")
+                .when_all([
+                    PullRequest::labeled_with("P-merge").into(),
+                    Predicate::not(PullRequest::converted_to_draft()),
+                ])
+                .when_any([PullRequest::opened().into(), PullRequest::reopened().into()])
+                .when_not(PullRequestComment::from("dependabot"))
+                .execute(Actions::github().add_label("combined").build())
+                .submit(),
         ]
     }
 
@@ -68,6 +78,44 @@ mod test {
         assert_eq!(rule_set, RuleSet::from(rules()))
     }
 
+    #[test]
+    fn subscriptions_round_trip_yaml() {
+        let yaml = r#"---
+rules: []
+on:
+  - name: Ask for ACKs
+    event: acks_needed
+    and:
+      - progress:
+          max_progress: 99
+    then:
+      - github:
+          add_label: P-acks_required
+  - name: Acks achieved
+    event: acks_threshold_reached
+    then:
+      - github:
+          remove_label: P-acks_required
+"#;
+        let mut expected = RuleSet::default();
+        expected.add_subscription(
+            SubscriptionBuilder::on("Ask for ACKs", Event::AcksNeeded)
+                .and(ProgressConstraint::new().max_progress(99))
+                .then(Actions::github().add_label("P-acks_required").build())
+                .submit(),
+        );
+        expected.add_subscription(
+            SubscriptionBuilder::on("Acks achieved", Event::AcksThresholdReached)
+                .then(Actions::github().remove_label("P-acks_required").build())
+                .submit(),
+        );
+        let rule_set: RuleSet = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(rule_set, expected);
+        // serde_yaml quotes the `on` key, since YAML 1.1 parsers would read it as a boolean
+        let out = serde_yaml::to_string(&rule_set).unwrap();
+        assert_eq!(out, yaml.replace("\non:", "\n\"on\":"));
+    }
+
     #[test]
     fn deserialize_rules_from_yaml() {
         let yaml = include_str!("sample_rules.yaml");
@@ -7,22 +7,50 @@ use notify::{RecursiveMode, Watcher};
 
 use crate::{
     config::ServerConfig,
+    deliveries::DeliveryCache,
+    dry_run,
     error::ServerError,
     file_watch::async_watch,
-    load_rules::{load_rules, load_subscriptions},
+    journal::Journal,
+    load_rules::{load_rule_set, RuleSetSource},
+    metrics,
     pub_sub::PubSubActor,
-    routes::{github_webhook, health},
+    routes::{
+        disable_rule,
+        dry_run_report,
+        enable_rule,
+        github_webhook,
+        health,
+        list_rules,
+        list_subscriptions,
+        metrics_report,
+        recent_executions,
+        reload_rules,
+        replay_events,
+    },
+    schedule::SchedulerActor,
 };
 
 pub async fn run_server(config: ServerConfig) -> Result<(), ServerError> {
+    dry_run::set_enabled(config.dry_run);
+    github_pilot_api::api::set_request_observer(metrics::record_github_request);
+    if config.dry_run {
+        warn!("🧪 Dry-run mode is ON. Github will not be modified. Intended changes are logged instead.");
+    }
     let pubsub = PubSubActor::new().start();
     let rule_path = config.rule_set_path.as_str();
-    let num_rules = load_rules(pubsub.clone(), rule_path).await?;
-    info!("📄 {num_rules} Rules loaded");
-    let num_subs = load_subscriptions(pubsub.clone()).await?;
-    info!("📄 {num_subs} Subscriptions loaded");
+    let scheduler = SchedulerActor::default().start();
+    let summary = load_rule_set(pubsub.clone(), scheduler.clone(), rule_path).await?;
+    info!("📄 {} Rules loaded", summary.rules);
+    info!("📄 {} Subscriptions loaded", summary.subscriptions);
+    info!("⏰ {} Schedules loaded", summary.schedules);
 
-    let mut watcher = async_watch(pubsub.clone(), rule_path);
+    let source = Data::new(RuleSetSource {
+        path: rule_path.to_string(),
+        pubsub: pubsub.clone(),
+        scheduler: scheduler.clone(),
+    });
+    let mut watcher = async_watch(pubsub.clone(), scheduler, rule_path);
     if let Some(w) = &mut watcher {
         let path = Path::new(rule_path);
         if let Err(e) = w.watch(path, RecursiveMode::NonRecursive) {
@@ -30,12 +58,47 @@ pub async fn run_server(config: ServerConfig) -> Result<(), ServerError> {
         }
     }
 
+    let journal = match config.journal_path.as_deref().map(Journal::open).transpose() {
+        Ok(Some(journal)) => {
+            info!("📓 Journaling webhook deliveries to {}", journal.path().display());
+            Some(Data::new(journal))
+        },
+        Ok(None) => None,
+        Err(e) => {
+            warn!("📓 Could not open the event journal. Deliveries will not be journaled. {e}");
+            None
+        },
+    };
+
+    let deliveries = Data::new(DeliveryCache::new(config.delivery_cache_size, config.delivery_ttl));
+    if let Some(journal) = &journal {
+        let seeded = deliveries.seed_from_journal(journal);
+        debug!("📓 Loaded {seeded} recent delivery ids from the journal");
+    }
+
     HttpServer::new(move || {
-        App::new()
+        let mut app = App::new()
             .app_data(Data::new(pubsub.clone()))
-            .wrap(Logger::new("%t (%D ms) %s %a %{Host}i %U").log_target("ghp_server::logger"))
+            .app_data(deliveries.clone())
+            .app_data(source.clone());
+        if let Some(journal) = &journal {
+            app = app.app_data(journal.clone());
+        }
+        app.wrap(Logger::new("%t (%D ms) %s %a %{Host}i %U").log_target("ghp_server::logger"))
             .service(health)
+            .service(metrics_report)
             .service(web::scope("/github").service(github_webhook))
+            .service(
+                web::scope("/admin")
+                    .service(replay_events)
+                    .service(dry_run_report)
+                    .service(list_rules)
+                    .service(list_subscriptions)
+                    .service(reload_rules)
+                    .service(enable_rule)
+                    .service(disable_rule)
+                    .service(recent_executions),
+            )
     })
     .keep_alive(KeepAlive::Timeout(Duration::from_secs(600)))
     .bind((config.host.as_str(), config.port))?
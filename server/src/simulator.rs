@@ -0,0 +1,70 @@
+//! Evaluates a set of rules against a single Github event, without executing any actions.
+//!
+//! This lets you check what a rule set _would_ do with a webhook payload (e.g. one saved from the event journal, or
+//! copied from the "Recent Deliveries" tab of a Github webhook) before deploying it. Nothing is sent to Github.
+//!
+//! Only the rules are evaluated. Subscriptions that might be triggered by the actions' broadcast events, and the
+//! outcome of the actions themselves (which usually depends on the state of the PR on Github) are not simulated.
+use crate::{actions::Actions, predicates::Predicate, pub_sub::GithubEventMessage, rules::Rule};
+
+/// A rule that matched the simulated event.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub rule: String,
+    /// The first predicate in the rule that matched the event.
+    pub predicate: Predicate,
+    /// The actions that would be executed.
+    pub actions: Vec<Actions>,
+    /// The actions that would be executed if all of [`RuleMatch::actions`] succeed.
+    pub then_actions: Vec<Actions>,
+}
+
+/// Evaluates every rule against the event, in order, and returns the ones that matched.
+pub fn simulate(rules: &[Rule], event: &GithubEventMessage) -> Vec<RuleMatch> {
+    rules
+        .iter()
+        .filter_map(|rule| {
+            rule.matches(event).map(|predicate| RuleMatch {
+                rule: rule.name().to_string(),
+                predicate: predicate.as_ref().clone(),
+                actions: rule.actions().map(|a| a.as_ref().clone()).collect(),
+                then_actions: rule.then_actions().map(|a| a.as_ref().clone()).collect(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use github_pilot_api::GithubEvent;
+
+    use super::simulate;
+    use crate::{
+        actions::Actions,
+        predicates::{Issue, Predicate, PullRequest},
+        pub_sub::GithubEventMessage,
+        rules::RuleBuilder,
+    };
+
+    #[test]
+    fn simulate_pull_request_event() {
+        let data = include_str!("../test-data/pr_event.json");
+        let event = GithubEvent::try_from_webhook_info("pull_request", data).unwrap();
+        let event = GithubEventMessage::new("pull_request", event);
+        let rules = vec![
+            RuleBuilder::new("issues").when(Issue::opened()).submit(),
+            RuleBuilder::new("new PRs")
+                .when(PullRequest::closed_with_unmerged_commits())
+                .when(PullRequest::opened())
+                .execute(Actions::github().add_label("T-new").build())
+                .then(Actions::github().add_comment("Thanks!").build())
+                .submit(),
+        ];
+        let matches = simulate(&rules, &event);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].rule, "new PRs");
+        assert_eq!(matches[0].predicate, Predicate::from(PullRequest::opened()));
+        assert_eq!(matches[0].actions, vec![Actions::github().add_label("T-new").build()]);
+        assert_eq!(matches[0].then_actions.len(), 1);
+    }
+}
@@ -36,6 +36,24 @@ pub fn get_secret() -> Result<String, ServerError> {
         .map_err(|_| ServerError::InvalidSecret("GH_PILOT_WEBHOOK_SECRET is not set".into()))
 }
 
+/// Checks the request's `Authorization: Bearer <token>` header against `GH_PILOT_ADMIN_TOKEN`. If the token is not
+/// configured, the admin API is disabled and every request is rejected.
+pub fn check_admin_token(headers: &HeaderMap) -> Result<(), ServerError> {
+    let expected = std::env::var("GH_PILOT_ADMIN_TOKEN")
+        .ok()
+        .filter(|t| !t.is_empty())
+        .ok_or(ServerError::AdminApiDisabled)?;
+    let token = headers
+        .get("authorization")
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| h.strip_prefix("Bearer "))
+        .ok_or(ServerError::Unauthorized)?;
+    match expected.as_bytes().ct_eq(token.as_bytes()).unwrap_u8() {
+        1 => Ok(()),
+        _ => Err(ServerError::Unauthorized),
+    }
+}
+
 pub fn extract_signature(headers: &HeaderMap) -> Result<&str, ServerError> {
     headers
         .get("x-hub-signature-256")